@@ -1,18 +1,12 @@
 use crate::error::CliError;
 use serde::Deserialize;
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
 pub struct Config {
     #[serde(default)]
     pub auto_update: bool,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self { auto_update: false }
-    }
-}
-
 impl Config {
     pub fn load(path: Option<&std::path::Path>) -> Result<Self, CliError> {
         let (config_path, is_explicit) = match path {
@@ -62,10 +62,10 @@ impl From<httpdate::Error> for CliError {
     fn from(err: httpdate::Error) -> Self {
         // Wrap httpdate::Error in a generic IO error, as it's a parsing failure
         // during the update process. This avoids adding another error variant.
-        CliError::Io(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("Failed to parse HTTP date: {}", err),
-        ))
+        CliError::Io(std::io::Error::other(format!(
+            "Failed to parse HTTP date: {}",
+            err
+        )))
     }
 }
 
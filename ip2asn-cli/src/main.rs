@@ -22,6 +22,10 @@
 //! ip2asn 8.8.8.8
 //! 15169 | 8.8.8.8 | 8.8.8.0/24 | GOOGLE | US
 //!
+//! # Addresses in special-purpose ranges are labelled when not found
+//! ip2asn 10.1.2.3
+//! 10.1.2.3 | Not Found | private
+//!
 //! # Look up multiple IPs from stdin
 //! cat ips.txt | ip2asn
 //! 15169 | 8.8.8.8 | 8.8.8.0/24 | GOOGLE | US
@@ -130,6 +134,7 @@ struct LookupArgs {
     json: bool,
 }
 
+use ip2asn::special::SpecialPurpose;
 use ip2asn::IpAsnMap;
 use serde::Serialize;
 
@@ -142,6 +147,10 @@ struct JsonOutput {
     found: bool,
     /// The ASN information, present only if a record was found.
     info: Option<ip2asn::AsnInfo>,
+    /// The special-purpose category of the IP, such as `private`, present
+    /// only if it is in a special-purpose range.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    special: Option<SpecialPurpose>,
 }
 
 /// The main function of the application.
@@ -267,15 +276,15 @@ fn check_for_updates(
     eprintln!("Checking for dataset updates...");
     io::stderr().flush()?;
     let client = reqwest::blocking::Client::new();
-    let response = client.head(&get_data_url()).send()?;
+    let response = client.head(get_data_url()).send()?;
     response.error_for_status_ref()?;
 
     if let Some(last_modified) = response.headers().get(reqwest::header::LAST_MODIFIED) {
         let last_modified_str = last_modified.to_str().map_err(|e| {
-            CliError::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Invalid Last-Modified header: {}", e),
-            ))
+            CliError::Io(std::io::Error::other(format!(
+                "Invalid Last-Modified header: {}",
+                e
+            )))
         })?;
         let remote_mtime = httpdate::parse_http_date(last_modified_str)?;
         debug!(?remote_mtime, "Parsed remote mtime");
@@ -313,12 +322,9 @@ fn run_update(cache_dir_path: Option<&std::path::Path>) -> Result<(), CliError>
     let mut response = reqwest::blocking::get(get_data_url())?;
     response.error_for_status_ref()?;
 
-    let total_size = response.content_length().ok_or_else(|| {
-        CliError::Io(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Failed to get content length",
-        ))
-    })?;
+    let total_size = response
+        .content_length()
+        .ok_or_else(|| CliError::Io(std::io::Error::other("Failed to get content length")))?;
 
     let mut file = fs::File::create(&data_path)?;
 
@@ -329,8 +335,7 @@ fn run_update(cache_dir_path: Option<&std::path::Path>) -> Result<(), CliError>
         pb.set_style(
             indicatif::ProgressStyle::default_bar()
                 .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
-                .map_err(|e| CliError::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
+                .map_err(|e| CliError::Io(std::io::Error::other(
                     format!("Indicatif error: {}", e),
                 )))?
                 .progress_chars("#>-"),
@@ -354,14 +359,19 @@ fn perform_lookup(map: &IpAsnMap, ip_str: &str, json: bool) -> Result<(), CliErr
     }
 
     if json {
-        let (ip_str, result) = match trimmed_ip.parse::<IpAddr>() {
-            Ok(ip) => (ip.to_string(), map.lookup_owned(ip)),
-            Err(_) => (trimmed_ip.to_string(), None),
+        let (ip_str, result, special) = match trimmed_ip.parse::<IpAddr>() {
+            Ok(ip) => (
+                ip.to_string(),
+                map.lookup_owned(ip),
+                map.classify(ip).map(|range| range.purpose()),
+            ),
+            Err(_) => (trimmed_ip.to_string(), None, None),
         };
         let output = JsonOutput {
             ip: ip_str,
             found: result.is_some(),
             info: result,
+            special,
         };
         println!(
             "{}",
@@ -377,9 +387,10 @@ fn perform_lookup(map: &IpAsnMap, ip_str: &str, json: bool) -> Result<(), CliErr
                         info.asn, ip, info.network, info.organization, info.country_code
                     );
                 }
-                None => {
-                    println!("{} | Not Found", ip);
-                }
+                None => match map.classify(ip) {
+                    Some(range) => println!("{} | Not Found | {}", ip, range.purpose()),
+                    None => println!("{} | Not Found", ip),
+                },
             },
             Err(_) => {
                 eprintln!("Error: Invalid IP address '{}'", trimmed_ip);
@@ -104,7 +104,16 @@ fn test_lookup_not_found(test_env_populated: TestEnv) {
     cmd.arg("127.0.0.1");
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("127.0.0.1 | Not Found"));
+        .stdout(predicate::str::contains("127.0.0.1 | Not Found | loopback"));
+}
+
+#[rstest]
+fn test_lookup_not_found_ordinary_address(test_env_populated: TestEnv) {
+    let mut cmd = test_env_populated.cmd();
+    cmd.arg("9.9.9.9");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::diff("9.9.9.9 | Not Found\n"));
 }
 
 #[rstest]
@@ -143,10 +152,10 @@ fn test_lookup_json_output(test_env_populated: TestEnv) {
     cmd.assert()
         .success()
         .stdout(predicate::str::contains(
-            r#"{"ip":"1.1.1.1","found":true,"info":{"network":"1.1.1.0/24","asn":13335,"country_code":"US","organization":"CLOUDFLARENET"}}"#,
+            r#"{"ip":"1.1.1.1","found":true,"info":{"network":"1.1.1.0/24","asn":13335,"country_code":"US","organization":"CLOUDFLARENET","organization_parts":{"handle":"CLOUDFLARENET","description":""}}}"#,
         ))
         .stdout(predicate::str::contains(
-            r#"{"ip":"127.0.0.1","found":false,"info":null}"#,
+            r#"{"ip":"127.0.0.1","found":false,"info":null,"special":"loopback"}"#,
         ));
 }
 
@@ -193,7 +202,6 @@ fn test_run_lookup_dataset_missing(test_env_unpopulated: TestEnv) {
 #[cfg(test)]
 mod auto_update_tests {
     use super::*;
-    use mockito;
 
     #[rstest]
     #[tokio::test]
@@ -448,7 +456,6 @@ mod auto_update_tests {
 #[cfg(test)]
 mod command_tests {
     use super::*;
-    use mockito;
 
     #[rstest]
     #[tokio::test]
@@ -0,0 +1,154 @@
+#ifndef IP2ASN_H
+#define IP2ASN_H
+
+/* Generated by cbindgen from src/lib.rs. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * How lines that cannot be parsed are handled when a map is built.
+ *
+ * Functions take the mode as a `uint32_t`, and reject values other than
+ * these.
+ */
+typedef enum Ip2AsnMode {
+  /**
+   * Skip lines that cannot be parsed.
+   */
+  Ip2AsnMode_Resilient = 0,
+  /**
+   * Fail on the first line that cannot be parsed.
+   */
+  Ip2AsnMode_Strict = 1,
+} Ip2AsnMode;
+
+/**
+ * A map from IP addresses to ASN information.
+ *
+ * It is created by `ip2asn_map_from_path` or `ip2asn_map_from_buffer`, and
+ * must be freed with `ip2asn_map_free`. A map can be looked up from several
+ * threads at once.
+ */
+typedef struct Ip2AsnMap Ip2AsnMap;
+
+/**
+ * The ASN information for an address, filled in by `ip2asn_lookup_v4` and
+ * `ip2asn_lookup_v6`.
+ */
+typedef struct Ip2AsnInfo {
+  /**
+   * The Autonomous System Number.
+   */
+  uint32_t asn;
+  /**
+   * The two-letter country code, such as `US`, terminated by a NUL byte.
+   */
+  char country_code[3];
+  /**
+   * The prefix length of the matching network.
+   */
+  uint8_t prefix_len;
+  /**
+   * The address of the matching network in network byte order. IPv4
+   * networks use the first 4 bytes, and the rest are zero.
+   */
+  uint8_t network[16];
+  /**
+   * The organization name, which is not terminated by a NUL byte. It
+   * points into the map, and is valid until the map is freed.
+   */
+  const char *organization;
+  /**
+   * The length of the organization name in bytes.
+   */
+  size_t organization_len;
+} Ip2AsnInfo;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Returns a message describing the last error on the calling thread, or
+ * `NULL` if no call has failed.
+ *
+ * The message is owned by the library, and is valid until the next failing
+ * call on the same thread.
+ */
+const char *ip2asn_last_error(void);
+
+/**
+ * Builds a map from a data file, which may be gzip-compressed.
+ *
+ * `mode` is an `Ip2AsnMode`. Returns `NULL` if the mode is unknown, or the
+ * file cannot be read or, in strict mode, contains a line that cannot be
+ * parsed.
+ *
+ * # Safety
+ *
+ * `path` must be `NULL` or a NUL-terminated string.
+ */
+struct Ip2AsnMap *ip2asn_map_from_path(const char *path, uint32_t mode);
+
+/**
+ * Builds a map from data in memory, which may be gzip-compressed.
+ *
+ * The map does not refer to the buffer, so it can be freed once this
+ * returns. `mode` is an `Ip2AsnMode`. Returns `NULL` if the mode is unknown,
+ * or the data cannot be decompressed or, in strict mode, contains a line
+ * that cannot be parsed.
+ *
+ * # Safety
+ *
+ * `data` must point to `len` readable bytes. It may be `NULL` if `len` is
+ * zero.
+ */
+struct Ip2AsnMap *ip2asn_map_from_buffer(const uint8_t *data, size_t len, uint32_t mode);
+
+/**
+ * Frees a map. Does nothing if `map` is `NULL`.
+ *
+ * # Safety
+ *
+ * `map` must be `NULL` or a map returned by this library that has not been
+ * freed. Organization names from its lookups are invalid afterwards.
+ */
+void ip2asn_map_free(struct Ip2AsnMap *map);
+
+/**
+ * Looks up an IPv4 address given as 4 bytes in network byte order.
+ *
+ * Returns `true` and fills in `out` if the address is found, or returns
+ * `false` and leaves `out` unchanged if it is not, if any argument is
+ * `NULL`, or if the lookup panics.
+ *
+ * # Safety
+ *
+ * `map` must be `NULL` or a live map, `addr` must be `NULL` or point to 4
+ * readable bytes, and `out` must be `NULL` or valid for writes.
+ */
+bool ip2asn_lookup_v4(const struct Ip2AsnMap *map, const uint8_t *addr, struct Ip2AsnInfo *out);
+
+/**
+ * Looks up an IPv6 address given as 16 bytes in network byte order.
+ *
+ * Returns `true` and fills in `out` if the address is found, or returns
+ * `false` and leaves `out` unchanged if it is not, if any argument is
+ * `NULL`, or if the lookup panics.
+ *
+ * # Safety
+ *
+ * `map` must be `NULL` or a live map, `addr` must be `NULL` or point to 16
+ * readable bytes, and `out` must be `NULL` or valid for writes.
+ */
+bool ip2asn_lookup_v6(const struct Ip2AsnMap *map, const uint8_t *addr, struct Ip2AsnInfo *out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* IP2ASN_H */
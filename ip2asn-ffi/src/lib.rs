@@ -0,0 +1,343 @@
+//! # ip2asn-ffi
+//!
+//! C bindings for the `ip2asn` crate, built as a shared and a static library.
+//!
+//! The C API is declared in `include/ip2asn.h`, which is generated from this
+//! file with [cbindgen](https://github.com/mozilla/cbindgen):
+//!
+//! ```sh
+//! cbindgen --config cbindgen.toml --output include/ip2asn.h
+//! ```
+//!
+//! A map is opened from a file or a buffer with `ip2asn_map_from_path` or
+//! `ip2asn_map_from_buffer`, looked up with `ip2asn_lookup_v4` and
+//! `ip2asn_lookup_v6`, and freed with `ip2asn_map_free`. Functions that fail
+//! return `NULL`, and `ip2asn_last_error` describes why. A panic inside the
+//! library is caught at the boundary and reported the same way, rather than
+//! unwinding into the caller.
+//!
+//! ```c
+//! Ip2AsnMap *map = ip2asn_map_from_path("ip2asn-combined.tsv.gz",
+//!                                       Ip2AsnMode_Resilient);
+//! if (map == NULL) {
+//!     fprintf(stderr, "%s\n", ip2asn_last_error());
+//!     return 1;
+//! }
+//!
+//! const uint8_t addr[4] = {1, 1, 1, 1};
+//! Ip2AsnInfo info;
+//! if (ip2asn_lookup_v4(map, addr, &info)) {
+//!     printf("AS%u %.*s\n", info.asn, (int)info.organization_len,
+//!            info.organization);
+//! }
+//! ip2asn_map_free(map);
+//! ```
+
+#![deny(missing_docs)]
+
+use ip2asn::{AsnInfoView, Builder, Error, IpAsnMap};
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// A map from IP addresses to ASN information.
+///
+/// It is created by `ip2asn_map_from_path` or `ip2asn_map_from_buffer`, and
+/// must be freed with `ip2asn_map_free`. A map can be looked up from several
+/// threads at once.
+pub struct Ip2AsnMap {
+    map: IpAsnMap,
+}
+
+/// How lines that cannot be parsed are handled when a map is built.
+///
+/// Functions take the mode as a `uint32_t`, and reject values other than
+/// these.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ip2AsnMode {
+    /// Skip lines that cannot be parsed.
+    Resilient = 0,
+    /// Fail on the first line that cannot be parsed.
+    Strict = 1,
+}
+
+impl Ip2AsnMode {
+    /// Returns the mode with the value `mode`, if there is one.
+    fn from_raw(mode: u32) -> Option<Self> {
+        match mode {
+            0 => Some(Ip2AsnMode::Resilient),
+            1 => Some(Ip2AsnMode::Strict),
+            _ => None,
+        }
+    }
+}
+
+/// The ASN information for an address, filled in by `ip2asn_lookup_v4` and
+/// `ip2asn_lookup_v6`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Ip2AsnInfo {
+    /// The Autonomous System Number.
+    pub asn: u32,
+    /// The two-letter country code, such as `US`, terminated by a NUL byte.
+    pub country_code: [c_char; 3],
+    /// The prefix length of the matching network.
+    pub prefix_len: u8,
+    /// The address of the matching network in network byte order. IPv4
+    /// networks use the first 4 bytes, and the rest are zero.
+    pub network: [u8; 16],
+    /// The organization name, which is not terminated by a NUL byte. It
+    /// points into the map, and is valid until the map is freed.
+    pub organization: *const c_char,
+    /// The length of the organization name in bytes.
+    pub organization_len: usize,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl ToString) {
+    let message = message.to_string().replace('\0', "");
+    let message = CString::new(message).expect("NUL bytes were removed");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, returning `fallback` after recording the error if it panics.
+fn catch_panic<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        set_last_error(format!("panic in ip2asn: {}", panic_message(&*payload)));
+        fallback
+    })
+}
+
+/// Returns the message a panic was raised with.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+/// Builds a map, returning it as a pointer, or `NULL` after recording the
+/// error.
+fn build(builder: Result<Builder<'_>, Error>, mode: u32) -> *mut Ip2AsnMap {
+    let Some(mode) = Ip2AsnMode::from_raw(mode) else {
+        set_last_error(format!("invalid mode: {mode}"));
+        return ptr::null_mut();
+    };
+    let builder = match (builder, mode) {
+        (Ok(builder), Ip2AsnMode::Strict) => Ok(builder.strict()),
+        (builder, _) => builder,
+    };
+    match builder.and_then(Builder::build) {
+        Ok(map) => Box::into_raw(Box::new(Ip2AsnMap { map })),
+        Err(err) => {
+            set_last_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns a message describing the last error on the calling thread, or
+/// `NULL` if no call has failed.
+///
+/// The message is owned by the library, and is valid until the next failing
+/// call on the same thread.
+#[no_mangle]
+pub extern "C" fn ip2asn_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Builds a map from a data file, which may be gzip-compressed.
+///
+/// `mode` is an `Ip2AsnMode`. Returns `NULL` if the mode is unknown, or the
+/// file cannot be read or, in strict mode, contains a line that cannot be
+/// parsed.
+///
+/// # Safety
+///
+/// `path` must be `NULL` or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ip2asn_map_from_path(path: *const c_char, mode: u32) -> *mut Ip2AsnMap {
+    catch_panic(ptr::null_mut(), || {
+        if path.is_null() {
+            set_last_error("path is NULL");
+            return ptr::null_mut();
+        }
+        let Ok(path) = CStr::from_ptr(path).to_str() else {
+            set_last_error("path is not valid UTF-8");
+            return ptr::null_mut();
+        };
+        build(Builder::new().from_path(path), mode)
+    })
+}
+
+/// Builds a map from data in memory, which may be gzip-compressed.
+///
+/// The map does not refer to the buffer, so it can be freed once this
+/// returns. `mode` is an `Ip2AsnMode`. Returns `NULL` if the mode is unknown,
+/// or the data cannot be decompressed or, in strict mode, contains a line
+/// that cannot be parsed.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes. It may be `NULL` if `len` is
+/// zero.
+#[no_mangle]
+pub unsafe extern "C" fn ip2asn_map_from_buffer(
+    data: *const u8,
+    len: usize,
+    mode: u32,
+) -> *mut Ip2AsnMap {
+    catch_panic(ptr::null_mut(), || {
+        let data = if len == 0 {
+            &[][..]
+        } else if data.is_null() {
+            set_last_error("data is NULL");
+            return ptr::null_mut();
+        } else {
+            std::slice::from_raw_parts(data, len)
+        };
+        build(Builder::new().with_source(data), mode)
+    })
+}
+
+/// Frees a map. Does nothing if `map` is `NULL`.
+///
+/// # Safety
+///
+/// `map` must be `NULL` or a map returned by this library that has not been
+/// freed. Organization names from its lookups are invalid afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ip2asn_map_free(map: *mut Ip2AsnMap) {
+    catch_panic((), || {
+        if !map.is_null() {
+            drop(Box::from_raw(map));
+        }
+    })
+}
+
+/// Copies a lookup result into `out`.
+fn fill(info: &AsnInfoView<'_>, out: &mut Ip2AsnInfo) {
+    let mut country_code = [0; 3];
+    for (dst, &src) in country_code.iter_mut().zip(info.country_code.as_bytes()) {
+        *dst = src as c_char;
+    }
+    let mut network = [0; 16];
+    match info.network.network_address() {
+        IpAddr::V4(addr) => network[..4].copy_from_slice(&addr.octets()),
+        IpAddr::V6(addr) => network.copy_from_slice(&addr.octets()),
+    }
+    *out = Ip2AsnInfo {
+        asn: info.asn.get(),
+        country_code,
+        prefix_len: info.network.netmask(),
+        network,
+        organization: info.organization.as_ptr().cast(),
+        organization_len: info.organization.len(),
+    };
+}
+
+/// Looks up an address, filling in `out` and returning `true` if it is found.
+///
+/// # Safety
+///
+/// `map` must be `NULL` or a live map, and `out` must be `NULL` or valid for
+/// writes.
+unsafe fn lookup(map: *const Ip2AsnMap, ip: IpAddr, out: *mut Ip2AsnInfo) -> bool {
+    let (Some(map), Some(out)) = (map.as_ref(), out.as_mut()) else {
+        return false;
+    };
+    catch_panic(false, || match map.map.lookup(ip) {
+        Some(info) => {
+            fill(&info, out);
+            true
+        }
+        None => false,
+    })
+}
+
+/// Looks up an IPv4 address given as 4 bytes in network byte order.
+///
+/// Returns `true` and fills in `out` if the address is found, or returns
+/// `false` and leaves `out` unchanged if it is not, if any argument is
+/// `NULL`, or if the lookup panics.
+///
+/// # Safety
+///
+/// `map` must be `NULL` or a live map, `addr` must be `NULL` or point to 4
+/// readable bytes, and `out` must be `NULL` or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ip2asn_lookup_v4(
+    map: *const Ip2AsnMap,
+    addr: *const u8,
+    out: *mut Ip2AsnInfo,
+) -> bool {
+    if addr.is_null() {
+        return false;
+    }
+    let octets = ptr::read_unaligned(addr.cast::<[u8; 4]>());
+    lookup(map, IpAddr::V4(Ipv4Addr::from(octets)), out)
+}
+
+/// Looks up an IPv6 address given as 16 bytes in network byte order.
+///
+/// Returns `true` and fills in `out` if the address is found, or returns
+/// `false` and leaves `out` unchanged if it is not, if any argument is
+/// `NULL`, or if the lookup panics.
+///
+/// # Safety
+///
+/// `map` must be `NULL` or a live map, `addr` must be `NULL` or point to 16
+/// readable bytes, and `out` must be `NULL` or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ip2asn_lookup_v6(
+    map: *const Ip2AsnMap,
+    addr: *const u8,
+    out: *mut Ip2AsnInfo,
+) -> bool {
+    if addr.is_null() {
+        return false;
+    }
+    let octets = ptr::read_unaligned(addr.cast::<[u8; 16]>());
+    lookup(map, IpAddr::V6(Ipv6Addr::from(octets)), out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> String {
+        let message = ip2asn_last_error();
+        assert!(!message.is_null());
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_catch_panic() {
+        let result = catch_panic(ptr::null_mut::<Ip2AsnMap>(), || panic!("boom"));
+        assert!(result.is_null());
+        assert_eq!(last_error(), "panic in ip2asn: boom");
+
+        assert!(!catch_panic(false, || panic!("{} {}", "formatted", "boom")));
+        assert_eq!(last_error(), "panic in ip2asn: formatted boom");
+    }
+
+    #[test]
+    fn test_invalid_mode() {
+        let data = b"1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n";
+        let map = unsafe { ip2asn_map_from_buffer(data.as_ptr(), data.len(), 7) };
+        assert!(map.is_null());
+        assert_eq!(last_error(), "invalid mode: 7");
+    }
+}
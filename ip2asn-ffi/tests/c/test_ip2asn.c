@@ -0,0 +1,124 @@
+/* Tests for the C API, compiled and run by tests/c_api.rs. */
+
+#include "ip2asn.h"
+
+#include <stdio.h>
+#include <string.h>
+
+static int failures = 0;
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,         \
+                    __LINE__, #cond);                                      \
+            failures++;                                                    \
+        }                                                                  \
+    } while (0)
+
+static const char DATA[] =
+    "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n"
+    "8.8.8.0\t8.8.8.255\t15169\tUS\tGOOGLE\n"
+    "2001:200::\t2001:200:ffff:ffff:ffff:ffff:ffff:ffff\t2500\tJP\tWIDE\n";
+
+static const char MALFORMED[] =
+    "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n"
+    "this is not a valid line\n";
+
+static int org_equals(const Ip2AsnInfo *info, const char *expected) {
+    return info->organization_len == strlen(expected) &&
+           memcmp(info->organization, expected, info->organization_len) == 0;
+}
+
+static void test_buffer_lookups(void) {
+    Ip2AsnMap *map = ip2asn_map_from_buffer((const uint8_t *)DATA, strlen(DATA),
+                                            Ip2AsnMode_Strict);
+    CHECK(map != NULL);
+
+    const uint8_t v4[4] = {8, 8, 8, 8};
+    Ip2AsnInfo info;
+    memset(&info, 0, sizeof(info));
+    CHECK(ip2asn_lookup_v4(map, v4, &info));
+    CHECK(info.asn == 15169);
+    CHECK(strcmp(info.country_code, "US") == 0);
+    CHECK(info.prefix_len == 24);
+    CHECK(memcmp(info.network, "\x08\x08\x08\x00", 4) == 0);
+    CHECK(org_equals(&info, "GOOGLE"));
+
+    const uint8_t v6[16] = {0x20, 0x01, 0x02, 0x00, 0, 0, 0, 0,
+                            0,    0,    0,    0,    0, 0, 0, 1};
+    CHECK(ip2asn_lookup_v6(map, v6, &info));
+    CHECK(info.asn == 2500);
+    CHECK(strcmp(info.country_code, "JP") == 0);
+    CHECK(info.prefix_len == 32);
+    CHECK(memcmp(info.network, v6, 4) == 0);
+    CHECK(org_equals(&info, "WIDE"));
+
+    /* A miss leaves the result unchanged. */
+    const uint8_t missing[4] = {9, 9, 9, 9};
+    info.asn = 42;
+    CHECK(!ip2asn_lookup_v4(map, missing, &info));
+    CHECK(info.asn == 42);
+
+    CHECK(!ip2asn_lookup_v4(map, NULL, &info));
+    CHECK(!ip2asn_lookup_v4(map, v4, NULL));
+    CHECK(!ip2asn_lookup_v4(NULL, v4, &info));
+
+    ip2asn_map_free(map);
+    ip2asn_map_free(NULL);
+}
+
+static void test_modes(void) {
+    Ip2AsnMap *map = ip2asn_map_from_buffer(
+        (const uint8_t *)MALFORMED, strlen(MALFORMED), Ip2AsnMode_Resilient);
+    CHECK(map != NULL);
+    const uint8_t v4[4] = {1, 0, 0, 1};
+    Ip2AsnInfo info;
+    CHECK(ip2asn_lookup_v4(map, v4, &info));
+    CHECK(info.asn == 13335);
+    ip2asn_map_free(map);
+
+    map = ip2asn_map_from_buffer((const uint8_t *)MALFORMED, strlen(MALFORMED),
+                                 Ip2AsnMode_Strict);
+    CHECK(map == NULL);
+    const char *error = ip2asn_last_error();
+    CHECK(error != NULL);
+    CHECK(error != NULL && strstr(error, "line 2") != NULL);
+
+    map = ip2asn_map_from_buffer((const uint8_t *)DATA, strlen(DATA), 7);
+    CHECK(map == NULL);
+    CHECK(strcmp(ip2asn_last_error(), "invalid mode: 7") == 0);
+}
+
+static void test_path(const char *path) {
+    Ip2AsnMap *map = ip2asn_map_from_path(path, Ip2AsnMode_Strict);
+    CHECK(map != NULL);
+    const uint8_t v4[4] = {1, 1, 1, 1};
+    Ip2AsnInfo info;
+    CHECK(ip2asn_lookup_v4(map, v4, &info));
+    CHECK(info.asn == 13335);
+    CHECK(org_equals(&info, "CLOUDFLARENET"));
+    ip2asn_map_free(map);
+
+    CHECK(ip2asn_map_from_path("/nonexistent/ip2asn.tsv", Ip2AsnMode_Strict) ==
+          NULL);
+    CHECK(ip2asn_last_error() != NULL);
+    CHECK(ip2asn_map_from_path(NULL, Ip2AsnMode_Strict) == NULL);
+    CHECK(strcmp(ip2asn_last_error(), "path is NULL") == 0);
+}
+
+int main(int argc, char **argv) {
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s <data file>\n", argv[0]);
+        return 2;
+    }
+    CHECK(ip2asn_last_error() == NULL);
+    test_buffer_lookups();
+    test_modes();
+    test_path(argv[1]);
+    if (failures > 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    return 0;
+}
@@ -0,0 +1,54 @@
+//! Compiles the C tests in `tests/c` against the generated header and the
+//! static library, and runs them.
+
+#![cfg(unix)]
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Builds the static library, which `cargo test` does not, and returns the
+/// directory it is in, such as `target/debug`.
+fn build_library() -> PathBuf {
+    // The test binary is in `target/debug/deps`.
+    let exe = env::current_exe().unwrap();
+    let dir = exe.parent().unwrap().parent().unwrap().to_path_buf();
+
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo.args(["build", "--package", "ip2asn-ffi", "--lib"]);
+    if dir.ends_with("release") {
+        cargo.arg("--release");
+    }
+    assert!(
+        cargo.status().unwrap().success(),
+        "building the library failed"
+    );
+    dir
+}
+
+#[test]
+fn test_c_api() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let library_dir = build_library();
+    let binary = Path::new(env!("CARGO_TARGET_TMPDIR")).join("test_ip2asn");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+    let status = Command::new(&compiler)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-I"])
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/c/test_ip2asn.c"))
+        .arg(library_dir.join("libip2asn_ffi.a"))
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&binary)
+        .status()
+        .unwrap_or_else(|e| panic!("failed to run {compiler}: {e}"));
+    assert!(status.success(), "compiling the C tests failed");
+
+    let data = manifest_dir.join("../testdata/testdata-small-ip2asn.tsv.gz");
+    let output = Command::new(&binary).arg(data).output().unwrap();
+    assert!(
+        output.status.success(),
+        "C tests failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,211 @@
+//! # ip2asn-py
+//!
+//! Python bindings for the `ip2asn` crate, built with
+//! [maturin](https://www.maturin.rs) as the `ip2asn` Python module.
+//!
+//! ```python
+//! import ip2asn
+//!
+//! asn_map = ip2asn.Builder().from_path("ip2asn-combined.tsv.gz").build()
+//! asn_map.lookup("1.1.1.1")
+//! # {'network': '1.1.1.0/24', 'asn': 13335, 'country_code': 'US',
+//! #  'organization': 'CLOUDFLARENET'}
+//! asn_map.lookup_many(["8.8.8.8", "not-an-ip"])
+//! # [{'network': '8.8.8.0/24', 'asn': 15169, ...}, None]
+//! ```
+//!
+//! Building a map and batch lookups release the GIL, so other Python threads
+//! keep running while they do.
+
+#![deny(missing_docs)]
+
+use ip2asn::{AsnInfo, Builder, Error, IpAsnMap};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+create_exception!(
+    ip2asn,
+    Ip2AsnError,
+    PyException,
+    "Raised when a map cannot be built from its data source."
+);
+
+/// Where a map's data is read from.
+#[derive(Debug, Clone)]
+enum Source {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+/// Builds a map, without holding the GIL.
+fn build(py: Python<'_>, source: Source, strict: bool) -> PyResult<PyIpAsnMap> {
+    let result = py.allow_threads(move || -> Result<IpAsnMap, Error> {
+        let builder = match &source {
+            Source::Path(path) => Builder::new().from_path(path)?,
+            Source::Bytes(data) => Builder::new().with_source(data.as_slice())?,
+        };
+        let builder = if strict { builder.strict() } else { builder };
+        builder.build()
+    });
+    match result {
+        Ok(map) => Ok(PyIpAsnMap { map }),
+        Err(err) => Err(Ip2AsnError::new_err(err.to_string())),
+    }
+}
+
+/// Converts a lookup result into a dict.
+fn to_dict<'py>(py: Python<'py>, info: &AsnInfo) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("network", info.network.to_string())?;
+    dict.set_item("asn", info.asn.get())?;
+    dict.set_item("country_code", &info.country_code)?;
+    dict.set_item("organization", &info.organization)?;
+    Ok(dict)
+}
+
+/// A builder for a map, read from a file or from bytes.
+///
+/// Each method returns the builder, so calls can be chained:
+/// `Builder().from_path(path).strict().build()`.
+#[pyclass(name = "Builder", module = "ip2asn")]
+#[derive(Debug, Default)]
+struct PyBuilder {
+    source: Option<Source>,
+    strict: bool,
+}
+
+#[pymethods]
+impl PyBuilder {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the data from a file, which may be gzip-compressed.
+    fn from_path(mut slf: PyRefMut<'_, Self>, path: PathBuf) -> PyRefMut<'_, Self> {
+        slf.source = Some(Source::Path(path));
+        slf
+    }
+
+    /// Reads the data from bytes, which may be gzip-compressed.
+    fn from_bytes(mut slf: PyRefMut<'_, Self>, data: Vec<u8>) -> PyRefMut<'_, Self> {
+        slf.source = Some(Source::Bytes(data));
+        slf
+    }
+
+    /// Fails the build on the first line that cannot be parsed, instead of
+    /// skipping it.
+    fn strict(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+        slf.strict = true;
+        slf
+    }
+
+    /// Builds the map, raising `Ip2AsnError` if the data cannot be read or, in
+    /// strict mode, contains a line that cannot be parsed.
+    fn build(&self, py: Python<'_>) -> PyResult<PyIpAsnMap> {
+        let source = self.source.clone().ok_or_else(|| {
+            PyValueError::new_err("no data source, call from_path() or from_bytes() first")
+        })?;
+        build(py, source, self.strict)
+    }
+
+    fn __repr__(&self) -> String {
+        let source = match &self.source {
+            Some(Source::Path(path)) => format!("{:?}", path.display().to_string()),
+            Some(Source::Bytes(data)) => format!("<{} bytes>", data.len()),
+            None => "None".to_string(),
+        };
+        format!("Builder(source={source}, strict={})", self.strict)
+    }
+}
+
+/// A map from IP addresses to ASN information.
+///
+/// Lookups return a dict with the `network`, `asn`, `country_code`, and
+/// `organization` of the matching range, or `None` if the address is not in
+/// the map.
+#[pyclass(name = "IpAsnMap", module = "ip2asn", frozen)]
+struct PyIpAsnMap {
+    map: IpAsnMap,
+}
+
+#[pymethods]
+impl PyIpAsnMap {
+    /// Builds a map from a file, which may be gzip-compressed.
+    #[staticmethod]
+    #[pyo3(signature = (path, strict = false))]
+    fn from_path(py: Python<'_>, path: PathBuf, strict: bool) -> PyResult<Self> {
+        build(py, Source::Path(path), strict)
+    }
+
+    /// Builds a map from bytes, which may be gzip-compressed.
+    #[staticmethod]
+    #[pyo3(signature = (data, strict = false))]
+    fn from_bytes(py: Python<'_>, data: Vec<u8>, strict: bool) -> PyResult<Self> {
+        build(py, Source::Bytes(data), strict)
+    }
+
+    /// Looks up an IP address, raising `ValueError` if it is not valid.
+    fn lookup<'py>(&self, py: Python<'py>, ip: &str) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let addr: IpAddr = ip
+            .trim()
+            .parse()
+            .map_err(|_| PyValueError::new_err(format!("invalid IP address: {ip:?}")))?;
+        self.map
+            .lookup_owned(addr)
+            .map(|info| to_dict(py, &info))
+            .transpose()
+    }
+
+    /// Looks up each IP address in an iterable, such as a list, a pandas
+    /// Series, or a numpy array of strings, returning a list of results in
+    /// the same order.
+    ///
+    /// Items that are not valid IP address strings, such as `None` or `NaN`
+    /// for missing values, give `None` instead of raising.
+    fn lookup_many<'py>(
+        &self,
+        py: Python<'py>,
+        ips: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyList>> {
+        let ips = ips
+            .try_iter()?
+            .map(|item| Ok(item?.extract::<String>().ok()))
+            .collect::<PyResult<Vec<_>>>()?;
+        let results: Vec<Option<AsnInfo>> = py.allow_threads(|| {
+            ips.iter()
+                .map(|ip| {
+                    let addr = ip.as_deref()?.trim().parse().ok()?;
+                    self.map.lookup_owned(addr)
+                })
+                .collect()
+        });
+
+        let list = PyList::empty(py);
+        for info in &results {
+            match info {
+                Some(info) => list.append(to_dict(py, info)?)?,
+                None => list.append(py.None())?,
+            }
+        }
+        Ok(list)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.map)
+    }
+}
+
+/// The `ip2asn` Python module.
+#[pymodule]
+#[pyo3(name = "ip2asn")]
+fn ip2asn_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyBuilder>()?;
+    m.add_class::<PyIpAsnMap>()?;
+    m.add("Ip2AsnError", m.py().get_type::<Ip2AsnError>())?;
+    Ok(())
+}
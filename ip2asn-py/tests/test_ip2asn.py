@@ -0,0 +1,99 @@
+"""Tests for the ip2asn Python module.
+
+Run them from this directory with:
+
+    maturin develop --extras test && pytest
+"""
+
+import threading
+from pathlib import Path
+
+import pytest
+
+import ip2asn
+
+DATA = (
+    b"1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n"
+    b"8.8.8.0\t8.8.8.255\t15169\tUS\tGOOGLE\n"
+    b"2001:200::\t2001:200:ffff:ffff:ffff:ffff:ffff:ffff\t2500\tJP\tWIDE\n"
+)
+
+FIXTURE = Path(__file__).parents[2] / "testdata" / "testdata-small-ip2asn.tsv.gz"
+
+
+@pytest.fixture
+def asn_map():
+    return ip2asn.IpAsnMap.from_bytes(DATA)
+
+
+def test_lookup(asn_map):
+    assert asn_map.lookup("8.8.8.8") == {
+        "network": "8.8.8.0/24",
+        "asn": 15169,
+        "country_code": "US",
+        "organization": "GOOGLE",
+    }
+    assert asn_map.lookup("2001:200::1")["asn"] == 2500
+    assert asn_map.lookup("9.9.9.9") is None
+
+
+def test_lookup_invalid_ip(asn_map):
+    with pytest.raises(ValueError):
+        asn_map.lookup("not-an-ip")
+
+
+def test_lookup_many(asn_map):
+    results = asn_map.lookup_many(["1.0.0.1", "9.9.9.9", "not-an-ip", None, " 8.8.8.8 "])
+    assert [r and r["asn"] for r in results] == [13335, None, None, None, 15169]
+
+
+def test_lookup_many_accepts_iterables(asn_map):
+    results = asn_map.lookup_many(ip for ip in ["1.0.0.1", "8.8.8.8"])
+    assert [r["asn"] for r in results] == [13335, 15169]
+
+
+def test_lookup_many_numpy(asn_map):
+    np = pytest.importorskip("numpy")
+    for array in [
+        np.array(["1.0.0.1", "8.8.8.8"]),
+        np.array(["1.0.0.1", None], dtype=object),
+    ]:
+        results = asn_map.lookup_many(array)
+        assert results[0]["asn"] == 13335
+    assert asn_map.lookup_many(np.array([], dtype=str)) == []
+
+
+def test_builder_from_path():
+    asn_map = ip2asn.Builder().from_path(FIXTURE).strict().build()
+    assert asn_map.lookup("1.1.1.1")["organization"] == "CLOUDFLARENET"
+    assert ip2asn.IpAsnMap.from_path(str(FIXTURE)).lookup("1.1.1.1")["asn"] == 13335
+
+
+def test_builder_strict():
+    data = DATA + b"this is not a valid line\n"
+    assert ip2asn.Builder().from_bytes(data).build().lookup("1.0.0.1") is not None
+    with pytest.raises(ip2asn.Ip2AsnError, match="line 4"):
+        ip2asn.Builder().from_bytes(data).strict().build()
+    with pytest.raises(ip2asn.Ip2AsnError):
+        ip2asn.IpAsnMap.from_bytes(data, strict=True)
+
+
+def test_builder_errors():
+    with pytest.raises(ValueError):
+        ip2asn.Builder().build()
+    with pytest.raises(ip2asn.Ip2AsnError):
+        ip2asn.Builder().from_path("/nonexistent/ip2asn.tsv").build()
+
+
+def test_lookups_from_threads(asn_map):
+    results = []
+
+    def worker():
+        results.append(asn_map.lookup_many(["1.0.0.1"] * 1000))
+
+    threads = [threading.Thread(target=worker) for _ in range(4)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+    assert all(len(r) == 1000 and r[0]["asn"] == 13335 for r in results)
@@ -0,0 +1,310 @@
+//! Contains the `Asn` type and its notations and classification.
+//!
+//! An [`Asn`] can be parsed from the `asplain` notation (`15169`), with an
+//! `AS` prefix (`AS15169`), or from the `asdot` notation of RFC 5396 (`1.10`
+//! for 65546), and displayed in any of them with an [`AsnNotation`].
+//! [`Asn::kind`] classifies the number according to the IANA special-purpose
+//! AS number registry.
+
+use crate::ParseErrorKind;
+use alloc::string::ToString;
+use core::fmt;
+use core::str::FromStr;
+
+/// An Autonomous System Number.
+///
+/// `Asn` compares equal to the `u32` it wraps, and displays in the `asplain`
+/// notation, or with an `AS` prefix using the alternate flag (`{:#}`).
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::asn::{Asn, AsnKind, AsnNotation};
+///
+/// let asn: Asn = "AS1.10".parse().unwrap();
+/// assert_eq!(asn, 65546);
+/// assert_eq!(asn.to_string(), "65546");
+/// assert_eq!(format!("{asn:#}"), "AS65546");
+/// assert_eq!(asn.display(AsnNotation::Dot).to_string(), "1.10");
+/// assert_eq!(asn.kind(), AsnKind::Documentation);
+///
+/// assert_eq!(Asn::new(64512).kind(), AsnKind::Private);
+/// assert!("AS15169".parse::<Asn>().unwrap().is_public());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Asn(u32);
+
+impl Asn {
+    /// The AS_TRANS number, 23456, used by RFC 6793 to represent 4-byte ASNs
+    /// to speakers that only support 2-byte ASNs.
+    pub const AS_TRANS: Asn = Asn(23456);
+
+    /// Creates an `Asn` from its number.
+    pub const fn new(asn: u32) -> Self {
+        Asn(asn)
+    }
+
+    /// Returns the number.
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    /// Classifies the number according to the IANA special-purpose AS number
+    /// registry.
+    pub fn kind(self) -> AsnKind {
+        match self.0 {
+            0 | 65535 | 4_294_967_295 => AsnKind::Reserved,
+            23456 => AsnKind::AsTrans,
+            64496..=64511 | 65536..=65551 => AsnKind::Documentation,
+            64512..=65534 | 4_200_000_000..=4_294_967_294 => AsnKind::Private,
+            65552..=131071 => AsnKind::Reserved,
+            _ => AsnKind::Public,
+        }
+    }
+
+    /// Returns `true` if the number is an ordinary, publicly routable ASN.
+    pub fn is_public(self) -> bool {
+        self.kind() == AsnKind::Public
+    }
+
+    /// Returns `true` if the number is reserved for private use by RFC 6996.
+    pub fn is_private(self) -> bool {
+        self.kind() == AsnKind::Private
+    }
+
+    /// Returns a value that displays the number in the given notation.
+    pub fn display(self, notation: AsnNotation) -> AsnDisplay {
+        AsnDisplay {
+            asn: self,
+            notation,
+        }
+    }
+}
+
+impl From<u32> for Asn {
+    fn from(asn: u32) -> Self {
+        Asn(asn)
+    }
+}
+
+impl From<Asn> for u32 {
+    fn from(asn: Asn) -> Self {
+        asn.0
+    }
+}
+
+impl PartialEq<u32> for Asn {
+    fn eq(&self, other: &u32) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<Asn> for u32 {
+    fn eq(&self, other: &Asn) -> bool {
+        *self == other.0
+    }
+}
+
+impl fmt::Display for Asn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "AS{}", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+impl FromStr for Asn {
+    type Err = ParseErrorKind;
+
+    /// Parses an ASN in the `asplain` or `asdot` notation, with or without a
+    /// case-insensitive `AS` prefix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseErrorKind::InvalidAsnNumber {
+            value: s.to_string(),
+        };
+        let number = match s.get(..2) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("AS") => &s[2..],
+            _ => s,
+        };
+        // `u32::from_str` accepts a leading `+`, which no notation allows.
+        let parse_part = |part: &str| {
+            if part.starts_with('+') {
+                None
+            } else {
+                part.parse::<u32>().ok()
+            }
+        };
+        let asn = match number.split_once('.') {
+            Some((high, low)) => {
+                let high = parse_part(high).and_then(|high| u16::try_from(high).ok());
+                let low = parse_part(low).and_then(|low| u16::try_from(low).ok());
+                match (high, low) {
+                    (Some(high), Some(low)) => (u32::from(high) << 16) | u32::from(low),
+                    _ => return Err(invalid()),
+                }
+            }
+            None => parse_part(number).ok_or_else(invalid)?,
+        };
+        Ok(Asn(asn))
+    }
+}
+
+/// The class of an ASN in the IANA special-purpose AS number registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[non_exhaustive]
+pub enum AsnKind {
+    /// An ordinary ASN that may be assigned to a network.
+    Public,
+    /// An ASN reserved for private use by RFC 6996: 64512–65534 and
+    /// 4200000000–4294967294.
+    Private,
+    /// An ASN reserved for documentation by RFC 5398: 64496–64511 and
+    /// 65536–65551.
+    Documentation,
+    /// AS_TRANS, 23456, from RFC 6793.
+    AsTrans,
+    /// An ASN that may not be used, such as 0 (RFC 7607), 65535 and
+    /// 4294967295 (RFC 7300), or 65552–131071 (reserved by IANA).
+    Reserved,
+}
+
+/// A notation for writing an [`Asn`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AsnNotation {
+    /// The decimal number, such as `65546`. This is the default.
+    #[default]
+    Plain,
+    /// The decimal number with an `AS` prefix, such as `AS65546`.
+    Prefixed,
+    /// The `asdot` notation of RFC 5396, such as `1.10`, with ASNs below
+    /// 65536 written as decimal numbers.
+    Dot,
+    /// The `asdot+` notation of RFC 5396, writing every ASN as two 16-bit
+    /// numbers, such as `0.15169`.
+    DotPlus,
+}
+
+/// Displays an [`Asn`] in a given notation. Returned by [`Asn::display`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsnDisplay {
+    asn: Asn,
+    notation: AsnNotation,
+}
+
+impl fmt::Display for AsnDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let asn = self.asn.0;
+        match self.notation {
+            AsnNotation::Plain => write!(f, "{asn}"),
+            AsnNotation::Prefixed => write!(f, "AS{asn}"),
+            AsnNotation::Dot if asn <= u32::from(u16::MAX) => write!(f, "{asn}"),
+            AsnNotation::Dot | AsnNotation::DotPlus => write!(f, "{}.{}", asn >> 16, asn & 0xffff),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        for (s, expected) in [
+            ("15169", 15169),
+            ("AS15169", 15169),
+            ("as15169", 15169),
+            ("1.10", 65546),
+            ("AS1.10", 65546),
+            ("0.15169", 15169),
+            ("65535.65535", u32::MAX),
+            ("4294967295", u32::MAX),
+        ] {
+            assert_eq!(s.parse::<Asn>(), Ok(Asn(expected)), "{s}");
+        }
+        for s in [
+            "",
+            "AS",
+            "+1",
+            "AS+1",
+            "-1",
+            "1.",
+            ".1",
+            "1.65536",
+            "1.2.3",
+            "4294967296",
+            " 1",
+        ] {
+            assert_eq!(
+                s.parse::<Asn>(),
+                Err(ParseErrorKind::InvalidAsnNumber {
+                    value: s.to_string()
+                }),
+                "{s}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let asn = Asn(65546);
+        assert_eq!(asn.to_string(), "65546");
+        assert_eq!(format!("{asn:#}"), "AS65546");
+        assert_eq!(asn.display(AsnNotation::Plain).to_string(), "65546");
+        assert_eq!(asn.display(AsnNotation::Prefixed).to_string(), "AS65546");
+        assert_eq!(asn.display(AsnNotation::Dot).to_string(), "1.10");
+        assert_eq!(asn.display(AsnNotation::DotPlus).to_string(), "1.10");
+
+        let asn = Asn(15169);
+        assert_eq!(asn.display(AsnNotation::Dot).to_string(), "15169");
+        assert_eq!(asn.display(AsnNotation::DotPlus).to_string(), "0.15169");
+        for notation in [
+            AsnNotation::Plain,
+            AsnNotation::Prefixed,
+            AsnNotation::Dot,
+            AsnNotation::DotPlus,
+        ] {
+            let s = asn.display(notation).to_string();
+            assert_eq!(s.parse::<Asn>(), Ok(asn), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_kind() {
+        for (asn, kind) in [
+            (0, AsnKind::Reserved),
+            (1, AsnKind::Public),
+            (15169, AsnKind::Public),
+            (23456, AsnKind::AsTrans),
+            (64495, AsnKind::Public),
+            (64496, AsnKind::Documentation),
+            (64511, AsnKind::Documentation),
+            (64512, AsnKind::Private),
+            (65534, AsnKind::Private),
+            (65535, AsnKind::Reserved),
+            (65536, AsnKind::Documentation),
+            (65551, AsnKind::Documentation),
+            (65552, AsnKind::Reserved),
+            (131071, AsnKind::Reserved),
+            (131072, AsnKind::Public),
+            (4_199_999_999, AsnKind::Public),
+            (4_200_000_000, AsnKind::Private),
+            (4_294_967_294, AsnKind::Private),
+            (4_294_967_295, AsnKind::Reserved),
+        ] {
+            assert_eq!(Asn(asn).kind(), kind, "{asn}");
+        }
+    }
+}
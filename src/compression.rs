@@ -0,0 +1,329 @@
+//! Contains the logic for detecting and decompressing compressed sources.
+//!
+//! The compression format is detected from the magic bytes at the start of the
+//! stream. Gzip is always supported, while bzip2, xz, and zstd are enabled by
+//! the `bzip2`, `xz`, and `zstd` features. Concatenated (multi-member) streams
+//! are decompressed in full for every format.
+//!
+//! Errors raised by a decompressor are reported as [`Error::Decompression`],
+//! while errors from reading the underlying source stay [`Error::Io`]. A
+//! stream in a format whose feature is disabled is also rejected with
+//! [`Error::Decompression`], naming the feature to enable.
+
+use crate::Error;
+use flate2::bufread::MultiGzDecoder;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// A compression format recognized by its magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CompressionFormat {
+    /// Gzip, detected by the bytes `1f 8b`.
+    Gzip,
+    /// Bzip2, detected by the bytes `BZh`. Requires the `bzip2` feature.
+    Bzip2,
+    /// Xz, detected by the bytes `fd 37 7a 58 5a 00`. Requires the `xz`
+    /// feature.
+    Xz,
+    /// Zstandard, detected by the bytes `28 b5 2f fd`. Requires the `zstd`
+    /// feature.
+    Zstd,
+}
+
+impl CompressionFormat {
+    /// Detects the compression format from the first bytes of a stream.
+    ///
+    /// Formats are detected even if their feature is disabled.
+    pub fn detect(header: &[u8]) -> Option<Self> {
+        if header.starts_with(&[0x1f, 0x8b]) {
+            Some(CompressionFormat::Gzip)
+        } else if header.starts_with(b"BZh") {
+            Some(CompressionFormat::Bzip2)
+        } else if header.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(CompressionFormat::Xz)
+        } else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(CompressionFormat::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for CompressionFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompressionFormat::Gzip => write!(f, "gzip"),
+            CompressionFormat::Bzip2 => write!(f, "bzip2"),
+            CompressionFormat::Xz => write!(f, "xz"),
+            CompressionFormat::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+/// Wraps a reader in a decompressor if its magic bytes identify a compressed
+/// stream.
+///
+/// Streams in a recognized format whose feature is disabled are rejected.
+pub(crate) fn create_source_from_reader<'a>(
+    reader: impl BufRead + Send + 'a,
+) -> Result<Box<dyn BufRead + Send + 'a>, Error> {
+    Source::detect(reader).map(|source| source.reader)
+}
+
+/// A data source, decompressed if needed, that counts the bytes consumed from
+/// it.
+pub(crate) struct Source<'a> {
+    pub(crate) reader: Box<dyn BufRead + Send + 'a>,
+    pub(crate) counts: ByteCounts,
+}
+
+impl<'a> Source<'a> {
+    /// Wraps a reader that is never decompressed.
+    pub(crate) fn plain(reader: impl BufRead + Send + 'a) -> Self {
+        let counts = ByteCounts::default();
+        let reader = CountingReader::new(reader, counts.read.clone());
+        Self {
+            reader: Box::new(reader),
+            counts,
+        }
+    }
+
+    /// Wraps a reader in a decompressor if its magic bytes identify a
+    /// compressed stream, or fails if that format's feature is disabled.
+    pub(crate) fn detect(mut reader: impl BufRead + Send + 'a) -> Result<Self, Error> {
+        let header = reader.fill_buf()?;
+        let Some(format) = CompressionFormat::detect(header) else {
+            return Ok(Self::plain(reader));
+        };
+
+        let read = Arc::new(AtomicU64::new(0));
+        let source = SourceReader(CountingReader::new(reader, read.clone()));
+        let decoder: Box<dyn Read + Send + 'a> = match format {
+            CompressionFormat::Gzip => Box::new(MultiGzDecoder::new(source)),
+            #[cfg(feature = "bzip2")]
+            CompressionFormat::Bzip2 => Box::new(bzip2::bufread::MultiBzDecoder::new(source)),
+            #[cfg(feature = "xz")]
+            CompressionFormat::Xz => Box::new(xz2::bufread::XzDecoder::new_multi_decoder(source)),
+            #[cfg(feature = "zstd")]
+            CompressionFormat::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(source)?),
+            #[cfg(not(feature = "bzip2"))]
+            CompressionFormat::Bzip2 => return Err(unsupported(format)),
+            #[cfg(not(feature = "xz"))]
+            CompressionFormat::Xz => return Err(unsupported(format)),
+            #[cfg(not(feature = "zstd"))]
+            CompressionFormat::Zstd => return Err(unsupported(format)),
+        };
+        let decompressed = Arc::new(AtomicU64::new(0));
+        let reader = BufReader::new(DecompressionReader {
+            format,
+            inner: decoder,
+        });
+        Ok(Self {
+            reader: Box::new(CountingReader::new(reader, decompressed.clone())),
+            counts: ByteCounts {
+                read,
+                decompressed: Some(decompressed),
+            },
+        })
+    }
+}
+
+/// The error for a stream in a format whose feature is disabled. The feature
+/// shares its name with the format.
+#[cfg(not(all(feature = "bzip2", feature = "xz", feature = "zstd")))]
+fn unsupported(format: CompressionFormat) -> Error {
+    Error::Decompression {
+        format,
+        source: io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{format} support is disabled; enable the `{format}` feature"),
+        ),
+    }
+}
+
+/// The number of bytes consumed from a [`Source`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ByteCounts {
+    /// The bytes consumed from the underlying reader.
+    read: Arc<AtomicU64>,
+    /// The bytes consumed after decompression, if the source is compressed.
+    decompressed: Option<Arc<AtomicU64>>,
+}
+
+impl ByteCounts {
+    pub(crate) fn read(&self) -> u64 {
+        self.read.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn decompressed(&self) -> Option<u64> {
+        self.decompressed
+            .as_ref()
+            .map(|count| count.load(Ordering::Relaxed))
+    }
+}
+
+/// Counts the bytes read or consumed from a reader.
+struct CountingReader<R> {
+    inner: R,
+    count: Arc<AtomicU64>,
+}
+
+impl<R> CountingReader<R> {
+    fn new(inner: R, count: Arc<AtomicU64>) -> Self {
+        Self { inner, count }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for CountingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.count.fetch_add(amt as u64, Ordering::Relaxed);
+        self.inner.consume(amt);
+    }
+}
+
+/// The payload of an I/O error raised by the source beneath a decompressor.
+#[derive(Debug)]
+struct SourceError(io::Error);
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for SourceError {}
+
+/// The payload of an I/O error raised by a decompressor itself.
+#[derive(Debug)]
+struct DecompressionError {
+    format: CompressionFormat,
+    source: io::Error,
+}
+
+impl fmt::Display for DecompressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} decompression error: {}", self.format, self.source)
+    }
+}
+
+impl std::error::Error for DecompressionError {}
+
+/// Tags errors from the compressed source so they can be told apart from
+/// decompression errors.
+struct SourceReader<R>(R);
+
+impl<R: Read> Read for SourceReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf).map_err(tag_source_error)
+    }
+}
+
+impl<R: BufRead> BufRead for SourceReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf().map_err(tag_source_error)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt);
+    }
+}
+
+fn tag_source_error(err: io::Error) -> io::Error {
+    io::Error::new(err.kind(), SourceError(err))
+}
+
+/// Reports errors raised by a decompressor as [`DecompressionError`]s, and
+/// restores errors raised by the source beneath it.
+struct DecompressionReader<D> {
+    format: CompressionFormat,
+    inner: D,
+}
+
+impl<D: Read> Read for DecompressionReader<D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner
+            .read(buf)
+            .map_err(|err| match err.downcast::<SourceError>() {
+                Ok(SourceError(err)) => err,
+                Err(err) => io::Error::new(
+                    err.kind(),
+                    DecompressionError {
+                        format: self.format,
+                        source: err,
+                    },
+                ),
+            })
+    }
+}
+
+/// Converts an I/O error into an [`Error`], recognizing decompression errors.
+pub(crate) fn classify_io_error(err: io::Error) -> Error {
+    match err.downcast::<DecompressionError>() {
+        Ok(DecompressionError { format, source }) => Error::Decompression { format, source },
+        Err(err) => Error::Io(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            CompressionFormat::detect(&[0x1f, 0x8b, 0x08]),
+            Some(CompressionFormat::Gzip)
+        );
+        assert_eq!(
+            CompressionFormat::detect(b"BZh91AY"),
+            Some(CompressionFormat::Bzip2)
+        );
+        assert_eq!(
+            CompressionFormat::detect(&[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00, 0x00]),
+            Some(CompressionFormat::Xz)
+        );
+        assert_eq!(
+            CompressionFormat::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0x04]),
+            Some(CompressionFormat::Zstd)
+        );
+        assert_eq!(CompressionFormat::detect(b"1.0.0.0\t"), None);
+        assert_eq!(CompressionFormat::detect(&[0x1f]), None);
+    }
+
+    #[test]
+    fn test_source_errors_are_not_decompression_errors() {
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"))
+            }
+        }
+
+        // A gzip header followed by a source that fails.
+        let source = (&[0x1f, 0x8b][..]).chain(FailingReader);
+        let mut reader = create_source_from_reader(BufReader::new(source)).unwrap();
+        let err = Error::from(reader.fill_buf().unwrap_err());
+        match err {
+            Error::Io(err) => {
+                assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+                assert_eq!(err.to_string(), "reset");
+            }
+            other => panic!("Expected an Io error, got {other:?}"),
+        }
+    }
+}
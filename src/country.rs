@@ -0,0 +1,1066 @@
+//! Contains the embedded ISO 3166-1 country table.
+//!
+//! The table lists every officially assigned ISO 3166-1 alpha-2 code with its
+//! alpha-3 and numeric codes, English short name, and the geographic region
+//! it belongs to in the UN M49 standard. A [`Country`] is a handle to one
+//! entry, and is returned by [`AsnInfoView::country`](crate::AsnInfoView::country)
+//! and [`AsnInfo::country`](crate::AsnInfo::country).
+
+use core::fmt;
+
+/// A continent, as used to group countries by [`Region`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[non_exhaustive]
+pub enum Continent {
+    /// Africa.
+    Africa,
+    /// Antarctica.
+    Antarctica,
+    /// Asia.
+    Asia,
+    /// Europe.
+    Europe,
+    /// North America, including Central America and the Caribbean.
+    NorthAmerica,
+    /// Oceania.
+    Oceania,
+    /// South America.
+    SouthAmerica,
+}
+
+impl Continent {
+    /// Returns the continent's two-letter code, such as `"NA"`, as used by
+    /// MaxMind databases.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Continent::Africa => "AF",
+            Continent::Antarctica => "AN",
+            Continent::Asia => "AS",
+            Continent::Europe => "EU",
+            Continent::NorthAmerica => "NA",
+            Continent::Oceania => "OC",
+            Continent::SouthAmerica => "SA",
+        }
+    }
+
+    /// Returns the continent's English name, such as `"North America"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Continent::Africa => "Africa",
+            Continent::Antarctica => "Antarctica",
+            Continent::Asia => "Asia",
+            Continent::Europe => "Europe",
+            Continent::NorthAmerica => "North America",
+            Continent::Oceania => "Oceania",
+            Continent::SouthAmerica => "South America",
+        }
+    }
+}
+
+impl fmt::Display for Continent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A geographic region in the UN M49 standard.
+///
+/// Sub-Saharan Africa and Latin America are split into their intermediate
+/// regions, such as [`Region::WesternAfrica`] and [`Region::Caribbean`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[non_exhaustive]
+pub enum Region {
+    /// Northern Africa.
+    NorthernAfrica,
+    /// Eastern Africa.
+    EasternAfrica,
+    /// Middle Africa.
+    MiddleAfrica,
+    /// Southern Africa.
+    SouthernAfrica,
+    /// Western Africa.
+    WesternAfrica,
+    /// The Caribbean.
+    Caribbean,
+    /// Central America.
+    CentralAmerica,
+    /// South America.
+    SouthAmerica,
+    /// Northern America.
+    NorthernAmerica,
+    /// Central Asia.
+    CentralAsia,
+    /// Eastern Asia.
+    EasternAsia,
+    /// South-eastern Asia.
+    SouthEasternAsia,
+    /// Southern Asia.
+    SouthernAsia,
+    /// Western Asia.
+    WesternAsia,
+    /// Eastern Europe.
+    EasternEurope,
+    /// Northern Europe.
+    NorthernEurope,
+    /// Southern Europe.
+    SouthernEurope,
+    /// Western Europe.
+    WesternEurope,
+    /// Australia and New Zealand.
+    AustraliaAndNewZealand,
+    /// Melanesia.
+    Melanesia,
+    /// Micronesia.
+    Micronesia,
+    /// Polynesia.
+    Polynesia,
+}
+
+impl Region {
+    /// Returns the region's English name, such as `"South-eastern Asia"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Region::NorthernAfrica => "Northern Africa",
+            Region::EasternAfrica => "Eastern Africa",
+            Region::MiddleAfrica => "Middle Africa",
+            Region::SouthernAfrica => "Southern Africa",
+            Region::WesternAfrica => "Western Africa",
+            Region::Caribbean => "Caribbean",
+            Region::CentralAmerica => "Central America",
+            Region::SouthAmerica => "South America",
+            Region::NorthernAmerica => "Northern America",
+            Region::CentralAsia => "Central Asia",
+            Region::EasternAsia => "Eastern Asia",
+            Region::SouthEasternAsia => "South-eastern Asia",
+            Region::SouthernAsia => "Southern Asia",
+            Region::WesternAsia => "Western Asia",
+            Region::EasternEurope => "Eastern Europe",
+            Region::NorthernEurope => "Northern Europe",
+            Region::SouthernEurope => "Southern Europe",
+            Region::WesternEurope => "Western Europe",
+            Region::AustraliaAndNewZealand => "Australia and New Zealand",
+            Region::Melanesia => "Melanesia",
+            Region::Micronesia => "Micronesia",
+            Region::Polynesia => "Polynesia",
+        }
+    }
+
+    /// Returns the continent the region belongs to.
+    pub fn continent(&self) -> Continent {
+        match self {
+            Region::NorthernAfrica
+            | Region::EasternAfrica
+            | Region::MiddleAfrica
+            | Region::SouthernAfrica
+            | Region::WesternAfrica => Continent::Africa,
+            Region::Caribbean | Region::CentralAmerica | Region::NorthernAmerica => {
+                Continent::NorthAmerica
+            }
+            Region::SouthAmerica => Continent::SouthAmerica,
+            Region::CentralAsia
+            | Region::EasternAsia
+            | Region::SouthEasternAsia
+            | Region::SouthernAsia
+            | Region::WesternAsia => Continent::Asia,
+            Region::EasternEurope
+            | Region::NorthernEurope
+            | Region::SouthernEurope
+            | Region::WesternEurope => Continent::Europe,
+            Region::AustraliaAndNewZealand
+            | Region::Melanesia
+            | Region::Micronesia
+            | Region::Polynesia => Continent::Oceania,
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A country, or other territory, with an officially assigned ISO 3166-1
+/// code.
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::country::{Continent, Country, Region};
+///
+/// let country = Country::from_code("de").unwrap();
+/// assert_eq!(country.code(), "DE");
+/// assert_eq!(country.alpha3(), "DEU");
+/// assert_eq!(country.numeric(), 276);
+/// assert_eq!(country.name(), "Germany");
+/// assert_eq!(country.region(), Some(Region::WesternEurope));
+/// assert_eq!(country.continent(), Continent::Europe);
+///
+/// // `ZZ` is used for unknown countries, and is not assigned.
+/// assert_eq!(Country::from_code("ZZ"), None);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Country(u8);
+
+impl Country {
+    /// Returns the country with an alpha-2 code, such as `"US"`, ignoring
+    /// case.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match *code.as_bytes() {
+            [a, b] => Self::from_bytes([a.to_ascii_uppercase(), b.to_ascii_uppercase()]),
+            _ => None,
+        }
+    }
+
+    /// Returns the country with an alpha-3 code, such as `"USA"`, ignoring
+    /// case.
+    pub fn from_alpha3(code: &str) -> Option<Self> {
+        Self::all().find(|country| country.alpha3().eq_ignore_ascii_case(code))
+    }
+
+    /// Returns the country with a numeric code, such as `840`.
+    pub fn from_numeric(numeric: u16) -> Option<Self> {
+        Self::all().find(|country| country.numeric() == numeric)
+    }
+
+    /// Returns an iterator over every country, in alpha-2 code order.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..COUNTRIES.len()).map(|index| Country(index as u8))
+    }
+
+    /// Returns the country with an upper-case alpha-2 code.
+    pub(crate) fn from_bytes(code: [u8; 2]) -> Option<Self> {
+        COUNTRIES
+            .binary_search_by(|entry| entry.alpha2.cmp(&code))
+            .ok()
+            .map(|index| Country(index as u8))
+    }
+
+    fn entry(&self) -> &'static Entry {
+        &COUNTRIES[usize::from(self.0)]
+    }
+
+    /// Returns the two-letter ISO 3166-1 alpha-2 code, such as `"US"`.
+    pub fn code(&self) -> &'static str {
+        // The table only contains ASCII letters.
+        core::str::from_utf8(&self.entry().alpha2).unwrap_or_default()
+    }
+
+    /// Returns the three-letter ISO 3166-1 alpha-3 code, such as `"USA"`.
+    pub fn alpha3(&self) -> &'static str {
+        self.entry().alpha3
+    }
+
+    /// Returns the ISO 3166-1 numeric code, such as `840`.
+    pub fn numeric(&self) -> u16 {
+        self.entry().numeric
+    }
+
+    /// Returns the English short name, such as `"United States"`.
+    pub fn name(&self) -> &'static str {
+        self.entry().name
+    }
+
+    /// Returns the UN M49 region the country belongs to, or `None` for
+    /// Antarctica.
+    pub fn region(&self) -> Option<Region> {
+        self.entry().region
+    }
+
+    /// Returns the continent the country belongs to.
+    pub fn continent(&self) -> Continent {
+        self.region()
+            .map_or(Continent::Antarctica, |region| region.continent())
+    }
+}
+
+impl fmt::Debug for Country {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Country").field(&self.code()).finish()
+    }
+}
+
+impl fmt::Display for Country {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Serializes as the alpha-2 code.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Country {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+/// Deserializes from an alpha-2 code, ignoring case.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Country {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = <alloc::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Country::from_code(&code).ok_or_else(|| {
+            serde::de::Error::custom(format_args!("unknown ISO 3166-1 alpha-2 code: {code}"))
+        })
+    }
+}
+
+/// Returns `true` if `code` is an officially assigned ISO 3166-1 alpha-2 code.
+#[cfg(any(feature = "std", test))]
+pub(crate) fn is_assigned(code: [u8; 2]) -> bool {
+    Country::from_bytes(code).is_some()
+}
+
+/// A row of the country table.
+struct Entry {
+    alpha2: [u8; 2],
+    alpha3: &'static str,
+    numeric: u16,
+    name: &'static str,
+    region: Option<Region>,
+}
+
+const fn entry(
+    alpha2: &[u8; 2],
+    alpha3: &'static str,
+    numeric: u16,
+    name: &'static str,
+    region: Option<Region>,
+) -> Entry {
+    Entry {
+        alpha2: *alpha2,
+        alpha3,
+        numeric,
+        name,
+        region,
+    }
+}
+
+/// The officially assigned ISO 3166-1 codes, sorted by alpha-2 code.
+const COUNTRIES: [Entry; 249] = [
+    entry(b"AD", "AND", 20, "Andorra", Some(Region::SouthernEurope)),
+    entry(
+        b"AE",
+        "ARE",
+        784,
+        "United Arab Emirates",
+        Some(Region::WesternAsia),
+    ),
+    entry(b"AF", "AFG", 4, "Afghanistan", Some(Region::SouthernAsia)),
+    entry(
+        b"AG",
+        "ATG",
+        28,
+        "Antigua and Barbuda",
+        Some(Region::Caribbean),
+    ),
+    entry(b"AI", "AIA", 660, "Anguilla", Some(Region::Caribbean)),
+    entry(b"AL", "ALB", 8, "Albania", Some(Region::SouthernEurope)),
+    entry(b"AM", "ARM", 51, "Armenia", Some(Region::WesternAsia)),
+    entry(b"AO", "AGO", 24, "Angola", Some(Region::MiddleAfrica)),
+    entry(b"AQ", "ATA", 10, "Antarctica", None),
+    entry(b"AR", "ARG", 32, "Argentina", Some(Region::SouthAmerica)),
+    entry(b"AS", "ASM", 16, "American Samoa", Some(Region::Polynesia)),
+    entry(b"AT", "AUT", 40, "Austria", Some(Region::WesternEurope)),
+    entry(
+        b"AU",
+        "AUS",
+        36,
+        "Australia",
+        Some(Region::AustraliaAndNewZealand),
+    ),
+    entry(b"AW", "ABW", 533, "Aruba", Some(Region::Caribbean)),
+    entry(
+        b"AX",
+        "ALA",
+        248,
+        "Åland Islands",
+        Some(Region::NorthernEurope),
+    ),
+    entry(b"AZ", "AZE", 31, "Azerbaijan", Some(Region::WesternAsia)),
+    entry(
+        b"BA",
+        "BIH",
+        70,
+        "Bosnia and Herzegovina",
+        Some(Region::SouthernEurope),
+    ),
+    entry(b"BB", "BRB", 52, "Barbados", Some(Region::Caribbean)),
+    entry(b"BD", "BGD", 50, "Bangladesh", Some(Region::SouthernAsia)),
+    entry(b"BE", "BEL", 56, "Belgium", Some(Region::WesternEurope)),
+    entry(
+        b"BF",
+        "BFA",
+        854,
+        "Burkina Faso",
+        Some(Region::WesternAfrica),
+    ),
+    entry(b"BG", "BGR", 100, "Bulgaria", Some(Region::EasternEurope)),
+    entry(b"BH", "BHR", 48, "Bahrain", Some(Region::WesternAsia)),
+    entry(b"BI", "BDI", 108, "Burundi", Some(Region::EasternAfrica)),
+    entry(b"BJ", "BEN", 204, "Benin", Some(Region::WesternAfrica)),
+    entry(
+        b"BL",
+        "BLM",
+        652,
+        "Saint Barthélemy",
+        Some(Region::Caribbean),
+    ),
+    entry(b"BM", "BMU", 60, "Bermuda", Some(Region::NorthernAmerica)),
+    entry(
+        b"BN",
+        "BRN",
+        96,
+        "Brunei Darussalam",
+        Some(Region::SouthEasternAsia),
+    ),
+    entry(b"BO", "BOL", 68, "Bolivia", Some(Region::SouthAmerica)),
+    entry(
+        b"BQ",
+        "BES",
+        535,
+        "Bonaire, Sint Eustatius and Saba",
+        Some(Region::Caribbean),
+    ),
+    entry(b"BR", "BRA", 76, "Brazil", Some(Region::SouthAmerica)),
+    entry(b"BS", "BHS", 44, "Bahamas", Some(Region::Caribbean)),
+    entry(b"BT", "BTN", 64, "Bhutan", Some(Region::SouthernAsia)),
+    entry(
+        b"BV",
+        "BVT",
+        74,
+        "Bouvet Island",
+        Some(Region::SouthAmerica),
+    ),
+    entry(b"BW", "BWA", 72, "Botswana", Some(Region::SouthernAfrica)),
+    entry(b"BY", "BLR", 112, "Belarus", Some(Region::EasternEurope)),
+    entry(b"BZ", "BLZ", 84, "Belize", Some(Region::CentralAmerica)),
+    entry(b"CA", "CAN", 124, "Canada", Some(Region::NorthernAmerica)),
+    entry(
+        b"CC",
+        "CCK",
+        166,
+        "Cocos (Keeling) Islands",
+        Some(Region::AustraliaAndNewZealand),
+    ),
+    entry(
+        b"CD",
+        "COD",
+        180,
+        "Congo, The Democratic Republic of the",
+        Some(Region::MiddleAfrica),
+    ),
+    entry(
+        b"CF",
+        "CAF",
+        140,
+        "Central African Republic",
+        Some(Region::MiddleAfrica),
+    ),
+    entry(b"CG", "COG", 178, "Congo", Some(Region::MiddleAfrica)),
+    entry(
+        b"CH",
+        "CHE",
+        756,
+        "Switzerland",
+        Some(Region::WesternEurope),
+    ),
+    entry(
+        b"CI",
+        "CIV",
+        384,
+        "Côte d'Ivoire",
+        Some(Region::WesternAfrica),
+    ),
+    entry(b"CK", "COK", 184, "Cook Islands", Some(Region::Polynesia)),
+    entry(b"CL", "CHL", 152, "Chile", Some(Region::SouthAmerica)),
+    entry(b"CM", "CMR", 120, "Cameroon", Some(Region::MiddleAfrica)),
+    entry(b"CN", "CHN", 156, "China", Some(Region::EasternAsia)),
+    entry(b"CO", "COL", 170, "Colombia", Some(Region::SouthAmerica)),
+    entry(
+        b"CR",
+        "CRI",
+        188,
+        "Costa Rica",
+        Some(Region::CentralAmerica),
+    ),
+    entry(b"CU", "CUB", 192, "Cuba", Some(Region::Caribbean)),
+    entry(b"CV", "CPV", 132, "Cabo Verde", Some(Region::WesternAfrica)),
+    entry(b"CW", "CUW", 531, "Curaçao", Some(Region::Caribbean)),
+    entry(
+        b"CX",
+        "CXR",
+        162,
+        "Christmas Island",
+        Some(Region::AustraliaAndNewZealand),
+    ),
+    entry(b"CY", "CYP", 196, "Cyprus", Some(Region::WesternAsia)),
+    entry(b"CZ", "CZE", 203, "Czechia", Some(Region::EasternEurope)),
+    entry(b"DE", "DEU", 276, "Germany", Some(Region::WesternEurope)),
+    entry(b"DJ", "DJI", 262, "Djibouti", Some(Region::EasternAfrica)),
+    entry(b"DK", "DNK", 208, "Denmark", Some(Region::NorthernEurope)),
+    entry(b"DM", "DMA", 212, "Dominica", Some(Region::Caribbean)),
+    entry(
+        b"DO",
+        "DOM",
+        214,
+        "Dominican Republic",
+        Some(Region::Caribbean),
+    ),
+    entry(b"DZ", "DZA", 12, "Algeria", Some(Region::NorthernAfrica)),
+    entry(b"EC", "ECU", 218, "Ecuador", Some(Region::SouthAmerica)),
+    entry(b"EE", "EST", 233, "Estonia", Some(Region::NorthernEurope)),
+    entry(b"EG", "EGY", 818, "Egypt", Some(Region::NorthernAfrica)),
+    entry(
+        b"EH",
+        "ESH",
+        732,
+        "Western Sahara",
+        Some(Region::NorthernAfrica),
+    ),
+    entry(b"ER", "ERI", 232, "Eritrea", Some(Region::EasternAfrica)),
+    entry(b"ES", "ESP", 724, "Spain", Some(Region::SouthernEurope)),
+    entry(b"ET", "ETH", 231, "Ethiopia", Some(Region::EasternAfrica)),
+    entry(b"FI", "FIN", 246, "Finland", Some(Region::NorthernEurope)),
+    entry(b"FJ", "FJI", 242, "Fiji", Some(Region::Melanesia)),
+    entry(
+        b"FK",
+        "FLK",
+        238,
+        "Falkland Islands (Malvinas)",
+        Some(Region::SouthAmerica),
+    ),
+    entry(
+        b"FM",
+        "FSM",
+        583,
+        "Micronesia, Federated States of",
+        Some(Region::Micronesia),
+    ),
+    entry(
+        b"FO",
+        "FRO",
+        234,
+        "Faroe Islands",
+        Some(Region::NorthernEurope),
+    ),
+    entry(b"FR", "FRA", 250, "France", Some(Region::WesternEurope)),
+    entry(b"GA", "GAB", 266, "Gabon", Some(Region::MiddleAfrica)),
+    entry(
+        b"GB",
+        "GBR",
+        826,
+        "United Kingdom",
+        Some(Region::NorthernEurope),
+    ),
+    entry(b"GD", "GRD", 308, "Grenada", Some(Region::Caribbean)),
+    entry(b"GE", "GEO", 268, "Georgia", Some(Region::WesternAsia)),
+    entry(
+        b"GF",
+        "GUF",
+        254,
+        "French Guiana",
+        Some(Region::SouthAmerica),
+    ),
+    entry(b"GG", "GGY", 831, "Guernsey", Some(Region::NorthernEurope)),
+    entry(b"GH", "GHA", 288, "Ghana", Some(Region::WesternAfrica)),
+    entry(b"GI", "GIB", 292, "Gibraltar", Some(Region::SouthernEurope)),
+    entry(
+        b"GL",
+        "GRL",
+        304,
+        "Greenland",
+        Some(Region::NorthernAmerica),
+    ),
+    entry(b"GM", "GMB", 270, "Gambia", Some(Region::WesternAfrica)),
+    entry(b"GN", "GIN", 324, "Guinea", Some(Region::WesternAfrica)),
+    entry(b"GP", "GLP", 312, "Guadeloupe", Some(Region::Caribbean)),
+    entry(
+        b"GQ",
+        "GNQ",
+        226,
+        "Equatorial Guinea",
+        Some(Region::MiddleAfrica),
+    ),
+    entry(b"GR", "GRC", 300, "Greece", Some(Region::SouthernEurope)),
+    entry(
+        b"GS",
+        "SGS",
+        239,
+        "South Georgia and the South Sandwich Islands",
+        Some(Region::SouthAmerica),
+    ),
+    entry(b"GT", "GTM", 320, "Guatemala", Some(Region::CentralAmerica)),
+    entry(b"GU", "GUM", 316, "Guam", Some(Region::Micronesia)),
+    entry(
+        b"GW",
+        "GNB",
+        624,
+        "Guinea-Bissau",
+        Some(Region::WesternAfrica),
+    ),
+    entry(b"GY", "GUY", 328, "Guyana", Some(Region::SouthAmerica)),
+    entry(b"HK", "HKG", 344, "Hong Kong", Some(Region::EasternAsia)),
+    entry(
+        b"HM",
+        "HMD",
+        334,
+        "Heard Island and McDonald Islands",
+        Some(Region::AustraliaAndNewZealand),
+    ),
+    entry(b"HN", "HND", 340, "Honduras", Some(Region::CentralAmerica)),
+    entry(b"HR", "HRV", 191, "Croatia", Some(Region::SouthernEurope)),
+    entry(b"HT", "HTI", 332, "Haiti", Some(Region::Caribbean)),
+    entry(b"HU", "HUN", 348, "Hungary", Some(Region::EasternEurope)),
+    entry(
+        b"ID",
+        "IDN",
+        360,
+        "Indonesia",
+        Some(Region::SouthEasternAsia),
+    ),
+    entry(b"IE", "IRL", 372, "Ireland", Some(Region::NorthernEurope)),
+    entry(b"IL", "ISR", 376, "Israel", Some(Region::WesternAsia)),
+    entry(
+        b"IM",
+        "IMN",
+        833,
+        "Isle of Man",
+        Some(Region::NorthernEurope),
+    ),
+    entry(b"IN", "IND", 356, "India", Some(Region::SouthernAsia)),
+    entry(
+        b"IO",
+        "IOT",
+        86,
+        "British Indian Ocean Territory",
+        Some(Region::EasternAfrica),
+    ),
+    entry(b"IQ", "IRQ", 368, "Iraq", Some(Region::WesternAsia)),
+    entry(b"IR", "IRN", 364, "Iran", Some(Region::SouthernAsia)),
+    entry(b"IS", "ISL", 352, "Iceland", Some(Region::NorthernEurope)),
+    entry(b"IT", "ITA", 380, "Italy", Some(Region::SouthernEurope)),
+    entry(b"JE", "JEY", 832, "Jersey", Some(Region::NorthernEurope)),
+    entry(b"JM", "JAM", 388, "Jamaica", Some(Region::Caribbean)),
+    entry(b"JO", "JOR", 400, "Jordan", Some(Region::WesternAsia)),
+    entry(b"JP", "JPN", 392, "Japan", Some(Region::EasternAsia)),
+    entry(b"KE", "KEN", 404, "Kenya", Some(Region::EasternAfrica)),
+    entry(b"KG", "KGZ", 417, "Kyrgyzstan", Some(Region::CentralAsia)),
+    entry(
+        b"KH",
+        "KHM",
+        116,
+        "Cambodia",
+        Some(Region::SouthEasternAsia),
+    ),
+    entry(b"KI", "KIR", 296, "Kiribati", Some(Region::Micronesia)),
+    entry(b"KM", "COM", 174, "Comoros", Some(Region::EasternAfrica)),
+    entry(
+        b"KN",
+        "KNA",
+        659,
+        "Saint Kitts and Nevis",
+        Some(Region::Caribbean),
+    ),
+    entry(b"KP", "PRK", 408, "North Korea", Some(Region::EasternAsia)),
+    entry(b"KR", "KOR", 410, "South Korea", Some(Region::EasternAsia)),
+    entry(b"KW", "KWT", 414, "Kuwait", Some(Region::WesternAsia)),
+    entry(b"KY", "CYM", 136, "Cayman Islands", Some(Region::Caribbean)),
+    entry(b"KZ", "KAZ", 398, "Kazakhstan", Some(Region::CentralAsia)),
+    entry(b"LA", "LAO", 418, "Laos", Some(Region::SouthEasternAsia)),
+    entry(b"LB", "LBN", 422, "Lebanon", Some(Region::WesternAsia)),
+    entry(b"LC", "LCA", 662, "Saint Lucia", Some(Region::Caribbean)),
+    entry(
+        b"LI",
+        "LIE",
+        438,
+        "Liechtenstein",
+        Some(Region::WesternEurope),
+    ),
+    entry(b"LK", "LKA", 144, "Sri Lanka", Some(Region::SouthernAsia)),
+    entry(b"LR", "LBR", 430, "Liberia", Some(Region::WesternAfrica)),
+    entry(b"LS", "LSO", 426, "Lesotho", Some(Region::SouthernAfrica)),
+    entry(b"LT", "LTU", 440, "Lithuania", Some(Region::NorthernEurope)),
+    entry(b"LU", "LUX", 442, "Luxembourg", Some(Region::WesternEurope)),
+    entry(b"LV", "LVA", 428, "Latvia", Some(Region::NorthernEurope)),
+    entry(b"LY", "LBY", 434, "Libya", Some(Region::NorthernAfrica)),
+    entry(b"MA", "MAR", 504, "Morocco", Some(Region::NorthernAfrica)),
+    entry(b"MC", "MCO", 492, "Monaco", Some(Region::WesternEurope)),
+    entry(b"MD", "MDA", 498, "Moldova", Some(Region::EasternEurope)),
+    entry(
+        b"ME",
+        "MNE",
+        499,
+        "Montenegro",
+        Some(Region::SouthernEurope),
+    ),
+    entry(
+        b"MF",
+        "MAF",
+        663,
+        "Saint Martin (French part)",
+        Some(Region::Caribbean),
+    ),
+    entry(b"MG", "MDG", 450, "Madagascar", Some(Region::EasternAfrica)),
+    entry(
+        b"MH",
+        "MHL",
+        584,
+        "Marshall Islands",
+        Some(Region::Micronesia),
+    ),
+    entry(
+        b"MK",
+        "MKD",
+        807,
+        "North Macedonia",
+        Some(Region::SouthernEurope),
+    ),
+    entry(b"ML", "MLI", 466, "Mali", Some(Region::WesternAfrica)),
+    entry(b"MM", "MMR", 104, "Myanmar", Some(Region::SouthEasternAsia)),
+    entry(b"MN", "MNG", 496, "Mongolia", Some(Region::EasternAsia)),
+    entry(b"MO", "MAC", 446, "Macao", Some(Region::EasternAsia)),
+    entry(
+        b"MP",
+        "MNP",
+        580,
+        "Northern Mariana Islands",
+        Some(Region::Micronesia),
+    ),
+    entry(b"MQ", "MTQ", 474, "Martinique", Some(Region::Caribbean)),
+    entry(b"MR", "MRT", 478, "Mauritania", Some(Region::WesternAfrica)),
+    entry(b"MS", "MSR", 500, "Montserrat", Some(Region::Caribbean)),
+    entry(b"MT", "MLT", 470, "Malta", Some(Region::SouthernEurope)),
+    entry(b"MU", "MUS", 480, "Mauritius", Some(Region::EasternAfrica)),
+    entry(b"MV", "MDV", 462, "Maldives", Some(Region::SouthernAsia)),
+    entry(b"MW", "MWI", 454, "Malawi", Some(Region::EasternAfrica)),
+    entry(b"MX", "MEX", 484, "Mexico", Some(Region::CentralAmerica)),
+    entry(
+        b"MY",
+        "MYS",
+        458,
+        "Malaysia",
+        Some(Region::SouthEasternAsia),
+    ),
+    entry(b"MZ", "MOZ", 508, "Mozambique", Some(Region::EasternAfrica)),
+    entry(b"NA", "NAM", 516, "Namibia", Some(Region::SouthernAfrica)),
+    entry(b"NC", "NCL", 540, "New Caledonia", Some(Region::Melanesia)),
+    entry(b"NE", "NER", 562, "Niger", Some(Region::WesternAfrica)),
+    entry(
+        b"NF",
+        "NFK",
+        574,
+        "Norfolk Island",
+        Some(Region::AustraliaAndNewZealand),
+    ),
+    entry(b"NG", "NGA", 566, "Nigeria", Some(Region::WesternAfrica)),
+    entry(b"NI", "NIC", 558, "Nicaragua", Some(Region::CentralAmerica)),
+    entry(
+        b"NL",
+        "NLD",
+        528,
+        "Netherlands",
+        Some(Region::WesternEurope),
+    ),
+    entry(b"NO", "NOR", 578, "Norway", Some(Region::NorthernEurope)),
+    entry(b"NP", "NPL", 524, "Nepal", Some(Region::SouthernAsia)),
+    entry(b"NR", "NRU", 520, "Nauru", Some(Region::Micronesia)),
+    entry(b"NU", "NIU", 570, "Niue", Some(Region::Polynesia)),
+    entry(
+        b"NZ",
+        "NZL",
+        554,
+        "New Zealand",
+        Some(Region::AustraliaAndNewZealand),
+    ),
+    entry(b"OM", "OMN", 512, "Oman", Some(Region::WesternAsia)),
+    entry(b"PA", "PAN", 591, "Panama", Some(Region::CentralAmerica)),
+    entry(b"PE", "PER", 604, "Peru", Some(Region::SouthAmerica)),
+    entry(
+        b"PF",
+        "PYF",
+        258,
+        "French Polynesia",
+        Some(Region::Polynesia),
+    ),
+    entry(
+        b"PG",
+        "PNG",
+        598,
+        "Papua New Guinea",
+        Some(Region::Melanesia),
+    ),
+    entry(
+        b"PH",
+        "PHL",
+        608,
+        "Philippines",
+        Some(Region::SouthEasternAsia),
+    ),
+    entry(b"PK", "PAK", 586, "Pakistan", Some(Region::SouthernAsia)),
+    entry(b"PL", "POL", 616, "Poland", Some(Region::EasternEurope)),
+    entry(
+        b"PM",
+        "SPM",
+        666,
+        "Saint Pierre and Miquelon",
+        Some(Region::NorthernAmerica),
+    ),
+    entry(b"PN", "PCN", 612, "Pitcairn", Some(Region::Polynesia)),
+    entry(b"PR", "PRI", 630, "Puerto Rico", Some(Region::Caribbean)),
+    entry(
+        b"PS",
+        "PSE",
+        275,
+        "Palestine, State of",
+        Some(Region::WesternAsia),
+    ),
+    entry(b"PT", "PRT", 620, "Portugal", Some(Region::SouthernEurope)),
+    entry(b"PW", "PLW", 585, "Palau", Some(Region::Micronesia)),
+    entry(b"PY", "PRY", 600, "Paraguay", Some(Region::SouthAmerica)),
+    entry(b"QA", "QAT", 634, "Qatar", Some(Region::WesternAsia)),
+    entry(b"RE", "REU", 638, "Réunion", Some(Region::EasternAfrica)),
+    entry(b"RO", "ROU", 642, "Romania", Some(Region::EasternEurope)),
+    entry(b"RS", "SRB", 688, "Serbia", Some(Region::SouthernEurope)),
+    entry(
+        b"RU",
+        "RUS",
+        643,
+        "Russian Federation",
+        Some(Region::EasternEurope),
+    ),
+    entry(b"RW", "RWA", 646, "Rwanda", Some(Region::EasternAfrica)),
+    entry(b"SA", "SAU", 682, "Saudi Arabia", Some(Region::WesternAsia)),
+    entry(b"SB", "SLB", 90, "Solomon Islands", Some(Region::Melanesia)),
+    entry(b"SC", "SYC", 690, "Seychelles", Some(Region::EasternAfrica)),
+    entry(b"SD", "SDN", 729, "Sudan", Some(Region::NorthernAfrica)),
+    entry(b"SE", "SWE", 752, "Sweden", Some(Region::NorthernEurope)),
+    entry(
+        b"SG",
+        "SGP",
+        702,
+        "Singapore",
+        Some(Region::SouthEasternAsia),
+    ),
+    entry(
+        b"SH",
+        "SHN",
+        654,
+        "Saint Helena, Ascension and Tristan da Cunha",
+        Some(Region::WesternAfrica),
+    ),
+    entry(b"SI", "SVN", 705, "Slovenia", Some(Region::SouthernEurope)),
+    entry(
+        b"SJ",
+        "SJM",
+        744,
+        "Svalbard and Jan Mayen",
+        Some(Region::NorthernEurope),
+    ),
+    entry(b"SK", "SVK", 703, "Slovakia", Some(Region::EasternEurope)),
+    entry(
+        b"SL",
+        "SLE",
+        694,
+        "Sierra Leone",
+        Some(Region::WesternAfrica),
+    ),
+    entry(
+        b"SM",
+        "SMR",
+        674,
+        "San Marino",
+        Some(Region::SouthernEurope),
+    ),
+    entry(b"SN", "SEN", 686, "Senegal", Some(Region::WesternAfrica)),
+    entry(b"SO", "SOM", 706, "Somalia", Some(Region::EasternAfrica)),
+    entry(b"SR", "SUR", 740, "Suriname", Some(Region::SouthAmerica)),
+    entry(
+        b"SS",
+        "SSD",
+        728,
+        "South Sudan",
+        Some(Region::EasternAfrica),
+    ),
+    entry(
+        b"ST",
+        "STP",
+        678,
+        "Sao Tome and Principe",
+        Some(Region::MiddleAfrica),
+    ),
+    entry(
+        b"SV",
+        "SLV",
+        222,
+        "El Salvador",
+        Some(Region::CentralAmerica),
+    ),
+    entry(
+        b"SX",
+        "SXM",
+        534,
+        "Sint Maarten (Dutch part)",
+        Some(Region::Caribbean),
+    ),
+    entry(b"SY", "SYR", 760, "Syria", Some(Region::WesternAsia)),
+    entry(b"SZ", "SWZ", 748, "Eswatini", Some(Region::SouthernAfrica)),
+    entry(
+        b"TC",
+        "TCA",
+        796,
+        "Turks and Caicos Islands",
+        Some(Region::Caribbean),
+    ),
+    entry(b"TD", "TCD", 148, "Chad", Some(Region::MiddleAfrica)),
+    entry(
+        b"TF",
+        "ATF",
+        260,
+        "French Southern Territories",
+        Some(Region::EasternAfrica),
+    ),
+    entry(b"TG", "TGO", 768, "Togo", Some(Region::WesternAfrica)),
+    entry(
+        b"TH",
+        "THA",
+        764,
+        "Thailand",
+        Some(Region::SouthEasternAsia),
+    ),
+    entry(b"TJ", "TJK", 762, "Tajikistan", Some(Region::CentralAsia)),
+    entry(b"TK", "TKL", 772, "Tokelau", Some(Region::Polynesia)),
+    entry(
+        b"TL",
+        "TLS",
+        626,
+        "Timor-Leste",
+        Some(Region::SouthEasternAsia),
+    ),
+    entry(b"TM", "TKM", 795, "Turkmenistan", Some(Region::CentralAsia)),
+    entry(b"TN", "TUN", 788, "Tunisia", Some(Region::NorthernAfrica)),
+    entry(b"TO", "TON", 776, "Tonga", Some(Region::Polynesia)),
+    entry(b"TR", "TUR", 792, "Türkiye", Some(Region::WesternAsia)),
+    entry(
+        b"TT",
+        "TTO",
+        780,
+        "Trinidad and Tobago",
+        Some(Region::Caribbean),
+    ),
+    entry(b"TV", "TUV", 798, "Tuvalu", Some(Region::Polynesia)),
+    entry(b"TW", "TWN", 158, "Taiwan", Some(Region::EasternAsia)),
+    entry(b"TZ", "TZA", 834, "Tanzania", Some(Region::EasternAfrica)),
+    entry(b"UA", "UKR", 804, "Ukraine", Some(Region::EasternEurope)),
+    entry(b"UG", "UGA", 800, "Uganda", Some(Region::EasternAfrica)),
+    entry(
+        b"UM",
+        "UMI",
+        581,
+        "United States Minor Outlying Islands",
+        Some(Region::Micronesia),
+    ),
+    entry(
+        b"US",
+        "USA",
+        840,
+        "United States",
+        Some(Region::NorthernAmerica),
+    ),
+    entry(b"UY", "URY", 858, "Uruguay", Some(Region::SouthAmerica)),
+    entry(b"UZ", "UZB", 860, "Uzbekistan", Some(Region::CentralAsia)),
+    entry(
+        b"VA",
+        "VAT",
+        336,
+        "Holy See (Vatican City State)",
+        Some(Region::SouthernEurope),
+    ),
+    entry(
+        b"VC",
+        "VCT",
+        670,
+        "Saint Vincent and the Grenadines",
+        Some(Region::Caribbean),
+    ),
+    entry(b"VE", "VEN", 862, "Venezuela", Some(Region::SouthAmerica)),
+    entry(
+        b"VG",
+        "VGB",
+        92,
+        "Virgin Islands, British",
+        Some(Region::Caribbean),
+    ),
+    entry(
+        b"VI",
+        "VIR",
+        850,
+        "Virgin Islands, U.S.",
+        Some(Region::Caribbean),
+    ),
+    entry(b"VN", "VNM", 704, "Vietnam", Some(Region::SouthEasternAsia)),
+    entry(b"VU", "VUT", 548, "Vanuatu", Some(Region::Melanesia)),
+    entry(
+        b"WF",
+        "WLF",
+        876,
+        "Wallis and Futuna",
+        Some(Region::Polynesia),
+    ),
+    entry(b"WS", "WSM", 882, "Samoa", Some(Region::Polynesia)),
+    entry(b"YE", "YEM", 887, "Yemen", Some(Region::WesternAsia)),
+    entry(b"YT", "MYT", 175, "Mayotte", Some(Region::EasternAfrica)),
+    entry(
+        b"ZA",
+        "ZAF",
+        710,
+        "South Africa",
+        Some(Region::SouthernAfrica),
+    ),
+    entry(b"ZM", "ZMB", 894, "Zambia", Some(Region::EasternAfrica)),
+    entry(b"ZW", "ZWE", 716, "Zimbabwe", Some(Region::EasternAfrica)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_is_sorted() {
+        assert!(COUNTRIES.windows(2).all(|w| w[0].alpha2 < w[1].alpha2));
+        assert!(COUNTRIES.len() <= usize::from(u8::MAX));
+    }
+
+    #[test]
+    fn test_is_assigned() {
+        assert!(is_assigned(*b"US"));
+        assert!(is_assigned(*b"AD"));
+        assert!(is_assigned(*b"ZW"));
+        // User-assigned and exceptionally reserved codes are not assigned.
+        assert!(!is_assigned(*b"ZZ"));
+        assert!(!is_assigned(*b"EU"));
+        assert!(!is_assigned(*b"us"));
+    }
+
+    #[test]
+    fn test_codes_are_unique() {
+        let mut alpha3: Vec<_> = Country::all().map(|c| c.alpha3()).collect();
+        let mut numeric: Vec<_> = Country::all().map(|c| c.numeric()).collect();
+        alpha3.sort_unstable();
+        alpha3.dedup();
+        numeric.sort_unstable();
+        numeric.dedup();
+        assert_eq!(alpha3.len(), COUNTRIES.len());
+        assert_eq!(numeric.len(), COUNTRIES.len());
+    }
+}
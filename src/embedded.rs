@@ -0,0 +1,128 @@
+//! Contains the logic for extracting IPv4 addresses embedded in IPv6 addresses.
+//!
+//! Dual-stack hosts and transition mechanisms often present an IPv4 client as
+//! an IPv6 address, such as `::ffff:192.0.2.1` from a dual-stack socket or
+//! `64:ff9b::c000:201` from a NAT64 gateway. A dataset only maps the IPv4
+//! address, so [`IpAsnMap::lookup_embedded`](crate::IpAsnMap::lookup_embedded)
+//! extracts it and looks it up instead.
+
+use crate::AsnInfoView;
+use core::fmt;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// A way of embedding an IPv4 address in an IPv6 address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[non_exhaustive]
+pub enum Ipv4Embedding {
+    /// An IPv4-mapped address in `::ffff:0:0/96` (RFC 4291), with the IPv4
+    /// address in the last 32 bits.
+    Mapped,
+    /// An address in the NAT64 well-known prefix `64:ff9b::/96` (RFC 6052),
+    /// with the IPv4 address in the last 32 bits.
+    Nat64,
+    /// A 6to4 address in `2002::/16` (RFC 3056), with the IPv4 address of the
+    /// site in the 32 bits after the prefix.
+    SixToFour,
+    /// A Teredo address in `2001::/32` (RFC 4380). The IPv4 address is the
+    /// client's public address, stored inverted in the last 32 bits.
+    Teredo,
+}
+
+impl Ipv4Embedding {
+    /// Returns the name of the embedding, such as `"nat64"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Ipv4Embedding::Mapped => "mapped",
+            Ipv4Embedding::Nat64 => "nat64",
+            Ipv4Embedding::SixToFour => "six_to_four",
+            Ipv4Embedding::Teredo => "teredo",
+        }
+    }
+}
+
+impl fmt::Display for Ipv4Embedding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Extracts the IPv4 address embedded in an IPv6 address, with the embedding
+/// it was found in, or returns `None` if the address embeds none.
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::embedded::{self, Ipv4Embedding};
+/// use std::net::Ipv4Addr;
+///
+/// let ip = "2002:c000:201::1".parse().unwrap();
+/// assert_eq!(
+///     embedded::extract(ip),
+///     Some((Ipv4Embedding::SixToFour, Ipv4Addr::new(192, 0, 2, 1)))
+/// );
+/// assert_eq!(embedded::extract("2606:4700::1111".parse().unwrap()), None);
+/// ```
+pub fn extract(ip: Ipv6Addr) -> Option<(Ipv4Embedding, Ipv4Addr)> {
+    let bits = u128::from(ip);
+    let low = Ipv4Addr::from(bits as u32);
+    match ip.segments() {
+        [0, 0, 0, 0, 0, 0xffff, _, _] => Some((Ipv4Embedding::Mapped, low)),
+        [0x64, 0xff9b, 0, 0, 0, 0, _, _] => Some((Ipv4Embedding::Nat64, low)),
+        [0x2002, ..] => Some((
+            Ipv4Embedding::SixToFour,
+            Ipv4Addr::from((bits >> 80) as u32),
+        )),
+        [0x2001, 0, ..] => Some((Ipv4Embedding::Teredo, Ipv4Addr::from(!(bits as u32)))),
+        _ => None,
+    }
+}
+
+/// The result of [`IpAsnMap::lookup_embedded`](crate::IpAsnMap::lookup_embedded).
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EmbeddedLookup<'a> {
+    /// The address that was found in the map: the embedded IPv4 address, or
+    /// the original address if it embeds none or the IPv4 address was not
+    /// found.
+    pub ip: IpAddr,
+    /// The embedding the IPv4 address was extracted from, or `None` if the
+    /// original address was looked up.
+    pub embedding: Option<Ipv4Embedding>,
+    /// The ASN information for `ip`.
+    pub info: AsnInfoView<'a>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        for (ip, expected) in [
+            ("::ffff:1.2.3.4", Some((Ipv4Embedding::Mapped, "1.2.3.4"))),
+            ("64:ff9b::1.2.3.4", Some((Ipv4Embedding::Nat64, "1.2.3.4"))),
+            (
+                "2002:102:304:1::5",
+                Some((Ipv4Embedding::SixToFour, "1.2.3.4")),
+            ),
+            // The Teredo example of RFC 4380, for client 192.0.2.45 behind
+            // server 65.54.227.120.
+            (
+                "2001:0:4136:e378:8000:63bf:3fff:fdd2",
+                Some((Ipv4Embedding::Teredo, "192.0.2.45")),
+            ),
+            ("::1.2.3.4", None),
+            ("64:ff9b:1::1.2.3.4", None),
+            ("2001:db8::1", None),
+            ("2003::1", None),
+        ] {
+            let expected = expected.map(|(embedding, v4)| (embedding, v4.parse().unwrap()));
+            assert_eq!(extract(ip.parse().unwrap()), expected, "{ip}");
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! Contains the logic for writing an `IpAsnMap` back out as `iptoasn.com` TSV.
+//!
+//! The map stores CIDR blocks, some of which may be nested inside others. On
+//! export they are flattened into the address ranges that lookups actually
+//! resolve to, and adjacent ranges with identical records are merged, so the
+//! output has one line per contiguous range.
+
+use crate::types::AsnRecord;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use ip_network::{Ipv4Network, Ipv6Network};
+use std::io::{self, BufWriter, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// An IP address family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum IpFamily {
+    /// IPv4 addresses.
+    Ipv4,
+    /// IPv6 addresses.
+    Ipv6,
+}
+
+/// Options for [`IpAsnMap::write_tsv_with`](crate::IpAsnMap::write_tsv_with).
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::export::{IpFamily, TsvOptions};
+///
+/// let options = TsvOptions::new().gzip(true).family(IpFamily::Ipv4);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TsvOptions {
+    gzip: bool,
+    family: Option<IpFamily>,
+}
+
+impl TsvOptions {
+    /// Creates the default options: uncompressed, with both address families.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the output is gzip-compressed.
+    pub fn gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+
+    /// Restricts the output to a single address family.
+    pub fn family(mut self, family: IpFamily) -> Self {
+        self.family = Some(family);
+        self
+    }
+}
+
+/// A contiguous, inclusive range of addresses sharing one record, with the
+/// addresses held as integers of either family.
+pub(crate) type Span = (u128, u128, AsnRecord);
+
+/// Resolves the IPv4 blocks of a map into the spans lookups return.
+pub(crate) fn ipv4_spans<'t>(
+    ipv4: impl Iterator<Item = (Ipv4Network, &'t AsnRecord)>,
+) -> Vec<Span> {
+    let blocks = ipv4.map(|(network, record)| {
+        let start = u32::from(network.network_address());
+        let end = u32::from(network.broadcast_address());
+        (u128::from(start), u128::from(end), *record)
+    });
+    flatten(blocks.collect())
+}
+
+/// Resolves the IPv6 blocks of a map into the spans lookups return.
+pub(crate) fn ipv6_spans<'t>(
+    ipv6: impl Iterator<Item = (Ipv6Network, &'t AsnRecord)>,
+) -> Vec<Span> {
+    let blocks = ipv6.map(|(network, record)| {
+        let start = u128::from(network.network_address());
+        let end = u128::from(network.last_address());
+        (start, end, *record)
+    });
+    flatten(blocks.collect())
+}
+
+/// Writes the IPv4 and IPv6 blocks of a map as TSV lines.
+pub(crate) fn write_tsv<'t>(
+    writer: impl Write,
+    options: TsvOptions,
+    ipv4: impl Iterator<Item = (Ipv4Network, &'t AsnRecord)>,
+    ipv6: impl Iterator<Item = (Ipv6Network, &'t AsnRecord)>,
+    organizations: &[String],
+) -> io::Result<()> {
+    if options.gzip {
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        write_lines(&mut encoder, options.family, ipv4, ipv6, organizations)?;
+        encoder.finish()?.flush()
+    } else {
+        let mut writer = writer;
+        write_lines(&mut writer, options.family, ipv4, ipv6, organizations)?;
+        writer.flush()
+    }
+}
+
+fn write_lines<'t>(
+    writer: impl Write,
+    family: Option<IpFamily>,
+    ipv4: impl Iterator<Item = (Ipv4Network, &'t AsnRecord)>,
+    ipv6: impl Iterator<Item = (Ipv6Network, &'t AsnRecord)>,
+    organizations: &[String],
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    if family != Some(IpFamily::Ipv6) {
+        for (start, end, record) in ipv4_spans(ipv4) {
+            let start = IpAddr::V4(Ipv4Addr::from(start as u32));
+            let end = IpAddr::V4(Ipv4Addr::from(end as u32));
+            write_line(&mut writer, start, end, &record, organizations)?;
+        }
+    }
+    if family != Some(IpFamily::Ipv4) {
+        for (start, end, record) in ipv6_spans(ipv6) {
+            let start = IpAddr::V6(Ipv6Addr::from(start));
+            let end = IpAddr::V6(Ipv6Addr::from(end));
+            write_line(&mut writer, start, end, &record, organizations)?;
+        }
+    }
+    writer.flush()
+}
+
+fn write_line(
+    writer: &mut impl Write,
+    start: IpAddr,
+    end: IpAddr,
+    record: &AsnRecord,
+    organizations: &[String],
+) -> io::Result<()> {
+    let country_code = match &record.country_code {
+        b"ZZ" => "None",
+        code => std::str::from_utf8(code).unwrap_or("None"),
+    };
+    let organization = &organizations[record.organization_idx as usize];
+    // A tab would split the organization into an extra column, and a newline
+    // or carriage return into an extra line.
+    let organization = organization.replace(|c: char| c.is_ascii_control(), " ");
+    writeln!(
+        writer,
+        "{start}\t{end}\t{}\t{country_code}\t{organization}",
+        record.asn
+    )
+}
+
+/// Resolves possibly nested blocks into the non-overlapping spans that a
+/// longest-prefix match would return, merging adjacent spans with equal
+/// records.
+fn flatten(mut blocks: Vec<Span>) -> Vec<Span> {
+    // Enclosing blocks sort before the blocks nested inside them.
+    blocks.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+
+    let mut spans: Vec<Span> = Vec::new();
+    let mut push = |start: u128, end: u128, record: AsnRecord| match spans.last_mut() {
+        Some(last) if last.2 == record && last.1.checked_add(1) == Some(start) => last.1 = end,
+        _ => spans.push((start, end, record)),
+    };
+
+    // The blocks containing the current address, innermost last, and the
+    // first address not yet emitted.
+    let mut stack: Vec<(u128, AsnRecord)> = Vec::new();
+    let mut cursor: u128 = 0;
+    for (start, end, record) in blocks {
+        while let Some(&(outer_end, outer_record)) = stack.last() {
+            if outer_end >= start {
+                break;
+            }
+            if cursor <= outer_end {
+                push(cursor, outer_end, outer_record);
+                cursor = outer_end + 1;
+            }
+            stack.pop();
+        }
+        if let Some(&(_, outer_record)) = stack.last() {
+            if cursor < start {
+                push(cursor, start - 1, outer_record);
+            }
+        }
+        cursor = start;
+        stack.push((end, record));
+    }
+    while let Some((outer_end, outer_record)) = stack.pop() {
+        if cursor <= outer_end {
+            push(cursor, outer_end, outer_record);
+            match outer_end.checked_add(1) {
+                Some(next) => cursor = next,
+                None => break,
+            }
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asn::Asn;
+
+    fn record(asn: u32) -> AsnRecord {
+        AsnRecord {
+            asn: Asn::new(asn),
+            country_code: *b"ZZ",
+            organization_idx: 0,
+        }
+    }
+
+    #[test]
+    fn test_flatten_nested_blocks() {
+        // 0-255 is AS1, with 16-31 overridden by AS2 and 20-23 by AS3.
+        let blocks = vec![
+            (16, 31, record(2)),
+            (0, 255, record(1)),
+            (20, 23, record(3)),
+        ];
+        assert_eq!(
+            flatten(blocks),
+            vec![
+                (0, 15, record(1)),
+                (16, 19, record(2)),
+                (20, 23, record(3)),
+                (24, 31, record(2)),
+                (32, 255, record(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flatten_merges_adjacent_blocks() {
+        let blocks = vec![
+            (0, 127, record(1)),
+            (128, 255, record(1)),
+            (256, 511, record(2)),
+            (1024, 1279, record(2)),
+        ];
+        assert_eq!(
+            flatten(blocks),
+            vec![
+                (0, 255, record(1)),
+                (256, 511, record(2)),
+                (1024, 1279, record(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flatten_whole_address_space() {
+        let blocks = vec![(0, u128::MAX, record(1)), (u128::MAX, u128::MAX, record(2))];
+        assert_eq!(
+            flatten(blocks),
+            vec![
+                (0, u128::MAX - 1, record(1)),
+                (u128::MAX, u128::MAX, record(2))
+            ]
+        );
+    }
+}
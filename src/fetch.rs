@@ -0,0 +1,373 @@
+//! Contains a cached HTTP fetcher with conditional and resumable downloads.
+//!
+//! A [`Fetcher`] keeps a copy of a remote dataset in a cache file. Next to it,
+//! a metadata file records the `ETag` and `Last-Modified` headers the file was
+//! served with, so the next fetch can send `If-None-Match` and
+//! `If-Modified-Since` and skip the download when the server answers
+//! `304 Not Modified`.
+//!
+//! A download is written to a `.part` file first, and only replaces the cache
+//! file once it is complete. If it is interrupted, the next fetch resumes it
+//! with a `Range` request, guarded by `If-Range` so that a dataset changed in
+//! the meantime is downloaded from the start instead of being spliced.
+//!
+//! For a cache file `data.tsv.gz`, the files used are:
+//!
+//! * `data.tsv.gz`: the complete dataset.
+//! * `data.tsv.gz.meta`: the validators of the complete dataset.
+//! * `data.tsv.gz.part`: an unfinished download.
+//! * `data.tsv.gz.part.meta`: the validators of the unfinished download.
+
+use crate::Error;
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{
+    HeaderMap, HeaderName, CONTENT_RANGE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE,
+    LAST_MODIFIED, RANGE,
+};
+use reqwest::StatusCode;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The validators a cached file was served with, saved next to it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CacheMetadata {
+    /// The `ETag` header, including its quotes and any weak prefix.
+    pub etag: Option<String>,
+    /// The `Last-Modified` header, as an HTTP date.
+    pub last_modified: Option<String>,
+}
+
+impl CacheMetadata {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: HeaderName| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    /// Returns `true` if neither validator is known.
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// Reads the metadata saved at `path`, or returns `None` if there is none.
+    fn read(path: &Path) -> Result<Option<Self>, Error> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut metadata = Self::default();
+        for line in contents.lines() {
+            match line.split_once(": ") {
+                Some(("ETag", value)) => metadata.etag = Some(value.to_string()),
+                Some(("Last-Modified", value)) => metadata.last_modified = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        Ok(Some(metadata))
+    }
+
+    /// Saves the metadata to `path`, in the same `Name: value` form as the
+    /// headers it came from.
+    fn write(&self, path: &Path) -> Result<(), Error> {
+        let mut contents = String::new();
+        if let Some(etag) = &self.etag {
+            contents.push_str(&format!("ETag: {etag}\n"));
+        }
+        if let Some(last_modified) = &self.last_modified {
+            contents.push_str(&format!("Last-Modified: {last_modified}\n"));
+        }
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Returns the validator to send in an `If-Range` header, preferring the
+    /// `ETag` unless it is weak, which `If-Range` does not allow.
+    fn if_range(&self) -> Option<&str> {
+        match &self.etag {
+            Some(etag) if !etag.starts_with("W/") => Some(etag),
+            _ => self.last_modified.as_deref(),
+        }
+    }
+}
+
+/// The result of [`Fetcher::fetch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FetchOutcome {
+    /// The server reported that the cached file is up to date, and nothing
+    /// was downloaded.
+    NotModified,
+    /// The whole file was downloaded.
+    Downloaded {
+        /// The size of the file in bytes.
+        size: u64,
+    },
+    /// An interrupted download was resumed and completed.
+    Resumed {
+        /// The number of bytes that had already been downloaded.
+        offset: u64,
+        /// The size of the file in bytes.
+        size: u64,
+    },
+}
+
+/// Keeps a cached copy of a remote dataset up to date.
+///
+/// This type is only available when the `fetch` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use ip2asn::fetch::{FetchOutcome, Fetcher};
+/// use ip2asn::Builder;
+///
+/// # fn main() -> Result<(), ip2asn::Error> {
+/// let fetcher = Fetcher::new(
+///     "https://iptoasn.com/data/ip2asn-combined.tsv.gz",
+///     "/var/cache/ip2asn/ip2asn-combined.tsv.gz",
+/// );
+/// match fetcher.fetch()? {
+///     FetchOutcome::NotModified => println!("already up to date"),
+///     outcome => println!("updated: {outcome:?}"),
+/// }
+///
+/// let map = Builder::new().from_path(fetcher.cache_path())?.build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fetcher {
+    client: Client,
+    url: String,
+    cache_path: PathBuf,
+}
+
+impl Fetcher {
+    /// Creates a fetcher that keeps the file at `url` cached at `cache_path`.
+    pub fn new(url: impl Into<String>, cache_path: impl Into<PathBuf>) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.into(),
+            cache_path: cache_path.into(),
+        }
+    }
+
+    /// Sets the HTTP client used for requests, for example to configure
+    /// timeouts or a proxy.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Returns the URL of the remote file.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the path of the cache file.
+    pub fn cache_path(&self) -> &Path {
+        &self.cache_path
+    }
+
+    /// Returns the metadata saved for the cache file, or `None` if there is
+    /// none.
+    pub fn metadata(&self) -> Result<Option<CacheMetadata>, Error> {
+        CacheMetadata::read(&self.sibling(".meta"))
+    }
+
+    /// Brings the cache file up to date with the remote file.
+    ///
+    /// If an earlier download was interrupted, it is resumed. Otherwise, if
+    /// the cache file exists, the request is made conditional on its saved
+    /// metadata, and [`FetchOutcome::NotModified`] is returned if the server
+    /// reports it is unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Http`] if the request fails or the server responds
+    /// with an error status, and [`Error::Io`] if the cache files cannot be
+    /// read or written. If the download is interrupted, the partial file is
+    /// kept for the next call to resume.
+    pub fn fetch(&self) -> Result<FetchOutcome, Error> {
+        if let Some(outcome) = self.resume()? {
+            return Ok(outcome);
+        }
+
+        let mut request = self.client.get(&self.url);
+        if self.cache_path.exists() {
+            if let Some(metadata) = self.metadata()? {
+                if let Some(etag) = &metadata.etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = &metadata.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+        }
+
+        let response = request.send()?.error_for_status()?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(FetchOutcome::NotModified);
+        }
+        let size = self.download(response, 0)?;
+        Ok(FetchOutcome::Downloaded { size })
+    }
+
+    /// Resumes an interrupted download, returning `None` if there is none to
+    /// resume, or if it had to be discarded.
+    fn resume(&self) -> Result<Option<FetchOutcome>, Error> {
+        let part_path = self.sibling(".part");
+        let offset = match fs::metadata(&part_path) {
+            Ok(metadata) => metadata.len(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let metadata = CacheMetadata::read(&self.sibling(".part.meta"))?;
+        // Without a validator, there is no way to tell whether the remote
+        // file has changed since the partial download started.
+        let validator = metadata.as_ref().and_then(CacheMetadata::if_range);
+        let (Some(validator), 1..) = (validator, offset) else {
+            self.discard_part()?;
+            return Ok(None);
+        };
+
+        let response = self
+            .client
+            .get(&self.url)
+            .header(RANGE, format!("bytes={offset}-"))
+            .header(IF_RANGE, validator)
+            .send()?;
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT if content_range_start(&response) == Some(offset) => {
+                let size = self.download(response, offset)?;
+                Ok(Some(FetchOutcome::Resumed { offset, size }))
+            }
+            StatusCode::PARTIAL_CONTENT | StatusCode::RANGE_NOT_SATISFIABLE => {
+                self.discard_part()?;
+                Ok(None)
+            }
+            _ => {
+                // The remote file changed, so the server sent all of it.
+                let response = response.error_for_status()?;
+                let size = self.download(response, 0)?;
+                Ok(Some(FetchOutcome::Downloaded { size }))
+            }
+        }
+    }
+
+    /// Writes a response body to the `.part` file from `offset`, then moves
+    /// it into place as the cache file, returning its size.
+    fn download(&self, mut response: Response, offset: u64) -> Result<u64, Error> {
+        let part_path = self.sibling(".part");
+        let part_meta_path = self.sibling(".part.meta");
+        if let Some(parent) = self.cache_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = if offset == 0 {
+            // Saved before the body, so an interrupted download can resume.
+            CacheMetadata::from_headers(response.headers()).write(&part_meta_path)?;
+            File::create(&part_path)?
+        } else {
+            OpenOptions::new().append(true).open(&part_path)?
+        };
+        io::copy(&mut response, &mut file)?;
+        file.flush()?;
+        let size = file.metadata()?.len();
+        drop(file);
+
+        fs::rename(&part_path, &self.cache_path)?;
+        fs::rename(&part_meta_path, self.sibling(".meta"))?;
+        Ok(size)
+    }
+
+    /// Removes an unfinished download and its metadata.
+    fn discard_part(&self) -> Result<(), Error> {
+        for path in [self.sibling(".part"), self.sibling(".part.meta")] {
+            match fs::remove_file(path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the cache path with `suffix` appended to its file name.
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut path = OsString::from(self.cache_path.as_os_str());
+        path.push(suffix);
+        PathBuf::from(path)
+    }
+}
+
+/// Returns the first byte position of a `Content-Range: bytes` header.
+fn content_range_start(response: &Response) -> Option<u64> {
+    let value = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    let (start, _) = value.strip_prefix("bytes ")?.split_once('-')?;
+    start.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.meta");
+        assert_eq!(CacheMetadata::read(&path).unwrap(), None);
+
+        let metadata = CacheMetadata {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+        };
+        metadata.write(&path).unwrap();
+        assert_eq!(CacheMetadata::read(&path).unwrap(), Some(metadata));
+
+        CacheMetadata::default().write(&path).unwrap();
+        let metadata = CacheMetadata::read(&path).unwrap().unwrap();
+        assert!(metadata.is_empty());
+    }
+
+    #[test]
+    fn test_if_range_skips_weak_etags() {
+        let last_modified = "Wed, 21 Oct 2015 07:28:00 GMT".to_string();
+        let metadata = CacheMetadata {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: Some(last_modified.clone()),
+        };
+        assert_eq!(metadata.if_range(), Some("\"abc\""));
+
+        let metadata = CacheMetadata {
+            etag: Some("W/\"abc\"".to_string()),
+            last_modified: Some(last_modified),
+        };
+        assert_eq!(metadata.if_range(), Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+
+        let metadata = CacheMetadata {
+            etag: Some("W/\"abc\"".to_string()),
+            last_modified: None,
+        };
+        assert_eq!(metadata.if_range(), None);
+    }
+
+    #[test]
+    fn test_sibling_paths() {
+        let fetcher = Fetcher::new("http://example.com/data", "/tmp/cache/data.tsv.gz");
+        assert_eq!(
+            fetcher.sibling(".part.meta"),
+            Path::new("/tmp/cache/data.tsv.gz.part.meta")
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Contains the logic for parsing MaxMind GeoLite2 CSV databases.
+//!
+//! The GeoLite2-ASN export is split into `GeoLite2-ASN-Blocks-IPv4.csv` and
+//! `GeoLite2-ASN-Blocks-IPv6.csv`, each with the header
+//! `network,autonomous_system_number,autonomous_system_organization`. Rows are
+//! CIDR-based and carry no country, so records built from them are given the
+//! unknown country `ZZ`. A country can be joined in from the GeoLite2-Country
+//! export with
+//! [`Builder::with_geolite2_countries`](crate::Builder::with_geolite2_countries).
+
+use crate::asn::Asn;
+use crate::{Error, ParseErrorKind};
+use ip_network::IpNetwork;
+use ip_network_table::IpNetworkTable;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::str::FromStr;
+
+/// Returns `true` if the line is the header row of a GeoLite2 blocks file.
+///
+/// Every GeoLite2 blocks file starts with a header whose first column is
+/// `network`, so the IPv4 and IPv6 files can be concatenated into a single
+/// source.
+pub fn is_header(line: &str) -> bool {
+    line.starts_with("network,")
+}
+
+/// A successfully parsed row of a GeoLite2-ASN blocks file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct AsnBlock<'a> {
+    /// The network block covered by the row.
+    pub network: IpNetwork,
+    /// The Autonomous System Number (ASN).
+    pub asn: Asn,
+    /// The name of the organization that owns the network.
+    ///
+    /// This borrows from the line unless the field contained escaped quotes.
+    pub organization: Cow<'a, str>,
+}
+
+/// Parses a single row of a GeoLite2-ASN blocks CSV file.
+///
+/// The expected format is: `NETWORK,ASN,ORGANIZATION`, where the organization
+/// may be quoted.
+///
+/// # Errors
+///
+/// Returns a `ParseErrorKind` if the row does not have three columns, the
+/// network is not valid CIDR notation, or the ASN is not a number.
+pub fn parse_asn_line(line: &str) -> Result<AsnBlock<'_>, ParseErrorKind> {
+    const EXPECTED_COLUMNS: usize = 3;
+    let mut fields = split_record(line);
+    if fields.len() != EXPECTED_COLUMNS {
+        return Err(ParseErrorKind::IncorrectColumnCount {
+            expected: EXPECTED_COLUMNS,
+            found: fields.len(),
+        });
+    }
+
+    let network = parse_network(&fields[0])?;
+    let asn =
+        u32::from_str(&fields[1])
+            .map(Asn::new)
+            .map_err(|_| ParseErrorKind::InvalidAsnNumber {
+                value: fields[1].to_string(),
+            })?;
+    let organization = fields.pop().unwrap_or_default();
+
+    Ok(AsnBlock {
+        network,
+        asn,
+        organization,
+    })
+}
+
+fn parse_network(value: &str) -> Result<IpNetwork, ParseErrorKind> {
+    IpNetwork::from_str(value).map_err(|_| ParseErrorKind::InvalidNetwork {
+        value: value.to_string(),
+    })
+}
+
+/// Splits a CSV record into its fields.
+///
+/// Quoted fields have their surrounding quotes removed. Fields containing
+/// doubled (`""`) quotes are unescaped into an owned string.
+fn split_record(line: &str) -> Vec<Cow<'_, str>> {
+    let mut fields = Vec::new();
+    let mut rest = line;
+    loop {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let mut end = 0;
+            let mut escaped = false;
+            let bytes = quoted.as_bytes();
+            while end < bytes.len() {
+                if bytes[end] == b'"' {
+                    if bytes.get(end + 1) == Some(&b'"') {
+                        escaped = true;
+                        end += 2;
+                        continue;
+                    }
+                    break;
+                }
+                end += 1;
+            }
+            let field = &quoted[..end];
+            fields.push(if escaped {
+                Cow::Owned(field.replace("\"\"", "\""))
+            } else {
+                Cow::Borrowed(field)
+            });
+            rest = quoted.get(end + 1..).unwrap_or_default();
+            match rest.strip_prefix(',') {
+                Some(next) => rest = next,
+                None => break,
+            }
+        } else {
+            match rest.split_once(',') {
+                Some((field, next)) => {
+                    fields.push(Cow::Borrowed(field));
+                    rest = next;
+                }
+                None => {
+                    fields.push(Cow::Borrowed(rest));
+                    break;
+                }
+            }
+        }
+    }
+    fields
+}
+
+/// Loads a GeoLite2-Country database into a table of country codes.
+///
+/// `blocks` is a `GeoLite2-Country-Blocks-IPv4.csv` or `-IPv6.csv` file (or
+/// both concatenated), and `locations` is a `GeoLite2-Country-Locations-*.csv`
+/// file mapping each `geoname_id` to its `country_iso_code`. Blocks without a
+/// `geoname_id` fall back to their `registered_country_geoname_id`.
+pub(crate) fn load_countries(
+    blocks: impl BufRead,
+    locations: impl BufRead,
+) -> Result<IpNetworkTable<[u8; 2]>, Error> {
+    let mut countries = HashMap::new();
+    for (i, line_result) in locations.lines().enumerate() {
+        let line = line_result?;
+        if i == 0 || line.is_empty() {
+            continue;
+        }
+        let fields = split_record(&line);
+        let (Some(geoname_id), Some(iso_code)) = (fields.first(), fields.get(4)) else {
+            return Err(csv_error(i + 1, &line, fields.len(), 5));
+        };
+        if let [a, b] = iso_code.as_bytes() {
+            countries.insert(geoname_id.to_string(), [*a, *b]);
+        }
+    }
+
+    let mut table = IpNetworkTable::new();
+    for (i, line_result) in blocks.lines().enumerate() {
+        let line = line_result?;
+        if line.is_empty() || is_header(&line) {
+            continue;
+        }
+        let fields = split_record(&line);
+        if fields.len() < 3 {
+            return Err(csv_error(i + 1, &line, fields.len(), 3));
+        }
+        let network = parse_network(&fields[0]).map_err(|kind| Error::Parse {
+            line_number: i + 1,
+            line_content: line.clone(),
+            kind,
+        })?;
+        let geoname_id = if fields[1].is_empty() {
+            &fields[2]
+        } else {
+            &fields[1]
+        };
+        if let Some(country_code) = countries.get(geoname_id.as_ref()) {
+            table.insert(network, *country_code);
+        }
+    }
+    Ok(table)
+}
+
+fn csv_error(line_number: usize, line: &str, found: usize, expected: usize) -> Error {
+    Error::Parse {
+        line_number,
+        line_content: line.to_string(),
+        kind: ParseErrorKind::IncorrectColumnCount { expected, found },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_record() {
+        assert_eq!(split_record("a,b,c"), vec!["a", "b", "c"]);
+        assert_eq!(split_record("a,\"b, c\",d"), vec!["a", "b, c", "d"]);
+        assert_eq!(
+            split_record("a,\"say \"\"hi\"\"\""),
+            vec!["a", "say \"hi\""]
+        );
+        assert_eq!(split_record("a,,"), vec!["a", "", ""]);
+        assert_eq!(split_record(""), vec![""]);
+    }
+}
@@ -0,0 +1,336 @@
+//! Contains a store of dated map snapshots for historical lookups.
+//!
+//! A [`MapHistory`] holds one version of the address space per snapshot
+//! added to it. Each version is split into chunks by the top byte of IPv4
+//! addresses and the top 16 bits of IPv6 addresses, and a chunk that has not
+//! changed since the neighbouring version is shared with it rather than
+//! copied. Owners are shared between all versions, so a store of daily
+//! snapshots costs little more than the ranges that changed between them.
+
+use crate::asn::Asn;
+use crate::export;
+use crate::types::AsnRecord;
+use crate::IpAsnMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// The owner of a range of addresses in one version of a [`MapHistory`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Owner {
+    /// The Autonomous System Number.
+    pub asn: Asn,
+    /// The two-letter country code, or `ZZ` if unknown.
+    pub country_code: String,
+    /// The organization name.
+    pub organization: String,
+}
+
+/// An entry of [`MapHistory::history`]: the owner of an address from one
+/// version on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OwnershipChange<'a> {
+    /// The time of the version the owner first appears in.
+    pub since: SystemTime,
+    /// The owner, or `None` if the address is not in that version.
+    pub owner: Option<&'a Owner>,
+}
+
+/// The chunk an address falls in: its family, and its top 8 (IPv4) or 16
+/// (IPv6) bits.
+type ChunkKey = (bool, u16);
+
+/// The spans starting in one chunk, sorted by address.
+type Chunk = Vec<(u128, u128, Arc<Owner>)>;
+
+fn key(ip: IpAddr) -> (ChunkKey, u128) {
+    match ip {
+        IpAddr::V4(ip) => {
+            let value = u32::from(ip);
+            ((false, (value >> 24) as u16), u128::from(value))
+        }
+        IpAddr::V6(ip) => {
+            let value = u128::from(ip);
+            ((true, (value >> 112) as u16), value)
+        }
+    }
+}
+
+/// The address space of one snapshot.
+#[derive(Debug, Clone)]
+struct Version {
+    time: SystemTime,
+    chunks: BTreeMap<ChunkKey, Arc<Chunk>>,
+}
+
+impl Version {
+    fn lookup(&self, ip: IpAddr) -> Option<&Arc<Owner>> {
+        let (key, value) = key(ip);
+        // The span containing `ip` starts in its chunk or, for a span
+        // crossing a chunk boundary, in the last chunk before it.
+        for (chunk_key, chunk) in self.chunks.range(..=key).rev() {
+            if chunk_key.0 != key.0 {
+                return None;
+            }
+            let idx = chunk.partition_point(|&(start, _, _)| start <= value);
+            if idx > 0 {
+                let (_, end, owner) = &chunk[idx - 1];
+                return (value <= *end).then_some(owner);
+            }
+        }
+        None
+    }
+}
+
+/// A store of dated map snapshots, for looking up who owned an address at a
+/// given time.
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::history::MapHistory;
+/// use ip2asn::Builder;
+/// use std::time::{Duration, SystemTime};
+///
+/// # fn main() -> Result<(), ip2asn::Error> {
+/// let march = SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_251_200);
+/// let april = march + Duration::from_secs(31 * 86_400);
+///
+/// let mut history = MapHistory::new();
+/// let data = "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET";
+/// history.insert(march, &Builder::new().with_source(data.as_bytes())?.build()?);
+/// let data = "1.0.0.0\t1.0.0.255\t15169\tUS\tGOOGLE";
+/// history.insert(april, &Builder::new().with_source(data.as_bytes())?.build()?);
+///
+/// let ip = "1.0.0.1".parse().unwrap();
+/// let day = Duration::from_secs(86_400);
+/// assert_eq!(history.lookup_at(ip, march + 2 * day).unwrap().asn, 13335);
+/// assert_eq!(history.lookup_at(ip, april + day).unwrap().asn, 15169);
+/// assert!(history.lookup_at(ip, march - day).is_none());
+///
+/// let changes = history.history(ip);
+/// assert_eq!(changes.len(), 2);
+/// assert_eq!(changes[1].since, april);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct MapHistory {
+    /// The versions, sorted by time.
+    versions: Vec<Version>,
+    /// Every owner in any version, shared between them.
+    owners: HashSet<Arc<Owner>>,
+}
+
+impl fmt::Debug for MapHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapHistory")
+            .field("versions", &self.versions.len())
+            .field("owners", &self.owners.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl MapHistory {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a snapshot of `map` as the version in effect from `time`, until
+    /// the time of the next version. A version already at `time` is
+    /// replaced.
+    ///
+    /// The map itself is not kept. Its address space is copied into the
+    /// store, sharing chunks and owners that are unchanged from the versions
+    /// before and after it.
+    pub fn insert(&mut self, time: SystemTime, map: &IpAsnMap) {
+        let mut owners: HashMap<AsnRecord, Arc<Owner>> = HashMap::new();
+        let mut chunks: BTreeMap<ChunkKey, Chunk> = BTreeMap::new();
+        let spans = [
+            (false, export::ipv4_spans(map.table.iter_ipv4())),
+            (true, export::ipv6_spans(map.table.iter_ipv6())),
+        ];
+        for (is_ipv6, spans) in spans {
+            for (start, end, record) in spans {
+                let owner = owners
+                    .entry(record)
+                    .or_insert_with(|| self.intern(&record, &map.organizations))
+                    .clone();
+                let ip = match is_ipv6 {
+                    false => IpAddr::V4(Ipv4Addr::from(start as u32)),
+                    true => IpAddr::V6(Ipv6Addr::from(start)),
+                };
+                chunks
+                    .entry(key(ip).0)
+                    .or_default()
+                    .push((start, end, owner));
+            }
+        }
+
+        let idx = self.versions.partition_point(|version| version.time < time);
+        let replace = self
+            .versions
+            .get(idx)
+            .is_some_and(|version| version.time == time);
+        let neighbours: Vec<&Version> = [
+            idx.checked_sub(1).and_then(|i| self.versions.get(i)),
+            self.versions.get(if replace { idx + 1 } else { idx }),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let chunks = chunks
+            .into_iter()
+            .map(|(key, chunk)| {
+                let shared = neighbours
+                    .iter()
+                    .filter_map(|version| version.chunks.get(&key))
+                    .find(|shared| ***shared == chunk);
+                let chunk = match shared {
+                    Some(shared) => Arc::clone(shared),
+                    None => Arc::new(chunk),
+                };
+                (key, chunk)
+            })
+            .collect();
+
+        let version = Version { time, chunks };
+        if replace {
+            self.versions[idx] = version;
+        } else {
+            self.versions.insert(idx, version);
+        }
+    }
+
+    /// Returns the shared owner for a record of a map.
+    fn intern(&mut self, record: &AsnRecord, organizations: &[String]) -> Arc<Owner> {
+        let owner = Owner {
+            asn: record.asn,
+            country_code: String::from_utf8_lossy(&record.country_code).into_owned(),
+            organization: organizations[record.organization_idx as usize].clone(),
+        };
+        match self.owners.get(&owner) {
+            Some(owner) => Arc::clone(owner),
+            None => {
+                let owner = Arc::new(owner);
+                self.owners.insert(Arc::clone(&owner));
+                owner
+            }
+        }
+    }
+
+    /// Returns the number of versions.
+    pub fn len(&self) -> usize {
+        self.versions.len()
+    }
+
+    /// Returns `true` if no versions have been added.
+    pub fn is_empty(&self) -> bool {
+        self.versions.is_empty()
+    }
+
+    /// Returns the times of the versions, from the earliest.
+    pub fn versions(&self) -> impl Iterator<Item = SystemTime> + '_ {
+        self.versions.iter().map(|version| version.time)
+    }
+
+    /// Looks up the owner of an IP address in the version in effect at
+    /// `time`: the latest version from at or before it.
+    ///
+    /// Returns `None` if `time` is before the earliest version, or the
+    /// address is not in the version in effect.
+    pub fn lookup_at(&self, ip: IpAddr, time: SystemTime) -> Option<&Owner> {
+        let idx = self
+            .versions
+            .partition_point(|version| version.time <= time);
+        let version = self.versions.get(idx.checked_sub(1)?)?;
+        version.lookup(ip).map(|owner| &**owner)
+    }
+
+    /// Lists the owners of an IP address across all versions, from the
+    /// earliest, with an entry for the earliest version and for each version
+    /// where the owner differs from the version before it.
+    pub fn history(&self, ip: IpAddr) -> Vec<OwnershipChange<'_>> {
+        let mut changes: Vec<OwnershipChange<'_>> = Vec::new();
+        for version in &self.versions {
+            let owner = version.lookup(ip).map(|owner| &**owner);
+            if changes.last().is_none_or(|last| last.owner != owner) {
+                changes.push(OwnershipChange {
+                    since: version.time,
+                    owner,
+                });
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Builder;
+    use std::time::Duration;
+
+    fn map(data: &str) -> IpAsnMap {
+        Builder::new()
+            .with_source(data.as_bytes())
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    fn day(n: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(n * 86_400)
+    }
+
+    #[test]
+    fn test_unchanged_chunks_are_shared() {
+        let first = "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n\
+                     8.8.8.0\t8.8.8.255\t15169\tUS\tGOOGLE";
+        let second = "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n\
+                      8.8.8.0\t8.8.8.255\t64496\tUS\tOTHER";
+        let mut history = MapHistory::new();
+        history.insert(day(1), &map(first));
+        history.insert(day(2), &map(second));
+        // Inserted out of order, between two versions.
+        history.insert(day(3), &map(first));
+
+        let chunk = |version: usize, ip: &str| {
+            let (key, _) = key(ip.parse().unwrap());
+            Arc::clone(&history.versions[version].chunks[&key])
+        };
+        assert!(Arc::ptr_eq(&chunk(0, "1.0.0.1"), &chunk(1, "1.0.0.1")));
+        assert!(Arc::ptr_eq(&chunk(1, "1.0.0.1"), &chunk(2, "1.0.0.1")));
+        assert!(!Arc::ptr_eq(&chunk(0, "8.8.8.8"), &chunk(1, "8.8.8.8")));
+        assert_eq!(history.owners.len(), 3);
+    }
+
+    #[test]
+    fn test_lookup_spans_crossing_chunks() {
+        let data = "1.255.255.0\t2.0.0.255\t13335\tUS\tCLOUDFLARENET\n\
+                    2.0.1.0\t2.0.1.255\t15169\tUS\tGOOGLE\n\
+                    2001:db8::\t2001:db9:ffff:ffff:ffff:ffff:ffff:ffff\t64496\tZZ\tDOC";
+        let mut history = MapHistory::new();
+        history.insert(day(1), &map(data));
+        let asn = |ip: &str| {
+            history
+                .lookup_at(ip.parse().unwrap(), day(1))
+                .map(|owner| owner.asn.get())
+        };
+        assert_eq!(asn("1.255.255.1"), Some(13335));
+        assert_eq!(asn("2.0.0.1"), Some(13335));
+        assert_eq!(asn("2.0.1.1"), Some(15169));
+        assert_eq!(asn("2.0.2.1"), None);
+        assert_eq!(asn("1.0.0.1"), None);
+        assert_eq!(asn("2001:db9::1"), Some(64496));
+        assert_eq!(asn("2001:dba::1"), None);
+        assert_eq!(asn("::1"), None);
+    }
+}
@@ -21,6 +21,10 @@ impl StringInterner {
         id
     }
 
+    pub(crate) fn resolve(&self, id: u32) -> &str {
+        &self.vec[id as usize]
+    }
+
     pub(crate) fn into_vec(self) -> Vec<String> {
         self.vec
     }
@@ -46,6 +50,8 @@ mod tests {
         let id3 = interner.get_or_intern("Apple Inc.");
         assert_eq!(id3, 0);
 
+        assert_eq!(interner.resolve(id2), "Google LLC");
+
         // Consume the interner and check the resulting vector
         let strings = interner.into_vec();
         assert_eq!(strings.len(), 2);
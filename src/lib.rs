@@ -1,3 +1,4 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![deny(missing_docs)]
 //! A high-performance, memory-efficient Rust crate for mapping IP addresses to
 //! Autonomous System (AS) information with sub-microsecond lookups.
@@ -46,40 +47,163 @@
 //!     Ok(())
 //! }
 //! ```
+/// The `Asn` type and its notations and classification.
+pub mod asn;
+/// Detection and decompression of compressed data sources.
+#[cfg(feature = "std")]
+pub mod compression;
+/// ISO 3166-1 country codes, names, and regions.
+pub mod country;
+/// Extraction of IPv4 addresses embedded in IPv6 addresses.
+pub mod embedded;
+/// Writing logic for exporting a map as TSV.
+#[cfg(feature = "std")]
+pub mod export;
+/// A cached HTTP fetcher with conditional and resumable downloads.
+#[cfg(feature = "fetch")]
+pub mod fetch;
+/// Parsing logic for MaxMind GeoLite2 CSV databases.
+#[cfg(feature = "std")]
+pub mod geolite2;
+/// Time-versioned maps for historical lookups.
+#[cfg(feature = "std")]
+pub mod history;
+#[cfg(feature = "std")]
 mod interner;
+/// Writing logic for MaxMind DB files.
+#[cfg(feature = "mmdb")]
+pub mod mmdb;
+/// Reading logic for MRT `TABLE_DUMP_V2` RIB snapshots.
+#[cfg(feature = "mrt")]
+pub mod mrt;
+/// The `Network` type for IP network blocks.
+pub mod network;
+/// Splitting of organization names into AS handles and descriptions.
+pub mod organization;
+#[cfg(feature = "std")]
+mod overlap;
 /// Line-by-line parsing logic for IP-to-ASN data.
 pub mod parser;
+/// Parsing logic for CAIDA prefix-to-AS data.
+#[cfg(feature = "std")]
+pub mod pfx2as;
 /// IP range to CIDR conversion logic.
 pub mod range;
+/// Parsing logic for RIR delegated-extended statistics.
+#[cfg(feature = "std")]
+pub mod rir;
+/// RPKI route origin validation against exported VRPs.
+#[cfg(feature = "rpki")]
+pub mod rpki;
+#[cfg(feature = "serde")]
+mod snapshot;
+/// The RFC 6890 registry of special-purpose address ranges.
+#[cfg(feature = "std")]
+pub mod special;
+mod table;
 /// Core data structures for ASN records.
 pub mod types;
-
+/// Validation of data sources without building a map.
+#[cfg(feature = "std")]
+pub mod validate;
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::net::IpAddr;
+
+use crate::asn::Asn;
+#[cfg(feature = "std")]
+pub(crate) use crate::compression::create_source_from_reader;
+#[cfg(feature = "std")]
+use crate::compression::{CompressionFormat, Source};
+use crate::country::Country;
+#[cfg(feature = "std")]
 use crate::interner::StringInterner;
+use crate::network::Network;
+use crate::organization::{Organization, OrganizationInfo};
+#[cfg(feature = "std")]
+use crate::overlap::RangeSet;
+#[cfg(feature = "std")]
 use crate::parser::{parse_line, ParsedLine};
-use crate::range::range_to_cidrs;
+#[cfg(feature = "std")]
+use crate::range::{network_bounds, range_to_cidrs};
+#[cfg(feature = "std")]
+use crate::rir::{RegistryInfo, RirDelegations};
+use crate::table::NetworkTable;
 use crate::types::AsnRecord;
-use flate2::read::GzDecoder;
+#[cfg(feature = "std")]
+use crate::validate::{ValidationReport, Validator};
+#[cfg(any(feature = "mmdb", feature = "mrt", feature = "rpki"))]
 use ip_network::IpNetwork;
+#[cfg(feature = "std")]
 use ip_network_table::IpNetworkTable;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+#[cfg(feature = "std")]
 use std::error::Error as StdError;
-use std::fmt;
+#[cfg(feature = "std")]
 use std::fs::File;
-use std::hash::{Hash, Hasher};
-use std::io::{BufRead, BufReader};
-use std::net::IpAddr;
+#[cfg(feature = "std")]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(feature = "std")]
 use std::path::Path;
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::sync::Arc;
 
 /// The primary error type for the crate.
+#[cfg(feature = "std")]
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum Error {
     /// An error occurred during an I/O operation.
     Io(std::io::Error),
 
+    /// A compressed data source could not be decompressed, either because it
+    /// is corrupt or because support for its format is not enabled.
+    Decompression {
+        /// The compression format detected from the source's magic bytes.
+        format: CompressionFormat,
+        /// The underlying error reported by the decompressor.
+        source: std::io::Error,
+    },
+
     /// An error occurred during an HTTP request.
     #[cfg(feature = "fetch")]
     Http(reqwest::Error),
 
+    /// A JSON data source could not be deserialized.
+    #[cfg(feature = "rpki")]
+    Json(serde_json::Error),
+
+    /// The build was stopped by a [`CancellationToken`].
+    Cancelled,
+
+    /// A record overlapped an earlier one, and [`OverlapAction::Error`] was
+    /// configured.
+    Overlap {
+        /// The earlier record.
+        existing: Box<RangeRecord>,
+        /// The record that overlapped it.
+        new: Box<RangeRecord>,
+    },
+
+    /// The build crossed a limit set with [`Builder::max_warnings`] or
+    /// [`Builder::min_records`].
+    Limit {
+        /// The limit that was crossed.
+        limit: BuildLimit,
+        /// The report of the build up to the point it was stopped.
+        report: Box<BuildReport>,
+    },
+
     /// A line in the data source was malformed (only in strict mode).
     Parse {
         /// The 1-based line number where the error occurred.
@@ -91,23 +215,66 @@ pub enum Error {
     },
 }
 
+#[cfg(feature = "std")]
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
             Error::Io(e) => Some(e),
+            Error::Decompression { source, .. } => Some(source),
             #[cfg(feature = "fetch")]
             Error::Http(e) => Some(e),
-            Error::Parse { .. } => None,
+            #[cfg(feature = "rpki")]
+            Error::Json(e) => Some(e),
+            Error::Cancelled
+            | Error::Overlap { .. }
+            | Error::Limit { .. }
+            | Error::Parse { .. } => None,
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::Io(e) => write!(f, "I/O error: {e}"),
+            Error::Decompression { format, source } => {
+                write!(f, "{format} decompression error: {source}")
+            }
             #[cfg(feature = "fetch")]
             Error::Http(e) => write!(f, "HTTP error: {e}"),
+            #[cfg(feature = "rpki")]
+            Error::Json(e) => write!(f, "JSON error: {e}"),
+            Error::Cancelled => write!(f, "the build was cancelled"),
+            Error::Overlap { existing, new } => write!(
+                f,
+                "Overlap error: line {} ({new}) overlaps line {} ({existing})",
+                new.line_number, existing.line_number
+            ),
+            Error::Limit { limit, report } => {
+                match limit {
+                    BuildLimit::MaxWarnings(max) => write!(
+                        f,
+                        "Limit error: more than {max} lines were skipped by line {}",
+                        report.lines_read
+                    )?,
+                    BuildLimit::MinRecords(min) => write!(
+                        f,
+                        "Limit error: {} records were inserted from {} lines, fewer than \
+                         the minimum of {min}",
+                        report.records_inserted, report.lines_read
+                    )?,
+                }
+                let mut warnings = report.warnings.iter();
+                if let Some((name, summary)) = warnings.next() {
+                    write!(f, " (skipped: {name} x{}", summary.count)?;
+                    for (name, summary) in warnings {
+                        write!(f, ", {name} x{}", summary.count)?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
             Error::Parse {
                 line_number,
                 line_content,
@@ -120,9 +287,10 @@ impl fmt::Display for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for Error {
     fn from(err: std::io::Error) -> Self {
-        Error::Io(err)
+        compression::classify_io_error(err)
     }
 }
 
@@ -133,8 +301,75 @@ impl From<reqwest::Error> for Error {
     }
 }
 
+#[cfg(feature = "rpki")]
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+/// A snapshot of a build's progress, passed to [`Builder::on_progress`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Progress {
+    /// The number of bytes consumed from the data source. For a compressed
+    /// source, these are the compressed bytes.
+    pub bytes_read: u64,
+    /// The number of decompressed bytes consumed, if the source is compressed.
+    pub bytes_decompressed: Option<u64>,
+    /// The number of lines processed, or of records for binary formats.
+    pub lines: usize,
+    /// The number of records inserted into the map.
+    pub records: usize,
+}
+
+/// A token for cancelling a build from another thread.
+///
+/// Clones share the same state, so one clone can be passed to
+/// [`Builder::cancellation_token`] while another is used to cancel the build,
+/// which then returns [`Error::Cancelled`].
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::{Builder, CancellationToken, Error};
+///
+/// let token = CancellationToken::new();
+/// token.cancel();
+///
+/// let result = Builder::new()
+///     .with_source("1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET".as_bytes())
+///     .unwrap()
+///     .cancellation_token(token)
+///     .build();
+/// assert!(matches!(result, Err(Error::Cancelled)));
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+#[cfg(feature = "std")]
+impl CancellationToken {
+    /// Creates a new token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels any build using this token or one of its clones.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
 /// A non-fatal warning for a skipped line during parsing.
-#[derive(Debug)]
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum Warning {
     /// A line in the data source could not be parsed and was skipped.
@@ -143,8 +378,8 @@ pub enum Warning {
         line_number: usize,
         /// The content of the line that was skipped.
         line_content: String,
-        /// A message describing the parse error.
-        message: String,
+        /// The specific type of parsing error.
+        kind: ParseErrorKind,
     },
     /// A line contained a start IP and end IP of different families.
     IpFamilyMismatch {
@@ -153,18 +388,27 @@ pub enum Warning {
         /// The content of the line that was skipped.
         line_content: String,
     },
+    /// A record overlapped an earlier one, and [`OverlapAction::Warn`] was
+    /// configured. The overlap is resolved by the [`OverlapWinner`].
+    Overlap {
+        /// The earlier record.
+        existing: RangeRecord,
+        /// The record that overlapped it.
+        new: RangeRecord,
+    },
 }
 
+#[cfg(feature = "std")]
 impl fmt::Display for Warning {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Warning::Parse {
                 line_number,
                 line_content,
-                message,
+                kind,
             } => write!(
                 f,
-                "Parse warning on line {line_number}: {message} in line: \"{line_content}\""
+                "Parse warning on line {line_number}: {kind} in line: \"{line_content}\""
             ),
             Warning::IpFamilyMismatch {
                 line_number,
@@ -173,10 +417,98 @@ impl fmt::Display for Warning {
                 f,
                 "IP family mismatch on line {line_number}: \"{line_content}\""
             ),
+            Warning::Overlap { existing, new } => write!(
+                f,
+                "Overlap on line {}: {new} overlaps line {} ({existing})",
+                new.line_number, existing.line_number
+            ),
         }
     }
 }
 
+/// A record read from a data source, as named in an overlap warning or error.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RangeRecord {
+    /// The 1-based line number the record was read from.
+    pub line_number: usize,
+    /// The start of the record's IP address range.
+    pub start_ip: IpAddr,
+    /// The end of the record's IP address range.
+    pub end_ip: IpAddr,
+    /// The Autonomous System Number (ASN).
+    pub asn: Asn,
+    /// The two-letter ISO 3166-1 alpha-2 country code.
+    pub country_code: String,
+    /// The common name of the organization that owns the IP range.
+    pub organization: String,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for RangeRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{} AS{} {} {}",
+            self.start_ip, self.end_ip, self.asn, self.country_code, self.organization
+        )
+    }
+}
+
+/// Statistics gathered while building a map, returned by
+/// [`Builder::build_with_report`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BuildReport {
+    /// The number of lines read, or of records for binary formats.
+    pub lines_read: usize,
+    /// The number of comment lines skipped.
+    pub comments_skipped: usize,
+    /// The number of records inserted into the map.
+    pub records_inserted: usize,
+    /// The number of CIDR blocks generated from the inserted records.
+    pub cidrs_generated: usize,
+    /// The number of overlaps between records, counted when an
+    /// [`OverlapAction`] other than `Ignore` is configured.
+    pub overlaps: usize,
+    /// The warnings for skipped lines, keyed by the
+    /// [`name`](ParseErrorKind::name) of their `ParseErrorKind`.
+    pub warnings: BTreeMap<&'static str, WarningSummary>,
+}
+
+#[cfg(feature = "std")]
+impl BuildReport {
+    /// Returns the total number of warnings for skipped lines, of all kinds.
+    pub fn warning_count(&self) -> usize {
+        self.warnings.values().map(|summary| summary.count).sum()
+    }
+}
+
+/// A limit on the outcome of a build, crossed by an [`Error::Limit`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildLimit {
+    /// More lines were skipped than allowed by [`Builder::max_warnings`].
+    MaxWarnings(usize),
+    /// Fewer records were inserted than required by [`Builder::min_records`].
+    MinRecords(usize),
+}
+
+/// The warnings of a single kind in a [`BuildReport`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct WarningSummary {
+    /// The number of warnings of this kind.
+    pub count: usize,
+    /// The first warnings of this kind, up to the limit set with
+    /// [`Builder::warning_samples`].
+    pub samples: Vec<Warning>,
+}
+
 /// The specific kind of error that occurred during line parsing.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
@@ -214,6 +546,67 @@ pub enum ParseErrorKind {
         /// The value that could not be parsed as a country code.
         value: String,
     },
+    /// A field could not be parsed as a network in CIDR notation.
+    InvalidNetwork {
+        /// The value that could not be parsed as a network.
+        value: String,
+    },
+    /// The prefix was announced by more than one origin AS, and the
+    /// [`MultiOriginPolicy`] is `Reject`.
+    MultipleOrigins {
+        /// The origin field listing the announcing ASNs.
+        value: String,
+    },
+    /// The origin was an AS set, and the [`MultiOriginPolicy`] is `Reject`.
+    AsSetOrigin {
+        /// The origin field listing the members of the AS set.
+        value: String,
+    },
+    /// A binary MRT record could not be decoded.
+    InvalidMrtRecord {
+        /// A message describing what was wrong with the record.
+        message: String,
+    },
+    /// A required field was not present in the record.
+    MissingField {
+        /// The name or path of the missing field.
+        field: String,
+    },
+    /// A record in the data section of a MaxMind DB file could not be decoded.
+    InvalidMmdbRecord {
+        /// A message describing what was wrong with the record.
+        message: String,
+    },
+    /// A field did not hold one of the values allowed by the format.
+    InvalidField {
+        /// The name of the field that failed parsing (e.g., "status").
+        field: String,
+        /// The value that could not be parsed.
+        value: String,
+    },
+}
+
+impl ParseErrorKind {
+    /// Returns the name of the variant, such as `"InvalidAsnNumber"`.
+    ///
+    /// This is the key under which warnings are grouped in a [`BuildReport`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            ParseErrorKind::IncorrectColumnCount { .. } => "IncorrectColumnCount",
+            ParseErrorKind::InvalidIpAddress { .. } => "InvalidIpAddress",
+            ParseErrorKind::InvalidAsnNumber { .. } => "InvalidAsnNumber",
+            ParseErrorKind::InvalidRange { .. } => "InvalidRange",
+            ParseErrorKind::IpFamilyMismatch => "IpFamilyMismatch",
+            ParseErrorKind::InvalidCountryCode { .. } => "InvalidCountryCode",
+            ParseErrorKind::InvalidNetwork { .. } => "InvalidNetwork",
+            ParseErrorKind::MultipleOrigins { .. } => "MultipleOrigins",
+            ParseErrorKind::AsSetOrigin { .. } => "AsSetOrigin",
+            ParseErrorKind::InvalidMrtRecord { .. } => "InvalidMrtRecord",
+            ParseErrorKind::MissingField { .. } => "MissingField",
+            ParseErrorKind::InvalidMmdbRecord { .. } => "InvalidMmdbRecord",
+            ParseErrorKind::InvalidField { .. } => "InvalidField",
+        }
+    }
 }
 
 impl fmt::Display for ParseErrorKind {
@@ -237,15 +630,71 @@ impl fmt::Display for ParseErrorKind {
             ParseErrorKind::InvalidCountryCode { value } => {
                 write!(f, "invalid country code: {value}")
             }
+            ParseErrorKind::InvalidNetwork { value } => {
+                write!(f, "invalid network: {value}")
+            }
+            ParseErrorKind::MultipleOrigins { value } => {
+                write!(f, "multiple origin ASNs: {value}")
+            }
+            ParseErrorKind::AsSetOrigin { value } => {
+                write!(f, "AS set origin: {value}")
+            }
+            ParseErrorKind::InvalidMrtRecord { message } => {
+                write!(f, "invalid MRT record: {message}")
+            }
+            ParseErrorKind::MissingField { field } => {
+                write!(f, "missing field `{field}`")
+            }
+            ParseErrorKind::InvalidMmdbRecord { message } => {
+                write!(f, "invalid MMDB record: {message}")
+            }
+            ParseErrorKind::InvalidField { field, value } => {
+                write!(f, "invalid value for field `{field}`: {value}")
+            }
         }
     }
 }
 
 /// A read-optimized, in-memory map for IP address to ASN lookups.
 /// Construction is handled by the `Builder`.
+///
+/// With the `serde` feature, a map can be saved as a snapshot and loaded
+/// again with any serde format, without the `std` feature. A snapshot holds
+/// the map's networks and organizations, but not registry data or VRPs.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "serde")]
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use ip2asn::{Builder, IpAsnMap};
+///
+/// let data = "1.0.0.0\t1.0.0.255\t13335\tAU\tCLOUDFLARENET";
+/// let map = Builder::new().with_source(data.as_bytes())?.build()?;
+///
+/// let snapshot = serde_json::to_string(&map)?;
+/// assert_eq!(
+///     snapshot,
+///     r#"{"organizations":["CLOUDFLARENET"],"networks":[["1.0.0.0/24",13335,"AU",0]]}"#
+/// );
+///
+/// let map: IpAsnMap = serde_json::from_str(&snapshot)?;
+/// let info = map.lookup("1.0.0.1".parse().unwrap()).unwrap();
+/// assert_eq!(info.asn, 13335);
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "serde"))]
+/// # fn main() {}
+/// ```
 pub struct IpAsnMap {
-    table: IpNetworkTable<AsnRecord>,
+    table: NetworkTable<AsnRecord>,
     organizations: Vec<String>,
+    /// The parts of each organization name, split once when the map is built.
+    organization_parts: Vec<organization::Spans>,
+    #[cfg(feature = "std")]
+    registry: RirDelegations,
+    #[cfg(feature = "rpki")]
+    vrps: rpki::VrpTable,
 }
 
 impl fmt::Debug for IpAsnMap {
@@ -260,13 +709,37 @@ impl Default for IpAsnMap {
     /// Creates a new, empty `IpAsnMap`.
     fn default() -> Self {
         Self {
-            table: IpNetworkTable::new(),
+            table: NetworkTable::new(),
             organizations: Vec::new(),
+            organization_parts: Vec::new(),
+            #[cfg(feature = "std")]
+            registry: RirDelegations::new(),
+            #[cfg(feature = "rpki")]
+            vrps: rpki::VrpTable::new(),
         }
     }
 }
 
 impl IpAsnMap {
+    /// Creates a map from its lookup table and organization names, with no
+    /// registry data or VRPs.
+    #[cfg(any(feature = "std", feature = "serde"))]
+    fn from_table(table: NetworkTable<AsnRecord>, organizations: Vec<String>) -> Self {
+        let organization_parts = organizations
+            .iter()
+            .map(|name| organization::Spans::new(name))
+            .collect();
+        Self {
+            table,
+            organizations,
+            organization_parts,
+            #[cfg(feature = "std")]
+            registry: RirDelegations::new(),
+            #[cfg(feature = "rpki")]
+            vrps: rpki::VrpTable::new(),
+        }
+    }
+
     /// Creates a new, empty `IpAsnMap`.
     ///
     /// This is a convenience method equivalent to `IpAsnMap::default()`.
@@ -286,6 +759,7 @@ impl IpAsnMap {
     /// Creates a new `Builder` for constructing an `IpAsnMap`.
     ///
     /// This is a convenience method equivalent to `Builder::new()`.
+    #[cfg(feature = "std")]
     pub fn builder() -> Builder<'static> {
         Builder::new()
     }
@@ -295,14 +769,18 @@ impl IpAsnMap {
     /// The lookup is a longest-prefix match, ensuring the most specific
     /// network range is returned. The returned `AsnInfoView` includes the
     /// matching network block itself.
-    pub fn lookup(&self, ip: IpAddr) -> Option<AsnInfoView> {
+    pub fn lookup(&self, ip: IpAddr) -> Option<AsnInfoView<'_>> {
         self.table.longest_match(ip).map(|(network, record)| {
-            let organization = &self.organizations[record.organization_idx as usize];
+            let idx = record.organization_idx as usize;
+            let organization = &self.organizations[idx];
             AsnInfoView {
                 network,
                 asn: record.asn,
-                country_code: std::str::from_utf8(&record.country_code).unwrap_or_default(),
+                country_code: core::str::from_utf8(&record.country_code).unwrap_or_default(),
                 organization,
+                organization_parts: self.organization_parts[idx].resolve(organization),
+                #[cfg(feature = "std")]
+                registry: self.registry.lookup(ip).map(|(_, info)| info),
             }
         })
     }
@@ -335,6 +813,285 @@ impl IpAsnMap {
     pub fn lookup_owned(&self, ip: IpAddr) -> Option<AsnInfo> {
         self.lookup(ip).map(AsnInfo::from)
     }
+
+    /// Looks up an IP address, first extracting any IPv4 address embedded in
+    /// it.
+    ///
+    /// IPv4-mapped (`::ffff:0:0/96`), NAT64 (`64:ff9b::/96`), 6to4
+    /// (`2002::/16`), and Teredo (`2001::/32`) addresses are looked up by
+    /// their IPv4 address, and the result names the embedding that was
+    /// detected. If the IPv4 address is not found, or the address embeds none,
+    /// the address itself is looked up as with [`lookup`](#method.lookup).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ip2asn::Builder;
+    /// use ip2asn::embedded::Ipv4Embedding;
+    ///
+    /// # fn main() -> Result<(), ip2asn::Error> {
+    /// let data = "1.0.0.0\t1.0.0.255\t13335\tAU\tCLOUDFLARENET";
+    /// let map = Builder::new().with_source(data.as_bytes())?.build()?;
+    ///
+    /// let ip = "64:ff9b::1.0.0.1".parse().unwrap();
+    /// assert!(map.lookup(ip).is_none());
+    ///
+    /// let result = map.lookup_embedded(ip).unwrap();
+    /// assert_eq!(result.embedding, Some(Ipv4Embedding::Nat64));
+    /// assert_eq!(result.ip.to_string(), "1.0.0.1");
+    /// assert_eq!(result.info.asn, 13335);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lookup_embedded(&self, ip: IpAddr) -> Option<embedded::EmbeddedLookup<'_>> {
+        if let IpAddr::V6(v6) = ip {
+            if let Some((embedding, v4)) = embedded::extract(v6) {
+                if let Some(info) = self.lookup(IpAddr::V4(v4)) {
+                    return Some(embedded::EmbeddedLookup {
+                        ip: IpAddr::V4(v4),
+                        embedding: Some(embedding),
+                        info,
+                    });
+                }
+            }
+        }
+        self.lookup(ip).map(|info| embedded::EmbeddedLookup {
+            ip,
+            embedding: None,
+            info,
+        })
+    }
+
+    /// Returns the special-purpose range containing an IP address, such as
+    /// private, loopback, or documentation space, or `None` for an ordinary
+    /// unicast address.
+    ///
+    /// This explains why [`lookup`](#method.lookup) finds nothing for most
+    /// addresses that are not routed on the internet. It does not depend on
+    /// the map's data, and is the same as [`special::classify`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ip2asn::special::SpecialPurpose;
+    /// use ip2asn::IpAsnMap;
+    ///
+    /// let map = IpAsnMap::new();
+    /// let ip = "10.1.2.3".parse().unwrap();
+    /// assert!(map.lookup(ip).is_none());
+    ///
+    /// let range = map.classify(ip).unwrap();
+    /// assert_eq!(range.purpose(), SpecialPurpose::Private);
+    /// assert_eq!(range.network().to_string(), "10.0.0.0/8");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn classify(&self, ip: IpAddr) -> Option<&'static special::SpecialRange> {
+        special::classify(ip)
+    }
+
+    /// Writes the map in the five-column `iptoasn.com` TSV format.
+    ///
+    /// Each line covers a contiguous range of addresses with the same ASN,
+    /// country, and organization, so the output can be loaded again with
+    /// [`Builder::with_source`] to give an equivalent map. Unknown countries
+    /// are written as `None`, and tabs, newlines, and other control characters
+    /// in organization names are replaced with spaces.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ip2asn::Builder;
+    /// # fn main() -> Result<(), ip2asn::Error> {
+    /// let data = "1.0.0.0\t1.0.0.255\t13335\tAU\tCLOUDFLARENET";
+    /// let map = Builder::new().with_source(data.as_bytes())?.build()?;
+    ///
+    /// let mut output = Vec::new();
+    /// map.write_tsv(&mut output)?;
+    /// assert_eq!(output, b"1.0.0.0\t1.0.0.255\t13335\tAU\tCLOUDFLARENET\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_tsv(&self, writer: impl Write) -> Result<(), Error> {
+        self.write_tsv_with(writer, export::TsvOptions::default())
+    }
+
+    /// Writes the map as TSV with the given options, such as gzip compression
+    /// or a single address family.
+    ///
+    /// See [`write_tsv`](#method.write_tsv) for the format.
+    #[cfg(feature = "std")]
+    pub fn write_tsv_with(
+        &self,
+        writer: impl Write,
+        options: export::TsvOptions,
+    ) -> Result<(), Error> {
+        export::write_tsv(
+            writer,
+            options,
+            self.table.iter_ipv4(),
+            self.table.iter_ipv6(),
+            &self.organizations,
+        )?;
+        Ok(())
+    }
+
+    /// Writes the map as a MaxMind DB (MMDB) file.
+    ///
+    /// Each network is stored with `autonomous_system_number`,
+    /// `autonomous_system_organization`, and `country.iso_code` fields, so the
+    /// file can be read by MaxMind DB readers such as the nginx `geoip2`
+    /// module. The country is left out for networks with the unknown country
+    /// `ZZ`. IPv4 networks are stored in the `::/96` subtree of an IPv6
+    /// database.
+    ///
+    /// This method is only available when the `mmdb` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ip2asn::Builder;
+    /// # use std::fs::File;
+    /// # use std::io::BufWriter;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let data = "1.0.0.0\t1.0.0.255\t13335\tAU\tCLOUDFLARENET";
+    /// let map = Builder::new().with_source(data.as_bytes())?.build()?;
+    ///
+    /// # let dir = tempfile::tempdir()?;
+    /// # let path = dir.path().join("ip2asn.mmdb");
+    /// let file = BufWriter::new(File::create(&path)?);
+    /// map.write_mmdb(file)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "mmdb")]
+    pub fn write_mmdb(&self, writer: impl Write) -> Result<(), Error> {
+        self.write_mmdb_with(writer, &mmdb::MmdbOptions::default())
+    }
+
+    /// Writes the map as a MaxMind DB file with the given metadata options.
+    ///
+    /// See [`write_mmdb`](#method.write_mmdb) for the layout.
+    ///
+    /// This method is only available when the `mmdb` feature is enabled.
+    #[cfg(feature = "mmdb")]
+    pub fn write_mmdb_with(
+        &self,
+        writer: impl Write,
+        options: &mmdb::MmdbOptions,
+    ) -> Result<(), Error> {
+        mmdb::write_mmdb(
+            writer,
+            options,
+            self.table.iter_ipv4(),
+            self.table.iter_ipv6(),
+            &self.organizations,
+        )?;
+        Ok(())
+    }
+
+    /// Overlays RIR registry data onto the map, replacing any set before.
+    ///
+    /// Once set, lookups include the [`RegistryInfo`] of the delegated block
+    /// containing the address, which can be compared against the map's own
+    /// country with [`AsnInfoView::country_mismatch`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ip2asn::Builder;
+    /// use ip2asn::rir::{AllocationStatus, Registry, RirDelegations};
+    ///
+    /// # fn main() -> Result<(), ip2asn::Error> {
+    /// # let data = "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET";
+    /// let mut delegations = RirDelegations::new();
+    /// delegations.load("apnic|AU|ipv4|1.0.0.0|256|20110811|assigned".as_bytes())?;
+    ///
+    /// let map = Builder::new()
+    ///     .with_source(data.as_bytes())?
+    ///     .build()?
+    ///     .with_registry_data(delegations);
+    ///
+    /// let info = map.lookup("1.0.0.1".parse().unwrap()).unwrap();
+    /// let registry = info.registry.unwrap();
+    /// assert_eq!(registry.registry, Registry::Apnic);
+    /// assert_eq!(registry.status, AllocationStatus::Assigned);
+    /// assert!(info.country_mismatch());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_registry_data(mut self, delegations: RirDelegations) -> Self {
+        self.registry = delegations;
+        self
+    }
+
+    /// Returns the RIR registry data overlaid onto the map.
+    ///
+    /// This is empty unless set with
+    /// [`with_registry_data`](#method.with_registry_data), and can be used to
+    /// look up the registry of an ASN.
+    #[cfg(feature = "std")]
+    pub fn registry_data(&self) -> &RirDelegations {
+        &self.registry
+    }
+
+    /// Sets the VRPs used by [`validate_origin`](#method.validate_origin),
+    /// replacing any set before.
+    ///
+    /// This method is only available when the `rpki` feature is enabled.
+    #[cfg(feature = "rpki")]
+    pub fn with_vrps(mut self, vrps: rpki::VrpTable) -> Self {
+        self.vrps = vrps;
+        self
+    }
+
+    /// Validates the map's origin ASN for an IP address or prefix against the
+    /// VRPs set with [`with_vrps`](#method.with_vrps).
+    ///
+    /// The route validated is the most specific network in the map that
+    /// contains all of `target`, announced by that network's ASN. Since the
+    /// map's networks are rebuilt from address ranges, they may be shorter
+    /// than the prefixes actually announced. Returns
+    /// [`ValidationState::NotFound`](rpki::ValidationState::NotFound) if no
+    /// network in the map contains `target`.
+    ///
+    /// This method is only available when the `rpki` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ip2asn::Builder;
+    /// use ip2asn::rpki::{ValidationState, VrpTable};
+    /// use std::net::IpAddr;
+    ///
+    /// # fn main() -> Result<(), ip2asn::Error> {
+    /// # let data = "1.0.0.0\t1.0.0.255\t13335\tAU\tCLOUDFLARENET";
+    /// let mut vrps = VrpTable::new();
+    /// vrps.load("AS13335,1.0.0.0/24,24,apnic".as_bytes())?;
+    ///
+    /// let map = Builder::new()
+    ///     .with_source(data.as_bytes())?
+    ///     .build()?
+    ///     .with_vrps(vrps);
+    ///
+    /// let ip: IpAddr = "1.0.0.1".parse().unwrap();
+    /// assert_eq!(map.validate_origin(ip), ValidationState::Valid);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "rpki")]
+    pub fn validate_origin(&self, target: impl Into<IpNetwork>) -> rpki::ValidationState {
+        let target = target.into();
+        let route = self
+            .table
+            .matches(target.network_address())
+            .find(|(network, _)| network.netmask() <= target.netmask());
+        match route {
+            Some((network, record)) => self.vrps.validate(network.into(), record.asn),
+            None => rpki::ValidationState::NotFound,
+        }
+    }
 }
 
 /// An owned struct containing ASN information for an IP address.
@@ -370,37 +1127,73 @@ impl IpAsnMap {
 #[non_exhaustive]
 pub struct AsnInfo {
     /// The matching IP network block for the looked-up address.
-    pub network: IpNetwork,
+    pub network: Network,
     /// The Autonomous System Number (ASN).
-    pub asn: u32,
+    pub asn: Asn,
     /// The two-letter ISO 3166-1 alpha-2 country code.
     pub country_code: String,
     /// The common name of the organization that owns the IP range.
     pub organization: String,
+    /// The organization name split into its AS handle, description, and
+    /// registry-suffix country.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub organization_parts: OrganizationInfo,
+    /// The RIR registry data for the address, if registry data was overlaid
+    /// with [`IpAsnMap::with_registry_data`].
+    #[cfg(feature = "std")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub registry: Option<RegistryInfo>,
+}
+
+impl AsnInfo {
+    /// Returns the [`Country`] for the country code, or `None` if the code is
+    /// unknown (`ZZ`) or not an assigned ISO 3166-1 code.
+    pub fn country(&self) -> Option<Country> {
+        Country::from_code(&self.country_code)
+    }
+
+    /// Returns `true` if the registry's country differs from the map's country.
+    ///
+    /// Blocks where either country is unknown (`ZZ`) never count as a
+    /// mismatch.
+    #[cfg(feature = "std")]
+    pub fn country_mismatch(&self) -> bool {
+        country_mismatch(&self.country_code, self.registry.as_ref())
+    }
 }
 
 impl PartialEq for AsnInfo {
     fn eq(&self, other: &Self) -> bool {
-        self.network == other.network
+        let eq = self.network == other.network
             && self.asn == other.asn
             && self.country_code == other.country_code
-            && self.organization == other.organization
+            && self.organization == other.organization;
+        #[cfg(feature = "std")]
+        let eq = eq && self.registry == other.registry;
+        eq
     }
 }
 
 impl PartialOrd for AsnInfo {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         Some(self.cmp(other))
     }
 }
 
 impl Ord for AsnInfo {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.asn
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        let ordering = self
+            .asn
             .cmp(&other.asn)
             .then_with(|| self.network.cmp(&other.network))
             .then_with(|| self.country_code.cmp(&other.country_code))
-            .then_with(|| self.organization.cmp(&other.organization))
+            .then_with(|| self.organization.cmp(&other.organization));
+        #[cfg(feature = "std")]
+        let ordering = ordering.then_with(|| self.registry.cmp(&other.registry));
+        ordering
     }
 }
 
@@ -410,6 +1203,8 @@ impl Hash for AsnInfo {
         self.asn.hash(state);
         self.country_code.hash(state);
         self.organization.hash(state);
+        #[cfg(feature = "std")]
+        self.registry.hash(state);
     }
 }
 
@@ -449,29 +1244,275 @@ impl<'a> From<AsnInfoView<'a>> for AsnInfo {
             asn: view.asn,
             country_code: view.country_code.to_string(),
             organization: view.organization.to_string(),
+            organization_parts: OrganizationInfo::from(view.organization_parts),
+            #[cfg(feature = "std")]
+            registry: view.registry.cloned(),
         }
     }
 }
 
-/// A builder for configuring and loading an `IpAsnMap`.
-#[derive(Default)]
-pub struct Builder<'a> {
-    source: Option<Box<dyn BufRead + Send + 'a>>,
-    strict: bool,
-    on_warning: Option<Box<dyn Fn(Warning) + Send + 'a>>,
+/// The layout of the data read by a `Builder`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InputFormat {
+    /// The five-column `iptoasn.com` TSV format. This is the default.
+    #[default]
+    Ip2Asn,
+    /// The MaxMind GeoLite2-ASN CSV format, as found in
+    /// `GeoLite2-ASN-Blocks-IPv4.csv` and `GeoLite2-ASN-Blocks-IPv6.csv`.
+    ///
+    /// These files have no country column, so every record is given the
+    /// country `ZZ` unless a country database is joined in with
+    /// [`Builder::with_geolite2_countries`].
+    GeoLite2AsnCsv,
+    /// The CAIDA RouteViews prefix-to-AS (`pfx2as`) format.
+    ///
+    /// These files have no country or organization columns, so every record
+    /// is given the country `ZZ` and an empty organization. Prefixes with
+    /// multiple origins are handled according to the
+    /// [`Builder::multi_origin_policy`].
+    Pfx2As,
+    /// A binary MRT `TABLE_DUMP_V2` RIB snapshot.
+    ///
+    /// Each prefix is mapped to the last AS in the `AS_PATH` of its routes.
+    /// When peers disagree on the origin, one is chosen according to the
+    /// [`Builder::multi_origin_policy`]. Records are numbered in place of
+    /// lines in errors and warnings. Every record is given the country `ZZ`
+    /// and an empty organization.
+    ///
+    /// This format is only available when the `mrt` feature is enabled.
+    #[cfg(feature = "mrt")]
+    Mrt,
+    /// A binary MaxMind DB (MMDB) file, such as GeoLite2-ASN.
+    ///
+    /// Each network in the file's search tree becomes a record, with its
+    /// fields read from the paths set with [`Builder::mmdb_fields`]. Networks
+    /// are numbered in place of lines in errors and warnings. The whole source
+    /// is read into memory before it is walked.
+    ///
+    /// This format is only available when the `mmdb` feature is enabled.
+    #[cfg(feature = "mmdb")]
+    Mmdb,
 }
 
-impl<'a> fmt::Debug for Builder<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Builder")
-            .field("has_source", &self.source.is_some())
-            .field("strict", &self.strict)
-            .field("has_on_warning", &self.on_warning.is_some())
-            .finish()
-    }
+/// How to choose an origin ASN when a prefix has more than one.
+///
+/// This applies to prefixes announced by multiple origin ASes (MOAS), to
+/// origins that are AS sets, and to MRT peers that disagree on the origin.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MultiOriginPolicy {
+    /// Use the first origin listed. This is the default.
+    #[default]
+    First,
+    /// Use the numerically lowest origin.
+    Lowest,
+    /// Use the numerically highest origin.
+    Highest,
+    /// Use the origin listed most often, such as the one seen by the most MRT
+    /// peers. Ties go to the origin listed first.
+    MostCommon,
+    /// Treat the line as malformed, with a
+    /// [`ParseErrorKind::MultipleOrigins`] or [`ParseErrorKind::AsSetOrigin`]
+    /// error.
+    Reject,
 }
 
-impl<'a> Builder<'a> {
+#[cfg(feature = "std")]
+impl MultiOriginPolicy {
+    /// Chooses one of the origins, or `None` if there are none.
+    ///
+    /// `Reject` is treated like `First`; callers check for it beforehand. The
+    /// origins are taken as a cloneable iterator so that callers need not
+    /// collect them.
+    fn choose(self, mut origins: impl Iterator<Item = Asn> + Clone) -> Option<Asn> {
+        match self {
+            MultiOriginPolicy::Lowest => origins.min(),
+            MultiOriginPolicy::Highest => origins.max(),
+            MultiOriginPolicy::MostCommon => {
+                let mut best: Option<(Asn, usize)> = None;
+                for (i, asn) in origins.clone().enumerate() {
+                    if origins.clone().take(i).any(|a| a == asn) {
+                        continue;
+                    }
+                    let count = origins.clone().skip(i).filter(|a| *a == asn).count();
+                    if best.is_none_or(|(_, best_count)| count > best_count) {
+                        best = Some((asn, count));
+                    }
+                }
+                best.map(|(asn, _)| asn)
+            }
+            MultiOriginPolicy::First | MultiOriginPolicy::Reject => origins.next(),
+        }
+    }
+}
+
+/// What to do when a record overlaps address space covered by an earlier one.
+///
+/// See [`Builder::on_overlap`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OverlapAction {
+    /// Resolve the overlap silently. This is the default.
+    #[default]
+    Ignore,
+    /// Resolve the overlap and report it with a [`Warning::Overlap`].
+    Warn,
+    /// Fail the build with an [`Error::Overlap`].
+    Error,
+}
+
+/// Which record covers the address space where two records overlap.
+///
+/// See [`Builder::overlap_winner`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OverlapWinner {
+    /// The record read first wins.
+    First,
+    /// The record read last wins.
+    Last,
+    /// The record with the smaller range wins, or the one read last if both
+    /// are the same size. This is the default.
+    #[default]
+    MostSpecific,
+}
+
+/// A single record read from a data source, independent of its input format.
+#[cfg(feature = "std")]
+struct SourceRecord<'a> {
+    start_ip: IpAddr,
+    end_ip: IpAddr,
+    asn: Asn,
+    country_code: [u8; 2],
+    organization: Cow<'a, str>,
+}
+
+#[cfg(feature = "std")]
+impl<'a> From<ParsedLine<'a>> for SourceRecord<'a> {
+    fn from(line: ParsedLine<'a>) -> Self {
+        Self {
+            start_ip: line.start_ip,
+            end_ip: line.end_ip,
+            asn: line.asn,
+            country_code: line.country_code,
+            organization: Cow::Borrowed(line.organization),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<pfx2as::Pfx2AsLine> for SourceRecord<'_> {
+    fn from(line: pfx2as::Pfx2AsLine) -> Self {
+        let (start_ip, end_ip) = network_bounds(line.network);
+        Self {
+            start_ip,
+            end_ip,
+            asn: line.asn,
+            country_code: [b'Z'; 2],
+            organization: Cow::Borrowed(""),
+        }
+    }
+}
+
+#[cfg(feature = "mmdb")]
+impl SourceRecord<'_> {
+    fn from_mmdb(network: IpNetwork, record: mmdb::MmdbRecord) -> Self {
+        let (start_ip, end_ip) = network_bounds(network);
+        Self {
+            start_ip,
+            end_ip,
+            asn: record.asn,
+            country_code: record.country_code,
+            organization: Cow::Owned(record.organization),
+        }
+    }
+}
+
+#[cfg(feature = "mrt")]
+impl SourceRecord<'_> {
+    fn from_rib(network: IpNetwork, asn: Asn) -> Self {
+        let (start_ip, end_ip) = network_bounds(network);
+        Self {
+            start_ip,
+            end_ip,
+            asn,
+            country_code: [b'Z'; 2],
+            organization: Cow::Borrowed(""),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> From<geolite2::AsnBlock<'a>> for SourceRecord<'a> {
+    fn from(block: geolite2::AsnBlock<'a>) -> Self {
+        let (start_ip, end_ip) = network_bounds(block.network);
+        Self {
+            start_ip,
+            end_ip,
+            asn: block.asn,
+            country_code: [b'Z'; 2],
+            organization: block.organization,
+        }
+    }
+}
+
+/// A builder for configuring and loading an `IpAsnMap`.
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct Builder<'a> {
+    source: Option<Source<'a>>,
+    format: InputFormat,
+    countries: Option<IpNetworkTable<[u8; 2]>>,
+    multi_origin_policy: MultiOriginPolicy,
+    #[cfg(feature = "mmdb")]
+    mmdb_fields: mmdb::MmdbFields,
+    strict: bool,
+    on_warning: Option<Box<dyn Fn(Warning) + Send + 'a>>,
+    on_progress: Option<Box<dyn Fn(Progress) + Send + 'a>>,
+    progress_interval: Option<usize>,
+    cancellation_token: Option<CancellationToken>,
+    warning_samples: Option<usize>,
+    max_warnings: Option<usize>,
+    min_records: Option<usize>,
+    overlap_action: Option<OverlapAction>,
+    overlap_winner: Option<OverlapWinner>,
+}
+
+/// The default number of lines between progress reports.
+#[cfg(feature = "std")]
+const DEFAULT_PROGRESS_INTERVAL: usize = 100_000;
+
+/// The default number of sample warnings kept per kind in a `BuildReport`.
+#[cfg(feature = "std")]
+const DEFAULT_WARNING_SAMPLES: usize = 5;
+
+#[cfg(feature = "std")]
+impl<'a> fmt::Debug for Builder<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("has_source", &self.source.is_some())
+            .field("format", &self.format)
+            .field("has_countries", &self.countries.is_some())
+            .field("multi_origin_policy", &self.multi_origin_policy)
+            .field("strict", &self.strict)
+            .field("has_on_warning", &self.on_warning.is_some())
+            .field("has_on_progress", &self.on_progress.is_some())
+            .field("has_cancellation_token", &self.cancellation_token.is_some())
+            .field("max_warnings", &self.max_warnings)
+            .field("min_records", &self.min_records)
+            .field("overlap_action", &self.overlap_action)
+            .field("overlap_winner", &self.overlap_winner)
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> Builder<'a> {
     /// Creates a new builder with default settings.
     pub fn new() -> Self {
         Self::default()
@@ -480,10 +1521,11 @@ impl<'a> Builder<'a> {
     /// Configures the builder to load data from a file path.
     ///
     /// Gzip decompression is handled automatically by inspecting the file's magic bytes.
+    /// Bzip2, xz, and zstd are detected as well when their features are enabled.
     pub fn from_path<P: AsRef<Path>>(mut self, path: P) -> Result<Self, Error> {
         let file = File::open(path.as_ref())?;
         let reader = BufReader::new(file);
-        self.source = Some(self.create_source_from_reader(reader)?);
+        self.source = Some(Source::detect(reader)?);
         Ok(self)
     }
 
@@ -493,8 +1535,9 @@ impl<'a> Builder<'a> {
     /// such as an in-memory buffer or a network stream.
     ///
     /// Gzip decompression is handled automatically by inspecting the stream's magic bytes.
+    /// Bzip2, xz, and zstd are detected as well when their features are enabled.
     pub fn with_source(mut self, source: impl BufRead + Send + 'a) -> Result<Self, Error> {
-        self.source = Some(self.create_source_from_reader(source)?);
+        self.source = Some(Source::detect(source)?);
         Ok(self)
     }
 
@@ -502,18 +1545,152 @@ impl<'a> Builder<'a> {
     ///
     /// This method is only available when the `fetch` feature is enabled.
     /// Gzip decompression is handled automatically by inspecting the stream's magic bytes.
+    ///
+    /// The whole file is downloaded on every call. To keep a cached copy that
+    /// is only downloaded again when it changes, use a
+    /// [`Fetcher`](fetch::Fetcher) and build from its cache path.
     #[cfg(feature = "fetch")]
     pub fn from_url(mut self, url: &str) -> Result<Self, Error> {
         let response = reqwest::blocking::get(url)?;
         let response = response.error_for_status()?;
         let reader = BufReader::new(response);
-        self.source = Some(self.create_source_from_reader(reader)?);
+        self.source = Some(Source::detect(reader)?);
+        Ok(self)
+    }
+
+    /// Configures the builder to load data from a MaxMind DB (MMDB) file.
+    ///
+    /// This sets the format to [`InputFormat::Mmdb`]. By default, the ASN,
+    /// organization, and country are read from the GeoLite2-ASN fields and
+    /// `country.iso_code`; other layouts can be read by setting
+    /// [`mmdb_fields`](#method.mmdb_fields).
+    ///
+    /// This method is only available when the `mmdb` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ip2asn::Builder;
+    ///
+    /// # fn main() -> Result<(), ip2asn::Error> {
+    /// let map = Builder::new()
+    ///     .from_mmdb("GeoLite2-ASN.mmdb")?
+    ///     .build()?;
+    ///
+    /// if let Some(info) = map.lookup("1.1.1.1".parse().unwrap()) {
+    ///     println!("AS{} {}", info.asn, info.organization);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "mmdb")]
+    pub fn from_mmdb<P: AsRef<Path>>(mut self, path: P) -> Result<Self, Error> {
+        // The search tree can start with bytes that look like a compression
+        // header, so the file is never decompressed.
+        let file = File::open(path.as_ref())?;
+        self.source = Some(Source::plain(BufReader::new(file)));
+        Ok(self.format(InputFormat::Mmdb))
+    }
+
+    /// Sets the paths of the fields read from each MMDB record.
+    ///
+    /// This only applies to [`InputFormat::Mmdb`]. Defaults to
+    /// [`MmdbFields::default`](mmdb::MmdbFields::default).
+    ///
+    /// This method is only available when the `mmdb` feature is enabled.
+    #[cfg(feature = "mmdb")]
+    pub fn mmdb_fields(mut self, fields: mmdb::MmdbFields) -> Self {
+        self.mmdb_fields = fields;
+        self
+    }
+
+    /// Sets the layout of the data source.
+    ///
+    /// Defaults to [`InputFormat::Ip2Asn`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ip2asn::{Builder, InputFormat};
+    ///
+    /// # fn main() -> Result<(), ip2asn::Error> {
+    /// let data = "network,autonomous_system_number,autonomous_system_organization\n\
+    ///             1.0.0.0/24,13335,CLOUDFLARENET";
+    /// let map = Builder::new()
+    ///     .with_source(data.as_bytes())?
+    ///     .format(InputFormat::GeoLite2AsnCsv)
+    ///     .build()?;
+    ///
+    /// let info = map.lookup("1.0.0.1".parse().unwrap()).unwrap();
+    /// assert_eq!(info.asn, 13335);
+    /// assert_eq!(info.country_code, "ZZ");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn format(mut self, format: InputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Joins country codes from a MaxMind GeoLite2-Country CSV database.
+    ///
+    /// `blocks` is a `GeoLite2-Country-Blocks-IPv4.csv` or `-IPv6.csv` file,
+    /// and `locations` is a `GeoLite2-Country-Locations-*.csv` file. Both are
+    /// read immediately, and gzip decompression is handled automatically.
+    ///
+    /// When set, each record's country is taken from the country block that
+    /// contains the record's first address, or `ZZ` if there is none. This is
+    /// intended for formats without a country column, such as
+    /// [`InputFormat::GeoLite2AsnCsv`].
+    pub fn with_geolite2_countries(
+        mut self,
+        blocks: impl BufRead + Send + 'a,
+        locations: impl BufRead + Send + 'a,
+    ) -> Result<Self, Error> {
+        let blocks = create_source_from_reader(blocks)?;
+        let locations = create_source_from_reader(locations)?;
+        self.countries = Some(geolite2::load_countries(blocks, locations)?);
         Ok(self)
     }
 
+    /// Sets how prefixes with multiple origin ASNs are handled.
+    ///
+    /// This only applies to formats that can list several origins for one
+    /// prefix, such as [`InputFormat::Pfx2As`]. Defaults to
+    /// [`MultiOriginPolicy::First`]. With [`MultiOriginPolicy::Reject`], such
+    /// lines are treated as malformed and follow the strict or resilient mode.
+    pub fn multi_origin_policy(mut self, policy: MultiOriginPolicy) -> Self {
+        self.multi_origin_policy = policy;
+        self
+    }
+
+    /// Sets what to do when a record overlaps address space covered by an
+    /// earlier one. Defaults to [`OverlapAction::Ignore`].
+    ///
+    /// Setting this or [`overlap_winner`](#method.overlap_winner) resolves
+    /// overlaps between whole records. Otherwise, each record's CIDR blocks
+    /// are inserted as they are read, so a block nested inside an earlier
+    /// one shadows part of it, and an identical block replaces it.
+    pub fn on_overlap(mut self, action: OverlapAction) -> Self {
+        self.overlap_action = Some(action);
+        self
+    }
+
+    /// Sets which record covers the address space where two records overlap.
+    /// Defaults to [`OverlapWinner::MostSpecific`].
+    ///
+    /// See [`on_overlap`](#method.on_overlap).
+    pub fn overlap_winner(mut self, winner: OverlapWinner) -> Self {
+        self.overlap_winner = Some(winner);
+        self
+    }
+
     /// Enables strict parsing mode.
     ///
     /// If called, `build()` will return an `Err` on the first parse failure.
+    /// Country codes that are not assigned in ISO 3166-1, other than `ZZ` for
+    /// unknown countries, are rejected with
+    /// [`ParseErrorKind::InvalidCountryCode`].
     pub fn strict(mut self) -> Self {
         self.strict = true;
         self
@@ -528,22 +1705,81 @@ impl<'a> Builder<'a> {
         self
     }
 
-    fn create_source_from_reader(
-        &self,
-        mut reader: impl BufRead + Send + 'a,
-    ) -> Result<Box<dyn BufRead + Send + 'a>, Error> {
-        let is_gzipped = {
-            let header = reader.fill_buf()?;
-            header.starts_with(&[0x1f, 0x8b])
-        };
+    /// Sets a callback function to be invoked with the build's progress.
+    ///
+    /// The callback is invoked every [`progress_interval`](#method.progress_interval)
+    /// lines, and once more when the build completes.
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(Progress) + Send + 'a,
+    {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
 
-        let source: Box<dyn BufRead + Send + 'a> = if is_gzipped {
-            Box::new(BufReader::new(GzDecoder::new(reader)))
-        } else {
-            Box::new(reader)
-        };
+    /// Sets the number of lines, or of records for binary formats, between
+    /// progress reports. Defaults to 100,000. An interval of zero is treated
+    /// as one.
+    pub fn progress_interval(mut self, lines: usize) -> Self {
+        self.progress_interval = Some(lines.max(1));
+        self
+    }
 
-        Ok(source)
+    /// Sets the number of warnings of each kind kept as samples in the
+    /// [`BuildReport`] returned by [`build_with_report`](#method.build_with_report).
+    /// Defaults to 5.
+    pub fn warning_samples(mut self, samples: usize) -> Self {
+        self.warning_samples = Some(samples);
+        self
+    }
+
+    /// Sets the most lines that may be skipped as malformed in resilient mode.
+    ///
+    /// Once one more line is skipped, `build` stops and returns an
+    /// [`Error::Limit`] with [`BuildLimit::MaxWarnings`], so a corrupted source
+    /// fails rather than producing a partial map. Overlaps reported with
+    /// [`OverlapAction::Warn`] do not count towards the limit.
+    pub fn max_warnings(mut self, max: usize) -> Self {
+        self.max_warnings = Some(max);
+        self
+    }
+
+    /// Sets the fewest records the build must insert into the map.
+    ///
+    /// If the source ends with fewer records, `build` returns an
+    /// [`Error::Limit`] with [`BuildLimit::MinRecords`], so a truncated source
+    /// fails rather than producing a nearly empty map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ip2asn::{BuildLimit, Builder, Error};
+    ///
+    /// let data = "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n1.0.1.0\t1.0";
+    /// let result = Builder::new()
+    ///     .with_source(data.as_bytes())
+    ///     .and_then(|builder| builder.min_records(2).build());
+    ///
+    /// match result {
+    ///     Err(Error::Limit { limit, report }) => {
+    ///         assert_eq!(limit, BuildLimit::MinRecords(2));
+    ///         assert_eq!(report.records_inserted, 1);
+    ///     }
+    ///     other => panic!("Expected a limit error, got {other:?}"),
+    /// }
+    /// ```
+    pub fn min_records(mut self, min: usize) -> Self {
+        self.min_records = Some(min);
+        self
+    }
+
+    /// Sets a token that stops the build when it is cancelled.
+    ///
+    /// The token is checked before each line, or record for binary formats, is
+    /// processed. Once it is cancelled, `build` returns [`Error::Cancelled`].
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
     }
 
     /// Builds the `IpAsnMap`, consuming the builder.
@@ -551,69 +1787,461 @@ impl<'a> Builder<'a> {
     /// This method reads from the source, parses each line, interns strings,
     /// converts IP ranges to CIDRs, and inserts them into the final lookup table.
     pub fn build(self) -> Result<IpAsnMap, Error> {
-        let source = self.source.ok_or_else(|| {
+        self.build_with_report().map(|(map, _)| map)
+    }
+
+    /// Builds the `IpAsnMap` like [`build`](#method.build), also returning a
+    /// [`BuildReport`] of what was read, inserted, and skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ip2asn::Builder;
+    ///
+    /// # fn main() -> Result<(), ip2asn::Error> {
+    /// let data = "# A comment\n\
+    ///             1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n\
+    ///             1.0.1.0\t1.0.1.255\tnot-a-number\tUS\tBAD";
+    /// let (map, report) = Builder::new()
+    ///     .with_source(data.as_bytes())?
+    ///     .build_with_report()?;
+    ///
+    /// assert_eq!(report.lines_read, 3);
+    /// assert_eq!(report.comments_skipped, 1);
+    /// assert_eq!(report.records_inserted, 1);
+    /// assert_eq!(report.warnings["InvalidAsnNumber"].count, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn build_with_report(mut self) -> Result<(IpAsnMap, BuildReport), Error> {
+        let Source {
+            reader: source,
+            counts,
+        } = self.source.take().ok_or_else(|| {
+            Error::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "No data source provided",
+            ))
+        })?;
+
+        let progress_interval = self.progress_interval.unwrap_or(DEFAULT_PROGRESS_INTERVAL);
+        let mut loader = Loader {
+            strict: self.strict,
+            on_warning: self.on_warning.as_deref(),
+            countries: self.countries.as_ref(),
+            interner: StringInterner::new(),
+            networks: Vec::new(),
+            on_progress: self.on_progress.as_deref(),
+            progress_interval,
+            next_progress: progress_interval,
+            cancellation_token: self.cancellation_token.as_ref(),
+            counts,
+            warning_samples: self.warning_samples.unwrap_or(DEFAULT_WARNING_SAMPLES),
+            max_warnings: self.max_warnings,
+            report: BuildReport::default(),
+            overlap_action: self.overlap_action.unwrap_or_default(),
+            overlap_winner: self.overlap_winner.unwrap_or_default(),
+            ranges: (self.overlap_action.is_some() || self.overlap_winner.is_some())
+                .then(RangeSet::new),
+        };
+
+        self.read_records(source, &mut loader)?;
+        if let Some(min) = self.min_records {
+            if loader.report.records_inserted < min {
+                return Err(Error::Limit {
+                    limit: BuildLimit::MinRecords(min),
+                    report: Box::new(loader.report),
+                });
+            }
+        }
+        Ok(loader.finish())
+    }
+
+    /// Checks the source for problems without building a map, consuming the
+    /// builder.
+    ///
+    /// Every record is read and checked for sort order, overlaps, duplicates,
+    /// gaps within an ASN's block (in sorted `iptoasn.com` data only),
+    /// reserved address space assigned to an ASN,
+    /// private-use ASNs, and unknown country codes. Lines that cannot be
+    /// parsed are reported as issues rather than errors, even in strict mode.
+    /// See [`validate::IssueKind`] for the checks performed.
+    ///
+    /// The number of samples kept for each kind of issue is set with
+    /// [`warning_samples`](#method.warning_samples).
+    pub fn validate(mut self) -> Result<ValidationReport, Error> {
+        let Source { reader: source, .. } = self.source.take().ok_or_else(|| {
             Error::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "No data source provided",
             ))
         })?;
 
-        let mut interner = StringInterner::new();
-        let mut table = IpNetworkTable::new();
+        // Only the `iptoasn.com` format covers the whole address space, so
+        // gaps in the other formats are expected.
+        let mut validator = Validator::new(
+            self.warning_samples.unwrap_or(DEFAULT_WARNING_SAMPLES),
+            self.format == InputFormat::Ip2Asn,
+            self.cancellation_token.as_ref(),
+        );
+        self.read_records(source, &mut validator)?;
+        Ok(validator.finish())
+    }
 
-        for (i, line_result) in source.lines().enumerate() {
-            let line_number = i + 1;
-            let line = line_result?;
-            if line.is_empty() || line.starts_with('#') {
+    /// Reads every record from `source` in the configured format, passing
+    /// them to `sink` in order.
+    fn read_records(
+        &self,
+        mut source: Box<dyn BufRead + Send + 'a>,
+        sink: &mut impl RecordSink,
+    ) -> Result<(), Error> {
+        #[cfg(feature = "mrt")]
+        if self.format == InputFormat::Mrt {
+            let mut reader = mrt::MrtReader::new(source);
+            while let Some(result) = reader.read_prefix()? {
+                let (content, parsed) = match result {
+                    Ok(prefix) => (
+                        prefix.network.to_string(),
+                        prefix
+                            .select_origin(self.multi_origin_policy)
+                            .map(|asn| SourceRecord::from_rib(prefix.network, asn)),
+                    ),
+                    Err(kind) => (
+                        format!(
+                            "MRT record {} subtype {}",
+                            reader.record_number(),
+                            reader.subtype()
+                        ),
+                        Err(kind),
+                    ),
+                };
+                sink.line(reader.record_number())?;
+                sink.record(reader.record_number(), &content, parsed)?;
+            }
+            return Ok(());
+        }
+
+        #[cfg(feature = "mmdb")]
+        if self.format == InputFormat::Mmdb {
+            let mut buf = Vec::new();
+            source.read_to_end(&mut buf)?;
+            let mut number = 0;
+            return mmdb::for_each_network(&buf, &self.mmdb_fields, |network, parsed| {
+                number += 1;
+                sink.line(number)?;
+                let parsed = parsed.map(|record| SourceRecord::from_mmdb(network, record));
+                sink.record(number, &network.to_string(), parsed)
+            });
+        }
+
+        // One buffer is reused for every line, so reading does not allocate
+        // once it has grown to fit the longest line.
+        let mut buf = String::new();
+        let mut line_number = 0;
+        loop {
+            buf.clear();
+            if source.read_line(&mut buf)? == 0 {
+                break;
+            }
+            line_number += 1;
+            sink.line(line_number)?;
+            let line = trim_line_ending(&buf);
+            if line.is_empty() {
                 continue;
             }
+            if line.starts_with('#') {
+                sink.comment();
+                continue;
+            }
+
+            let parsed = match self.format {
+                InputFormat::Ip2Asn => parse_line(line).map(SourceRecord::from),
+                InputFormat::GeoLite2AsnCsv => {
+                    if geolite2::is_header(line) {
+                        continue;
+                    }
+                    geolite2::parse_asn_line(line).map(SourceRecord::from)
+                }
+                InputFormat::Pfx2As => {
+                    pfx2as::parse_line(line, self.multi_origin_policy).map(SourceRecord::from)
+                }
+                #[cfg(feature = "mrt")]
+                InputFormat::Mrt => unreachable!("MRT sources are read as binary records"),
+                #[cfg(feature = "mmdb")]
+                InputFormat::Mmdb => unreachable!("MMDB sources are read as a search tree"),
+            };
+
+            sink.record(line_number, line, parsed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Receives the records read from a data source, in order.
+#[cfg(feature = "std")]
+trait RecordSink {
+    /// Called before each line, or record for binary formats, is processed.
+    fn line(&mut self, line_number: usize) -> Result<(), Error>;
+
+    /// Called for each comment line that is skipped.
+    fn comment(&mut self) {}
+
+    /// Called for each record, or for each line or record that could not be
+    /// parsed.
+    ///
+    /// `line_content` is only copied if it is needed for an error or warning.
+    fn record(
+        &mut self,
+        line_number: usize,
+        line_content: &str,
+        parsed: Result<SourceRecord<'_>, ParseErrorKind>,
+    ) -> Result<(), Error>;
+}
 
-            let parsed: ParsedLine = match parse_line(&line) {
-                Ok(p) => p,
-                Err(kind) => {
-                    if self.strict {
-                        return Err(Error::Parse {
+/// Strips a trailing `\n` or `\r\n`, as `BufRead::lines` does.
+#[cfg(feature = "std")]
+fn trim_line_ending(line: &str) -> &str {
+    match line.strip_suffix('\n') {
+        Some(line) => line.strip_suffix('\r').unwrap_or(line),
+        None => line,
+    }
+}
+
+/// The state of a map under construction, shared by all input formats.
+#[cfg(feature = "std")]
+struct Loader<'b, 'a> {
+    strict: bool,
+    on_warning: Option<&'b (dyn Fn(Warning) + Send + 'a)>,
+    countries: Option<&'b IpNetworkTable<[u8; 2]>>,
+    interner: StringInterner,
+    /// The CIDR blocks inserted so far, turned into the lookup table once
+    /// every record has been read.
+    networks: Vec<(Network, AsnRecord)>,
+    on_progress: Option<&'b (dyn Fn(Progress) + Send + 'a)>,
+    progress_interval: usize,
+    /// The line count at which progress is next reported.
+    next_progress: usize,
+    cancellation_token: Option<&'b CancellationToken>,
+    counts: compression::ByteCounts,
+    warning_samples: usize,
+    max_warnings: Option<usize>,
+    report: BuildReport,
+    overlap_action: OverlapAction,
+    overlap_winner: OverlapWinner,
+    /// The ranges read so far, if overlaps are resolved between records
+    /// rather than CIDR blocks.
+    ranges: Option<RangeSet>,
+}
+
+#[cfg(feature = "std")]
+impl Loader<'_, '_> {
+    /// Records that a line, or a record for binary formats, has been read,
+    /// reporting progress if it is due.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Cancelled` if the build has been cancelled.
+    fn tick(&mut self, lines: usize) -> Result<(), Error> {
+        if self
+            .cancellation_token
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(Error::Cancelled);
+        }
+        self.report.lines_read = lines;
+        if lines >= self.next_progress {
+            self.report_progress();
+            self.next_progress = lines + self.progress_interval;
+        }
+        Ok(())
+    }
+
+    fn report_progress(&self) {
+        if let Some(callback) = self.on_progress {
+            callback(Progress {
+                bytes_read: self.counts.read(),
+                bytes_decompressed: self.counts.decompressed(),
+                lines: self.report.lines_read,
+                records: self.report.records_inserted,
+            });
+        }
+    }
+
+    /// Inserts a parsed record, or handles its parse error according to the
+    /// strict or resilient mode.
+    ///
+    /// `line_content` is only copied if it is needed for an error or warning.
+    fn ingest(
+        &mut self,
+        line_number: usize,
+        line_content: &str,
+        parsed: Result<SourceRecord<'_>, ParseErrorKind>,
+    ) -> Result<(), Error> {
+        // Unassigned country codes are only rejected in strict mode, so
+        // resilient builds keep records with codes such as `EU` or `AP`.
+        let parsed = parsed.and_then(|record| {
+            if self.strict
+                && record.country_code != [b'Z'; 2]
+                && !country::is_assigned(record.country_code)
+            {
+                return Err(ParseErrorKind::InvalidCountryCode {
+                    value: String::from_utf8_lossy(&record.country_code).into_owned(),
+                });
+            }
+            Ok(record)
+        });
+        let mut parsed = match parsed {
+            Ok(p) => p,
+            Err(kind) => {
+                if self.strict {
+                    return Err(Error::Parse {
+                        line_number,
+                        line_content: line_content.to_string(),
+                        kind,
+                    });
+                }
+                let summary = self.report.warnings.entry(kind.name()).or_default();
+                summary.count += 1;
+                let keep_sample = summary.samples.len() < self.warning_samples;
+                if keep_sample || self.on_warning.is_some() {
+                    let warning = if kind == ParseErrorKind::IpFamilyMismatch {
+                        Warning::IpFamilyMismatch {
+                            line_number,
+                            line_content: line_content.to_string(),
+                        }
+                    } else {
+                        Warning::Parse {
                             line_number,
-                            line_content: line,
+                            line_content: line_content.to_string(),
                             kind,
-                        });
-                    } else if let Some(callback) = &self.on_warning {
-                        let warning = if kind == ParseErrorKind::IpFamilyMismatch {
-                            Warning::IpFamilyMismatch {
-                                line_number,
-                                line_content: line,
-                            }
-                        } else {
-                            Warning::Parse {
-                                line_number,
-                                line_content: line,
-                                message: format!("{kind:?}"),
-                            }
-                        };
+                        }
+                    };
+                    if keep_sample {
+                        summary.samples.push(warning.clone());
+                    }
+                    if let Some(callback) = self.on_warning {
                         callback(warning);
                     }
-                    continue;
                 }
-            };
+                if let Some(max) = self.max_warnings {
+                    if self.report.warning_count() > max {
+                        return Err(Error::Limit {
+                            limit: BuildLimit::MaxWarnings(max),
+                            report: Box::new(self.report.clone()),
+                        });
+                    }
+                }
+                return Ok(());
+            }
+        };
 
-            let org_idx = interner.get_or_intern(parsed.organization);
+        if let Some(countries) = self.countries {
+            parsed.country_code = countries
+                .longest_match(parsed.start_ip)
+                .map_or([b'Z'; 2], |(_, code)| *code);
+        }
 
-            let record = AsnRecord {
-                asn: parsed.asn,
-                country_code: parsed.country_code,
-                organization_idx: org_idx,
-            };
+        let org_idx = self.interner.get_or_intern(&parsed.organization);
+
+        let record = AsnRecord {
+            asn: parsed.asn,
+            country_code: parsed.country_code,
+            organization_idx: org_idx,
+        };
 
+        self.report.records_inserted += 1;
+        let Some(ranges) = &mut self.ranges else {
             for cidr in range_to_cidrs(parsed.start_ip, parsed.end_ip) {
-                table.insert(cidr, record);
+                self.networks.push((cidr, record));
+                self.report.cidrs_generated += 1;
             }
+            return Ok(());
+        };
+
+        let conflicts = ranges.insert(
+            parsed.start_ip,
+            parsed.end_ip,
+            record,
+            line_number,
+            self.overlap_winner,
+        );
+        if self.overlap_action == OverlapAction::Ignore {
+            return Ok(());
         }
+        for conflict in conflicts {
+            let existing = self.range_record(
+                conflict.line_number,
+                conflict.start_ip,
+                conflict.end_ip,
+                &conflict.record,
+            );
+            let new = self.range_record(line_number, parsed.start_ip, parsed.end_ip, &record);
+            if self.overlap_action == OverlapAction::Error {
+                return Err(Error::Overlap {
+                    existing: Box::new(existing),
+                    new: Box::new(new),
+                });
+            }
+            self.report.overlaps += 1;
+            if let Some(callback) = self.on_warning {
+                callback(Warning::Overlap { existing, new });
+            }
+        }
+        Ok(())
+    }
 
-        let organizations = interner.into_vec();
-        Ok(IpAsnMap {
-            table,
-            organizations,
-        })
+    fn range_record(
+        &self,
+        line_number: usize,
+        start_ip: IpAddr,
+        end_ip: IpAddr,
+        record: &AsnRecord,
+    ) -> RangeRecord {
+        RangeRecord {
+            line_number,
+            start_ip,
+            end_ip,
+            asn: record.asn,
+            country_code: String::from_utf8_lossy(&record.country_code).into_owned(),
+            organization: self.interner.resolve(record.organization_idx).to_string(),
+        }
+    }
+
+    fn finish(mut self) -> (IpAsnMap, BuildReport) {
+        if let Some(ranges) = self.ranges.take() {
+            for (start_ip, end_ip, record) in ranges.into_ranges() {
+                for cidr in range_to_cidrs(start_ip, end_ip) {
+                    self.networks.push((cidr, record));
+                    self.report.cidrs_generated += 1;
+                }
+            }
+        }
+        self.report_progress();
+        let map = IpAsnMap::from_table(
+            self.networks.into_iter().collect(),
+            self.interner.into_vec(),
+        );
+        (map, self.report)
+    }
+}
+
+#[cfg(feature = "std")]
+impl RecordSink for Loader<'_, '_> {
+    fn line(&mut self, line_number: usize) -> Result<(), Error> {
+        self.tick(line_number)
+    }
+
+    fn comment(&mut self) {
+        self.report.comments_skipped += 1;
+    }
+
+    fn record(
+        &mut self,
+        line_number: usize,
+        line_content: &str,
+        parsed: Result<SourceRecord<'_>, ParseErrorKind>,
+    ) -> Result<(), Error> {
+        self.ingest(line_number, line_content, parsed)
     }
 }
 
@@ -623,25 +2251,109 @@ impl<'a> Builder<'a> {
 #[non_exhaustive]
 pub struct AsnInfoView<'a> {
     /// The matching IP network block for the looked-up address.
-    pub network: IpNetwork,
+    pub network: Network,
     /// The Autonomous System Number (ASN).
-    pub asn: u32,
+    pub asn: Asn,
     /// The two-letter ISO 3166-1 alpha-2 country code.
     pub country_code: &'a str,
     /// The common name of the organization that owns the IP range.
     pub organization: &'a str,
+    /// The organization name split into its AS handle, description, and
+    /// registry-suffix country.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ip2asn::Builder;
+    ///
+    /// # fn main() -> Result<(), ip2asn::Error> {
+    /// let data = "1.0.0.0\t1.0.0.255\t15169\tUS\tGOOGLE - Google LLC";
+    /// let map = Builder::new().with_source(data.as_bytes())?.build()?;
+    ///
+    /// let info = map.lookup("1.0.0.1".parse().unwrap()).unwrap();
+    /// assert_eq!(info.organization_parts.handle, Some("GOOGLE"));
+    /// assert_eq!(info.organization_parts.name(), "Google LLC");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub organization_parts: Organization<'a>,
+    /// The RIR registry data for the address, if registry data was overlaid
+    /// with [`IpAsnMap::with_registry_data`].
+    #[cfg(feature = "std")]
+    pub registry: Option<&'a RegistryInfo>,
+}
+
+impl AsnInfoView<'_> {
+    /// Returns the [`Country`] for the country code, or `None` if the code is
+    /// unknown (`ZZ`) or not an assigned ISO 3166-1 code.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ip2asn::country::Continent;
+    /// use ip2asn::Builder;
+    ///
+    /// # fn main() -> Result<(), ip2asn::Error> {
+    /// let data = "1.0.0.0\t1.0.0.255\t13335\tau\tCLOUDFLARENET";
+    /// let map = Builder::new().with_source(data.as_bytes())?.build()?;
+    ///
+    /// let info = map.lookup("1.0.0.1".parse().unwrap()).unwrap();
+    /// assert_eq!(info.country_code, "AU");
+    /// let country = info.country().unwrap();
+    /// assert_eq!(country.name(), "Australia");
+    /// assert_eq!(country.continent(), Continent::Oceania);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn country(&self) -> Option<Country> {
+        Country::from_code(self.country_code)
+    }
+
+    /// Returns `true` if the registry's country differs from the map's country.
+    ///
+    /// Blocks where either country is unknown (`ZZ`) never count as a
+    /// mismatch.
+    #[cfg(feature = "std")]
+    pub fn country_mismatch(&self) -> bool {
+        country_mismatch(self.country_code, self.registry)
+    }
+}
+
+#[cfg(feature = "std")]
+fn country_mismatch(country_code: &str, registry: Option<&RegistryInfo>) -> bool {
+    registry.is_some_and(|info| {
+        country_code != "ZZ" && info.country_code != "ZZ" && country_code != info.country_code
+    })
 }
 
+#[cfg(feature = "std")]
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io;
 
+    fn range_record(line_number: usize, asn: u32) -> RangeRecord {
+        RangeRecord {
+            line_number,
+            start_ip: "1.0.0.0".parse().unwrap(),
+            end_ip: "1.0.0.255".parse().unwrap(),
+            asn: Asn::new(asn),
+            country_code: "US".to_string(),
+            organization: "EXAMPLE".to_string(),
+        }
+    }
+
     #[test]
     fn test_error_source() {
         let io_error = Error::Io(io::Error::new(io::ErrorKind::NotFound, "file not found"));
         assert!(io_error.source().is_some());
 
+        let decompression_error = Error::Decompression {
+            format: CompressionFormat::Gzip,
+            source: io::Error::new(io::ErrorKind::InvalidInput, "corrupt deflate stream"),
+        };
+        assert!(decompression_error.source().is_some());
+
         #[cfg(feature = "fetch")]
         {
             let client = reqwest::blocking::Client::new();
@@ -650,6 +2362,26 @@ mod tests {
             assert!(http_error.source().is_some());
         }
 
+        #[cfg(feature = "rpki")]
+        {
+            let json_error = Error::Json(serde_json::from_str::<u32>("x").unwrap_err());
+            assert!(json_error.source().is_some());
+        }
+
+        assert!(Error::Cancelled.source().is_none());
+
+        let overlap_error = Error::Overlap {
+            existing: Box::new(range_record(1, 13335)),
+            new: Box::new(range_record(2, 15169)),
+        };
+        assert!(overlap_error.source().is_none());
+
+        let limit_error = Error::Limit {
+            limit: BuildLimit::MinRecords(1),
+            report: Box::default(),
+        };
+        assert!(limit_error.source().is_none());
+
         let parse_error = Error::Parse {
             line_number: 1,
             line_content: "bad line".to_string(),
@@ -666,6 +2398,15 @@ mod tests {
         let io_error = Error::Io(io::Error::new(io::ErrorKind::NotFound, "file not found"));
         assert_eq!(io_error.to_string(), "I/O error: file not found");
 
+        let decompression_error = Error::Decompression {
+            format: CompressionFormat::Zstd,
+            source: io::Error::other("Unknown frame descriptor"),
+        };
+        assert_eq!(
+            decompression_error.to_string(),
+            "zstd decompression error: Unknown frame descriptor"
+        );
+
         #[cfg(feature = "fetch")]
         {
             // This is hard to test deterministically, so we just check that it contains
@@ -676,6 +2417,57 @@ mod tests {
             assert!(http_error.to_string().starts_with("HTTP error:"));
         }
 
+        #[cfg(feature = "rpki")]
+        {
+            let json_error = Error::Json(serde_json::from_str::<u32>("x").unwrap_err());
+            assert!(json_error.to_string().starts_with("JSON error:"));
+        }
+
+        assert_eq!(Error::Cancelled.to_string(), "the build was cancelled");
+
+        let overlap_error = Error::Overlap {
+            existing: Box::new(range_record(1, 13335)),
+            new: Box::new(range_record(2, 15169)),
+        };
+        assert_eq!(
+            overlap_error.to_string(),
+            "Overlap error: line 2 (1.0.0.0-1.0.0.255 AS15169 US EXAMPLE) overlaps \
+             line 1 (1.0.0.0-1.0.0.255 AS13335 US EXAMPLE)"
+        );
+
+        let mut report = BuildReport {
+            lines_read: 12,
+            records_inserted: 3,
+            ..BuildReport::default()
+        };
+        let min_records_error = Error::Limit {
+            limit: BuildLimit::MinRecords(1000),
+            report: Box::new(report.clone()),
+        };
+        assert_eq!(
+            min_records_error.to_string(),
+            "Limit error: 3 records were inserted from 12 lines, fewer than the minimum of 1000"
+        );
+
+        for (name, count) in [("IncorrectColumnCount", 1), ("InvalidAsnNumber", 8)] {
+            report.warnings.insert(
+                name,
+                WarningSummary {
+                    count,
+                    samples: Vec::new(),
+                },
+            );
+        }
+        let max_warnings_error = Error::Limit {
+            limit: BuildLimit::MaxWarnings(8),
+            report: Box::new(report),
+        };
+        assert_eq!(
+            max_warnings_error.to_string(),
+            "Limit error: more than 8 lines were skipped by line 12 \
+             (skipped: IncorrectColumnCount x1, InvalidAsnNumber x8)"
+        );
+
         let parse_error = Error::Parse {
             line_number: 42,
             line_content: "bad line".to_string(),
@@ -694,11 +2486,13 @@ mod tests {
         let parse_warning = Warning::Parse {
             line_number: 10,
             line_content: "another bad line".to_string(),
-            message: "some issue".to_string(),
+            kind: ParseErrorKind::InvalidAsnNumber {
+                value: "AS-X".to_string(),
+            },
         };
         assert_eq!(
             parse_warning.to_string(),
-            "Parse warning on line 10: some issue in line: \"another bad line\""
+            "Parse warning on line 10: invalid ASN: AS-X in line: \"another bad line\""
         );
 
         let mismatch_warning = Warning::IpFamilyMismatch {
@@ -709,6 +2503,28 @@ mod tests {
             mismatch_warning.to_string(),
             "IP family mismatch on line 20: \"v4-and-v6\""
         );
+
+        let overlap_warning = Warning::Overlap {
+            existing: range_record(1, 13335),
+            new: range_record(2, 15169),
+        };
+        assert_eq!(
+            overlap_warning.to_string(),
+            "Overlap on line 2: 1.0.0.0-1.0.0.255 AS15169 US EXAMPLE overlaps \
+             line 1 (1.0.0.0-1.0.0.255 AS13335 US EXAMPLE)"
+        );
+    }
+
+    #[test]
+    fn test_parse_error_kind_name() {
+        assert_eq!(ParseErrorKind::IpFamilyMismatch.name(), "IpFamilyMismatch");
+        let err = ParseErrorKind::InvalidField {
+            field: "status".to_string(),
+            value: "lost".to_string(),
+        };
+        assert_eq!(err.name(), "InvalidField");
+        // The name matches the variant in the `Debug` output.
+        assert!(format!("{err:?}").starts_with(err.name()));
     }
 
     #[test]
@@ -752,6 +2568,45 @@ mod tests {
             value: "USA".to_string(),
         };
         assert_eq!(err.to_string(), "invalid country code: USA");
+
+        let err = ParseErrorKind::InvalidNetwork {
+            value: "1.0.0.1/24".to_string(),
+        };
+        assert_eq!(err.to_string(), "invalid network: 1.0.0.1/24");
+
+        let err = ParseErrorKind::MultipleOrigins {
+            value: "4134_4837".to_string(),
+        };
+        assert_eq!(err.to_string(), "multiple origin ASNs: 4134_4837");
+
+        let err = ParseErrorKind::AsSetOrigin {
+            value: "4134,4837".to_string(),
+        };
+        assert_eq!(err.to_string(), "AS set origin: 4134,4837");
+
+        let err = ParseErrorKind::InvalidMrtRecord {
+            message: "record is truncated".to_string(),
+        };
+        assert_eq!(err.to_string(), "invalid MRT record: record is truncated");
+
+        let err = ParseErrorKind::MissingField {
+            field: "autonomous_system_number".to_string(),
+        };
+        assert_eq!(err.to_string(), "missing field `autonomous_system_number`");
+
+        let err = ParseErrorKind::InvalidMmdbRecord {
+            message: "map key is not a string".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "invalid MMDB record: map key is not a string"
+        );
+
+        let err = ParseErrorKind::InvalidField {
+            field: "status".to_string(),
+            value: "lost".to_string(),
+        };
+        assert_eq!(err.to_string(), "invalid value for field `status`: lost");
     }
 
     #[test]
@@ -768,27 +2623,35 @@ mod tests {
 
         let info1 = AsnInfo {
             network: "1.0.0.0/24".parse().unwrap(),
-            asn: 13335,
+            asn: Asn::new(13335),
             country_code: "AU".to_string(),
             organization: "CLOUDFLARENET".to_string(),
+            organization_parts: OrganizationInfo::default(),
+            registry: None,
         };
         let info2 = AsnInfo {
             network: "1.0.0.0/24".parse().unwrap(),
-            asn: 13335,
+            asn: Asn::new(13335),
             country_code: "AU".to_string(),
             organization: "CLOUDFLARENET".to_string(),
+            organization_parts: OrganizationInfo::default(),
+            registry: None,
         };
         let info3 = AsnInfo {
             network: "8.8.8.0/24".parse().unwrap(),
-            asn: 15169,
+            asn: Asn::new(15169),
             country_code: "US".to_string(),
             organization: "GOOGLE".to_string(),
+            organization_parts: OrganizationInfo::default(),
+            registry: None,
         };
         let info4 = AsnInfo {
             network: "1.0.0.0/24".parse().unwrap(),
-            asn: 13336, // Different ASN
+            asn: Asn::new(13336), // Different ASN
             country_code: "AU".to_string(),
             organization: "CLOUDFLARENET".to_string(),
+            organization_parts: OrganizationInfo::default(),
+            registry: None,
         };
 
         // Test Ord
@@ -809,9 +2672,11 @@ mod tests {
     fn test_asn_info_display() {
         let info = AsnInfo {
             network: "192.0.2.0/24".parse().unwrap(),
-            asn: 64496,
+            asn: Asn::new(64496),
             country_code: "ZZ".to_string(),
             organization: "TEST-NET".to_string(),
+            organization_parts: OrganizationInfo::default(),
+            registry: None,
         };
         assert_eq!(info.to_string(), "AS64496 TEST-NET (ZZ) in 192.0.2.0/24");
     }
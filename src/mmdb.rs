@@ -0,0 +1,871 @@
+//! Contains the logic for writing and reading MaxMind DB (MMDB) files.
+//!
+//! A MaxMind DB file is a binary search tree over the bits of an address,
+//! followed by a data section holding the records the tree points to, and a
+//! metadata map. Maps are written as IPv6 databases, with IPv4 networks placed
+//! in the `::/96` subtree as MaxMind readers expect. Each record is a map of
+//! the form
+//!
+//! ```text
+//! {
+//!   "autonomous_system_number": 13335,
+//!   "autonomous_system_organization": "CLOUDFLARENET",
+//!   "country": { "iso_code": "AU" }
+//! }
+//! ```
+//!
+//! following the GeoLite2-ASN and GeoLite2-Country layouts. The `country` map
+//! is left out for records with the unknown country `ZZ`.
+//!
+//! When reading, the fields taken from each record are configured with
+//! [`MmdbFields`], so any ASN-type database can be loaded.
+
+use crate::asn::Asn;
+use crate::types::AsnRecord;
+use crate::ParseErrorKind;
+use ip_network::{IpNetwork, Ipv4Network, Ipv6Network};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The marker that precedes the metadata map at the end of the file.
+const METADATA_START_MARKER: &[u8] = b"\xAB\xCD\xEFMaxMind.com";
+
+/// The number of zero bytes between the search tree and the data section.
+const DATA_SECTION_SEPARATOR_SIZE: usize = 16;
+
+/// Options for [`IpAsnMap::write_mmdb_with`](crate::IpAsnMap::write_mmdb_with).
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::mmdb::MmdbOptions;
+///
+/// let options = MmdbOptions::new()
+///     .database_type("iptoasn-ASN")
+///     .description("IP to ASN data from iptoasn.com")
+///     .build_epoch(1_700_000_000);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MmdbOptions {
+    database_type: String,
+    description: String,
+    build_epoch: Option<u64>,
+}
+
+impl Default for MmdbOptions {
+    fn default() -> Self {
+        Self {
+            database_type: "ip2asn-ASN".to_string(),
+            description: "IP to ASN data written by ip2asn".to_string(),
+            build_epoch: None,
+        }
+    }
+}
+
+impl MmdbOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `database_type` metadata field. Defaults to `ip2asn-ASN`.
+    pub fn database_type(mut self, database_type: &str) -> Self {
+        self.database_type = database_type.to_string();
+        self
+    }
+
+    /// Sets the English `description` metadata field.
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    /// Sets the `build_epoch` metadata field, in seconds since the Unix
+    /// epoch. Defaults to the current time.
+    ///
+    /// Setting this makes the output reproducible.
+    pub fn build_epoch(mut self, build_epoch: u64) -> Self {
+        self.build_epoch = Some(build_epoch);
+        self
+    }
+}
+
+/// One of the two records of a search tree node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Record {
+    /// No data for this subtree.
+    Empty,
+    /// The index of a child node.
+    Node(u32),
+    /// The offset of a value in the data section.
+    Data(u32),
+}
+
+/// An in-memory MaxMind DB search tree with a deduplicated data section.
+struct TreeBuilder {
+    nodes: Vec<[Record; 2]>,
+    data: Vec<u8>,
+    offsets: HashMap<AsnRecord, u32>,
+}
+
+impl TreeBuilder {
+    fn new() -> Self {
+        Self {
+            nodes: vec![[Record::Empty; 2]],
+            data: Vec::new(),
+            offsets: HashMap::new(),
+        }
+    }
+
+    /// Points every address under the first `length` bits of `bits` at the
+    /// data for `record`.
+    ///
+    /// Networks must be inserted from least to most specific, so that a more
+    /// specific network overrides part of a less specific one.
+    fn insert(&mut self, bits: u128, length: u8, record: Record) {
+        let mut node = 0;
+        for depth in 0..length {
+            let bit = ((bits >> (127 - depth)) & 1) as usize;
+            if depth + 1 == length {
+                self.nodes[node][bit] = record;
+                return;
+            }
+            node = match self.nodes[node][bit] {
+                Record::Node(child) => child as usize,
+                inherited => {
+                    // Split the subtree, keeping what it held before.
+                    let child = self.nodes.len();
+                    self.nodes.push([inherited; 2]);
+                    self.nodes[node][bit] = Record::Node(child as u32);
+                    child
+                }
+            };
+        }
+    }
+
+    /// Returns the data section record for an `AsnRecord`, encoding it on
+    /// first use.
+    fn data_record(&mut self, record: &AsnRecord, organizations: &[String]) -> Record {
+        let offset = *self.offsets.entry(*record).or_insert_with(|| {
+            let offset = self.data.len() as u32;
+            encode_record(&mut self.data, record, organizations);
+            offset
+        });
+        Record::Data(offset)
+    }
+
+    fn write(&self, mut writer: impl Write, options: &MmdbOptions) -> io::Result<()> {
+        let node_count = self.nodes.len() as u64;
+        let max_value = node_count + (DATA_SECTION_SEPARATOR_SIZE + self.data.len()) as u64;
+        let record_size: u16 = match max_value {
+            v if v < 1 << 24 => 24,
+            v if v < 1 << 28 => 28,
+            v if v < 1 << 32 => 32,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "map is too large for a MaxMind DB file",
+                ))
+            }
+        };
+
+        let value = |record: Record| -> u32 {
+            match record {
+                Record::Empty => node_count as u32,
+                Record::Node(child) => child,
+                Record::Data(offset) => {
+                    (node_count as usize + DATA_SECTION_SEPARATOR_SIZE + offset as usize) as u32
+                }
+            }
+        };
+
+        let mut tree = Vec::with_capacity(self.nodes.len() * usize::from(record_size) / 4);
+        for [left, right] in &self.nodes {
+            encode_node(&mut tree, record_size, value(*left), value(*right));
+        }
+        writer.write_all(&tree)?;
+        writer.write_all(&[0; DATA_SECTION_SEPARATOR_SIZE])?;
+        writer.write_all(&self.data)?;
+
+        let build_epoch = options.build_epoch.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs())
+        });
+        let mut metadata = Vec::new();
+        encode_map_header(&mut metadata, 9);
+        encode_string(&mut metadata, "binary_format_major_version");
+        encode_uint(&mut metadata, UINT16, 2);
+        encode_string(&mut metadata, "binary_format_minor_version");
+        encode_uint(&mut metadata, UINT16, 0);
+        encode_string(&mut metadata, "build_epoch");
+        encode_uint(&mut metadata, UINT64, build_epoch);
+        encode_string(&mut metadata, "database_type");
+        encode_string(&mut metadata, &options.database_type);
+        encode_string(&mut metadata, "description");
+        encode_map_header(&mut metadata, 1);
+        encode_string(&mut metadata, "en");
+        encode_string(&mut metadata, &options.description);
+        encode_string(&mut metadata, "ip_version");
+        encode_uint(&mut metadata, UINT16, 6);
+        encode_string(&mut metadata, "languages");
+        encode_control(&mut metadata, ARRAY, 1);
+        encode_string(&mut metadata, "en");
+        encode_string(&mut metadata, "node_count");
+        encode_uint(&mut metadata, UINT32, node_count);
+        encode_string(&mut metadata, "record_size");
+        encode_uint(&mut metadata, UINT16, u64::from(record_size));
+
+        writer.write_all(METADATA_START_MARKER)?;
+        writer.write_all(&metadata)?;
+        writer.flush()
+    }
+}
+
+/// Appends a search tree node with records of the given size in bits.
+fn encode_node(tree: &mut Vec<u8>, record_size: u16, left: u32, right: u32) {
+    match record_size {
+        24 => {
+            tree.extend_from_slice(&left.to_be_bytes()[1..]);
+            tree.extend_from_slice(&right.to_be_bytes()[1..]);
+        }
+        28 => {
+            // The middle byte holds the top four bits of each record.
+            tree.extend_from_slice(&left.to_be_bytes()[1..]);
+            tree.push((((left >> 24) as u8) << 4) | (right >> 24) as u8);
+            tree.extend_from_slice(&right.to_be_bytes()[1..]);
+        }
+        _ => {
+            tree.extend_from_slice(&left.to_be_bytes());
+            tree.extend_from_slice(&right.to_be_bytes());
+        }
+    }
+}
+
+/// Writes the IPv4 and IPv6 blocks of a map as a MaxMind DB file.
+pub(crate) fn write_mmdb<'t>(
+    writer: impl Write,
+    options: &MmdbOptions,
+    ipv4: impl Iterator<Item = (Ipv4Network, &'t AsnRecord)>,
+    ipv6: impl Iterator<Item = (Ipv6Network, &'t AsnRecord)>,
+    organizations: &[String],
+) -> io::Result<()> {
+    let mut blocks: Vec<(u128, u8, &AsnRecord)> = ipv6
+        .map(|(network, record)| {
+            let bits = u128::from(network.network_address());
+            (bits, network.netmask(), record)
+        })
+        .chain(ipv4.map(|(network, record)| {
+            let bits = u128::from(u32::from(network.network_address()));
+            (bits, 96 + network.netmask(), record)
+        }))
+        .collect();
+    // Less specific networks go first so that nested ones override them.
+    blocks.sort_by_key(|(bits, length, _)| (*length, *bits));
+
+    let mut tree = TreeBuilder::new();
+    for (bits, length, record) in blocks {
+        let data = tree.data_record(record, organizations);
+        if length == 0 {
+            // A default route covers both halves of the root.
+            tree.nodes[0] = [data; 2];
+        } else {
+            tree.insert(bits, length, data);
+        }
+    }
+    tree.write(writer, options)
+}
+
+const POINTER: u8 = 1;
+const STRING: u8 = 2;
+const DOUBLE: u8 = 3;
+const BYTES: u8 = 4;
+const UINT16: u8 = 5;
+const UINT32: u8 = 6;
+const MAP: u8 = 7;
+const INT32: u8 = 8;
+const UINT64: u8 = 9;
+const UINT128: u8 = 10;
+const ARRAY: u8 = 11;
+const BOOLEAN: u8 = 14;
+const FLOAT: u8 = 15;
+
+/// Writes the control byte for a value of the given type and size, followed
+/// by any extended type and size bytes.
+fn encode_control(buf: &mut Vec<u8>, data_type: u8, size: usize) {
+    let (size_bits, extra) = match size {
+        0..=28 => (size as u8, Vec::new()),
+        29..=284 => (29, vec![(size - 29) as u8]),
+        285..=65_820 => (30, ((size - 285) as u16).to_be_bytes().to_vec()),
+        _ => (31, ((size - 65_821) as u32).to_be_bytes()[1..].to_vec()),
+    };
+    if data_type > 7 {
+        buf.push(size_bits);
+        buf.push(data_type - 7);
+    } else {
+        buf.push((data_type << 5) | size_bits);
+    }
+    buf.extend_from_slice(&extra);
+}
+
+fn encode_string(buf: &mut Vec<u8>, value: &str) {
+    encode_control(buf, STRING, value.len());
+    buf.extend_from_slice(value.as_bytes());
+}
+
+fn encode_uint(buf: &mut Vec<u8>, data_type: u8, value: u64) {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count();
+    encode_control(buf, data_type, bytes.len() - skip);
+    buf.extend_from_slice(&bytes[skip..]);
+}
+
+fn encode_map_header(buf: &mut Vec<u8>, pairs: usize) {
+    encode_control(buf, MAP, pairs);
+}
+
+fn encode_record(buf: &mut Vec<u8>, record: &AsnRecord, organizations: &[String]) {
+    let country = match &record.country_code {
+        b"ZZ" => None,
+        code => std::str::from_utf8(code).ok(),
+    };
+    encode_map_header(buf, if country.is_some() { 3 } else { 2 });
+    encode_string(buf, "autonomous_system_number");
+    encode_uint(buf, UINT32, u64::from(record.asn.get()));
+    encode_string(buf, "autonomous_system_organization");
+    encode_string(buf, &organizations[record.organization_idx as usize]);
+    if let Some(country) = country {
+        encode_string(buf, "country");
+        encode_map_header(buf, 1);
+        encode_string(buf, "iso_code");
+        encode_string(buf, country);
+    }
+}
+
+/// The paths of the fields read from each record by
+/// [`Builder::from_mmdb`](crate::Builder::from_mmdb).
+///
+/// Paths are dot-separated map keys, with array elements addressed by index.
+/// The defaults match the GeoLite2-ASN layout, plus the `country.iso_code`
+/// field of the GeoLite2-Country layout.
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::mmdb::MmdbFields;
+///
+/// // An ISP database that stores the ASN under different names.
+/// let fields = MmdbFields::new()
+///     .asn("asn")
+///     .organization("isp")
+///     .country("registered_country.iso_code");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MmdbFields {
+    asn: String,
+    organization: String,
+    country: String,
+}
+
+impl Default for MmdbFields {
+    fn default() -> Self {
+        Self {
+            asn: "autonomous_system_number".to_string(),
+            organization: "autonomous_system_organization".to_string(),
+            country: "country.iso_code".to_string(),
+        }
+    }
+}
+
+impl MmdbFields {
+    /// Creates the default field paths.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the path of the ASN, which must be an unsigned integer or a
+    /// string such as `AS13335`. Records without it are treated as
+    /// malformed. Defaults to `autonomous_system_number`.
+    pub fn asn(mut self, path: &str) -> Self {
+        self.asn = path.to_string();
+        self
+    }
+
+    /// Sets the path of the organization name. Records without it are given
+    /// an empty organization. Defaults to `autonomous_system_organization`.
+    pub fn organization(mut self, path: &str) -> Self {
+        self.organization = path.to_string();
+        self
+    }
+
+    /// Sets the path of the two-letter country code. Records without it are
+    /// given the unknown country `ZZ`. Defaults to `country.iso_code`.
+    pub fn country(mut self, path: &str) -> Self {
+        self.country = path.to_string();
+        self
+    }
+}
+
+/// The fields read from a single MMDB record.
+#[derive(Debug, Clone)]
+pub(crate) struct MmdbRecord {
+    pub(crate) asn: Asn,
+    pub(crate) country_code: [u8; 2],
+    pub(crate) organization: String,
+}
+
+/// A decoded value from the data or metadata section.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+    Uint(u128),
+    Int(i32),
+    Map(Vec<(String, Value)>),
+    Array(Vec<Value>),
+    /// A double, float, boolean, or byte string, none of which are read.
+    Other,
+}
+
+impl Value {
+    fn get(&self, path: &str) -> Option<&Value> {
+        path.split('.').try_fold(self, |value, key| match value {
+            Value::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+    }
+}
+
+/// The deepest nesting of maps, arrays, and pointers that is decoded.
+const MAX_DECODE_DEPTH: usize = 64;
+
+/// A decoder for one section of a MaxMind DB file, which pointers are
+/// relative to.
+struct Decoder<'a> {
+    section: &'a [u8],
+}
+
+impl Decoder<'_> {
+    /// Decodes the value at `offset`, returning it and the offset just past
+    /// it.
+    fn decode(&self, offset: usize, depth: usize) -> Result<(Value, usize), String> {
+        if depth > MAX_DECODE_DEPTH {
+            return Err("values are nested too deeply".to_string());
+        }
+        let control = self.byte(offset)?;
+        let mut pos = offset + 1;
+        let mut data_type = control >> 5;
+        if data_type == POINTER {
+            let size = usize::from((control >> 3) & 0x3);
+            let bytes = self.bytes(pos, size + 1)?;
+            let high = usize::from(control & 0x7);
+            let target = match size {
+                0 => (high << 8) | usize::from(bytes[0]),
+                1 => ((high << 16) | be_uint(bytes) as usize) + 2048,
+                2 => ((high << 24) | be_uint(bytes) as usize) + 526_336,
+                _ => be_uint(bytes) as usize,
+            };
+            let (value, _) = self.decode(target, depth + 1)?;
+            return Ok((value, pos + size + 1));
+        }
+        if data_type == 0 {
+            data_type = 7 + self.byte(pos)?;
+            pos += 1;
+        }
+        let mut size = usize::from(control & 0x1f);
+        if size >= 29 {
+            let extra = size - 28;
+            let bytes = self.bytes(pos, extra)?;
+            size = match extra {
+                1 => 29,
+                2 => 285,
+                _ => 65_821,
+            } + be_uint(bytes) as usize;
+            pos += extra;
+        }
+
+        match data_type {
+            STRING => {
+                let bytes = self.bytes(pos, size)?;
+                let value = std::str::from_utf8(bytes).map_err(|_| "invalid UTF-8 string")?;
+                Ok((Value::String(value.to_string()), pos + size))
+            }
+            UINT16 | UINT32 | UINT64 | UINT128 => {
+                let bytes = self.bytes(pos, size)?;
+                if size > 16 {
+                    return Err(format!("integer of {size} bytes"));
+                }
+                Ok((Value::Uint(be_uint(bytes)), pos + size))
+            }
+            INT32 => {
+                let bytes = self.bytes(pos, size)?;
+                if size > 4 {
+                    return Err(format!("integer of {size} bytes"));
+                }
+                Ok((Value::Int(be_uint(bytes) as u32 as i32), pos + size))
+            }
+            MAP => {
+                let mut entries = Vec::with_capacity(size.min(64));
+                for _ in 0..size {
+                    let (key, next) = self.decode(pos, depth + 1)?;
+                    let Value::String(key) = key else {
+                        return Err("map key is not a string".to_string());
+                    };
+                    let (value, next) = self.decode(next, depth + 1)?;
+                    entries.push((key, value));
+                    pos = next;
+                }
+                Ok((Value::Map(entries), pos))
+            }
+            ARRAY => {
+                let mut items = Vec::with_capacity(size.min(64));
+                for _ in 0..size {
+                    let (item, next) = self.decode(pos, depth + 1)?;
+                    items.push(item);
+                    pos = next;
+                }
+                Ok((Value::Array(items), pos))
+            }
+            BOOLEAN => Ok((Value::Other, pos)),
+            DOUBLE | BYTES | FLOAT => {
+                self.bytes(pos, size)?;
+                Ok((Value::Other, pos + size))
+            }
+            other => Err(format!("unsupported data type {other}")),
+        }
+    }
+
+    fn byte(&self, offset: usize) -> Result<u8, String> {
+        Ok(self.bytes(offset, 1)?[0])
+    }
+
+    fn bytes(&self, offset: usize, len: usize) -> Result<&[u8], String> {
+        offset
+            .checked_add(len)
+            .and_then(|end| self.section.get(offset..end))
+            .ok_or_else(|| format!("offset {offset} is past the end of the section"))
+    }
+}
+
+fn be_uint(bytes: &[u8]) -> u128 {
+    bytes
+        .iter()
+        .fold(0, |value, byte| (value << 8) | u128::from(*byte))
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Walks every network in a MaxMind DB file, calling `f` with the network and
+/// the fields read from its record.
+///
+/// IPv4 networks of an IPv6 database are reported as IPv4 networks, and the
+/// aliases of the IPv4 subtree that MaxMind databases usually contain are
+/// skipped. Records are decoded once, however many networks point at them.
+///
+/// # Errors
+///
+/// Returns an `io::ErrorKind::InvalidData` error if the file has no metadata
+/// or its search tree is malformed. Records that cannot be decoded or lack an
+/// ASN are passed to `f` as a `ParseErrorKind`.
+pub(crate) fn for_each_network<E: From<io::Error>>(
+    buf: &[u8],
+    fields: &MmdbFields,
+    mut f: impl FnMut(IpNetwork, Result<MmdbRecord, ParseErrorKind>) -> Result<(), E>,
+) -> Result<(), E> {
+    let marker = buf
+        .windows(METADATA_START_MARKER.len())
+        .rposition(|window| window == METADATA_START_MARKER)
+        .ok_or_else(|| invalid_data("not a MaxMind DB file: metadata marker not found"))?;
+    let metadata = Decoder {
+        section: &buf[marker + METADATA_START_MARKER.len()..],
+    };
+    let (metadata, _) = metadata
+        .decode(0, 0)
+        .map_err(|message| invalid_data(format!("invalid metadata: {message}")))?;
+    let metadata_uint = |key: &str| match metadata.get(key) {
+        Some(Value::Uint(value)) => Ok(*value),
+        _ => Err(invalid_data(format!("metadata is missing `{key}`"))),
+    };
+    let node_count = usize::try_from(metadata_uint("node_count")?)
+        .map_err(|_| invalid_data("node count is too large"))?;
+    let record_size = metadata_uint("record_size")?;
+    let ip_version = metadata_uint("ip_version")?;
+    if ![24, 28, 32].contains(&record_size) {
+        return Err(invalid_data(format!("unsupported record size {record_size}")).into());
+    }
+    let bit_count: u8 = match ip_version {
+        4 => 32,
+        6 => 128,
+        _ => return Err(invalid_data(format!("unsupported IP version {ip_version}")).into()),
+    };
+
+    let node_size = record_size as usize / 4;
+    let tree_size = node_count
+        .checked_mul(node_size)
+        .filter(|size| size + DATA_SECTION_SEPARATOR_SIZE <= marker)
+        .ok_or_else(|| invalid_data("search tree is larger than the file"))?;
+    let data = Decoder {
+        section: &buf[tree_size + DATA_SECTION_SEPARATOR_SIZE..marker],
+    };
+    let read_node = |node: usize| -> [usize; 2] {
+        let bytes = &buf[node * node_size..(node + 1) * node_size];
+        match record_size {
+            24 => [be_uint(&bytes[..3]) as usize, be_uint(&bytes[3..]) as usize],
+            28 => [
+                (usize::from(bytes[3] >> 4) << 24) | be_uint(&bytes[..3]) as usize,
+                (usize::from(bytes[3] & 0x0f) << 24) | be_uint(&bytes[4..]) as usize,
+            ],
+            _ => [be_uint(&bytes[..4]) as usize, be_uint(&bytes[4..]) as usize],
+        }
+    };
+
+    // The node reached by the 96 zero bits of `::/96`, which aliases such as
+    // `::ffff:0:0/96` and `2002::/16` point back to. If the path ends early
+    // in a data record, there is no such node, and data records must not be
+    // mistaken for aliases, since records with the same data share one.
+    let mut ipv4_start = None;
+    if bit_count == 128 {
+        let mut node = 0;
+        for _ in 0..96 {
+            if node >= node_count {
+                break;
+            }
+            node = read_node(node)[0];
+        }
+        ipv4_start = Some(node).filter(|&node| node < node_count);
+    }
+
+    let mut cache: HashMap<usize, Result<MmdbRecord, ParseErrorKind>> = HashMap::new();
+    // Children are pushed right first, so networks are visited in order.
+    let mut stack = vec![(0_usize, 0_u128, 0_u8)];
+    while let Some((value, bits, depth)) = stack.pop() {
+        if value < node_count {
+            if depth == bit_count {
+                return Err(invalid_data("search tree is deeper than an address").into());
+            }
+            let records = read_node(value);
+            for bit in [1, 0] {
+                let child = records[bit];
+                let child_bits = bits | ((bit as u128) << (bit_count - 1 - depth));
+                let is_alias = Some(child) == ipv4_start && (depth + 1 != 96 || child_bits != 0);
+                if !is_alias {
+                    stack.push((child, child_bits, depth + 1));
+                }
+            }
+            continue;
+        }
+        if value == node_count {
+            continue;
+        }
+
+        let network = if bit_count == 128 && depth >= 96 && bits >> 32 == 0 {
+            Ipv4Network::new_truncate(Ipv4Addr::from(bits as u32), depth - 96).map(IpNetwork::V4)
+        } else if bit_count == 128 {
+            Ipv6Network::new_truncate(Ipv6Addr::from(bits), depth).map(IpNetwork::V6)
+        } else {
+            Ipv4Network::new_truncate(Ipv4Addr::from(bits as u32), depth).map(IpNetwork::V4)
+        }
+        .map_err(|_| invalid_data("search tree is deeper than an address"))?;
+
+        let offset = (value - node_count)
+            .checked_sub(DATA_SECTION_SEPARATOR_SIZE)
+            .ok_or_else(|| invalid_data("search tree points into the separator"))?;
+        let record = cache
+            .entry(offset)
+            .or_insert_with(|| decode_record(&data, offset, fields))
+            .clone();
+        f(network, record)?;
+    }
+    Ok(())
+}
+
+fn decode_record(
+    data: &Decoder<'_>,
+    offset: usize,
+    fields: &MmdbFields,
+) -> Result<MmdbRecord, ParseErrorKind> {
+    let (value, _) = data
+        .decode(offset, 0)
+        .map_err(|message| ParseErrorKind::InvalidMmdbRecord { message })?;
+
+    let asn = match value.get(&fields.asn) {
+        Some(Value::Uint(asn)) => u32::try_from(*asn).ok().map(Asn::new),
+        Some(Value::String(asn)) => asn.parse().ok(),
+        Some(_) => None,
+        None => {
+            return Err(ParseErrorKind::MissingField {
+                field: fields.asn.clone(),
+            })
+        }
+    }
+    .ok_or_else(|| ParseErrorKind::InvalidAsnNumber {
+        value: format!("{:?}", value.get(&fields.asn)),
+    })?;
+
+    let organization = match value.get(&fields.organization) {
+        Some(Value::String(organization)) => organization.clone(),
+        _ => String::new(),
+    };
+
+    let country_code = match value.get(&fields.country) {
+        Some(Value::String(code)) => match code.as_bytes() {
+            [a, b] => [*a, *b],
+            _ => {
+                return Err(ParseErrorKind::InvalidCountryCode {
+                    value: code.clone(),
+                })
+            }
+        },
+        _ => [b'Z'; 2],
+    };
+
+    Ok(MmdbRecord {
+        asn,
+        country_code,
+        organization,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_control_sizes() {
+        let mut buf = Vec::new();
+        encode_control(&mut buf, STRING, 28);
+        assert_eq!(buf, [0x5c]);
+
+        buf.clear();
+        encode_control(&mut buf, STRING, 29);
+        assert_eq!(buf, [0x5d, 0x00]);
+
+        buf.clear();
+        encode_control(&mut buf, STRING, 285);
+        assert_eq!(buf, [0x5e, 0x00, 0x00]);
+
+        buf.clear();
+        encode_control(&mut buf, STRING, 65_821);
+        assert_eq!(buf, [0x5f, 0x00, 0x00, 0x00]);
+
+        buf.clear();
+        encode_control(&mut buf, ARRAY, 1);
+        assert_eq!(buf, [0x01, 0x04]);
+    }
+
+    #[test]
+    fn test_encode_uint() {
+        let mut buf = Vec::new();
+        encode_uint(&mut buf, UINT32, 0);
+        assert_eq!(buf, [0xc0]);
+
+        buf.clear();
+        encode_uint(&mut buf, UINT32, 13335);
+        assert_eq!(buf, [0xc2, 0x34, 0x17]);
+    }
+
+    #[test]
+    fn test_encode_node() {
+        let mut tree = Vec::new();
+        encode_node(&mut tree, 24, 0x010203, 0x040506);
+        assert_eq!(tree, [1, 2, 3, 4, 5, 6]);
+
+        tree.clear();
+        encode_node(&mut tree, 28, 0x0a01_0203, 0x0b04_0506);
+        assert_eq!(tree, [1, 2, 3, 0xab, 4, 5, 6]);
+
+        tree.clear();
+        encode_node(&mut tree, 32, 0x0102_0304, 0x0506_0708);
+        assert_eq!(tree, [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_for_each_network_skips_ipv4_aliases() {
+        let organizations = vec!["TEST".to_string()];
+        let record = AsnRecord {
+            asn: Asn::new(64496),
+            country_code: *b"NL",
+            organization_idx: 0,
+        };
+        let mut tree = TreeBuilder::new();
+        let data = tree.data_record(&record, &organizations);
+        tree.insert(u128::from(0x0a00_0000_u32), 104, data);
+
+        // Point `::ffff:0:0/96` at the node for `::/96`.
+        let mut ipv4_start = 0;
+        for _ in 0..96 {
+            let Record::Node(child) = tree.nodes[ipv4_start][0] else {
+                unreachable!()
+            };
+            ipv4_start = child as usize;
+        }
+        tree.insert(0xffff << 32, 96, Record::Node(ipv4_start as u32));
+
+        let mut buf = Vec::new();
+        tree.write(&mut buf, &MmdbOptions::new()).unwrap();
+        let mut networks = Vec::new();
+        for_each_network(&buf, &MmdbFields::new(), |network, record| {
+            networks.push((network, record.unwrap().asn));
+            Ok::<_, io::Error>(())
+        })
+        .unwrap();
+        assert_eq!(
+            networks,
+            vec![("10.0.0.0/8".parse().unwrap(), Asn::new(64496))]
+        );
+    }
+
+    #[test]
+    fn test_for_each_network_short_ipv4_path() {
+        let organizations = vec!["TEST".to_string()];
+        let record = AsnRecord {
+            asn: Asn::new(64496),
+            country_code: *b"NL",
+            organization_idx: 0,
+        };
+        let mut tree = TreeBuilder::new();
+        let data = tree.data_record(&record, &organizations);
+        // The `::/96` path ends in the data record at `::/8`, which
+        // `2001:db8::/32` shares.
+        tree.insert(0, 8, data);
+        tree.insert(0x2001_0db8 << 96, 32, data);
+
+        let mut buf = Vec::new();
+        tree.write(&mut buf, &MmdbOptions::new()).unwrap();
+        let mut networks = Vec::new();
+        for_each_network(&buf, &MmdbFields::new(), |network, record| {
+            networks.push((network, record.unwrap().asn));
+            Ok::<_, io::Error>(())
+        })
+        .unwrap();
+        assert_eq!(
+            networks,
+            vec![
+                ("::/8".parse().unwrap(), Asn::new(64496)),
+                ("2001:db8::/32".parse().unwrap(), Asn::new(64496)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_pointer_loop() {
+        // A pointer to itself.
+        let decoder = Decoder {
+            section: &[0x20, 0x00],
+        };
+        assert!(decoder.decode(0, 0).is_err());
+    }
+
+    #[test]
+    fn test_insert_splits_inherited_records() {
+        let mut tree = TreeBuilder::new();
+        tree.insert(0, 1, Record::Data(0));
+        tree.insert(1 << 126, 2, Record::Data(7));
+        assert_eq!(tree.nodes[0], [Record::Node(1), Record::Empty]);
+        assert_eq!(tree.nodes[1], [Record::Data(0), Record::Data(7)]);
+    }
+}
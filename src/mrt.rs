@@ -0,0 +1,468 @@
+//! Contains the logic for reading MRT `TABLE_DUMP_V2` RIB snapshots.
+//!
+//! Each RIB record holds one prefix and the route that every peer of the
+//! collector had for it. The origin of a route is the last AS in its
+//! `AS_PATH`, or the peer's own AS when the path is empty. Records of other
+//! types, such as BGP4MP updates or multicast RIBs, are skipped.
+//!
+//! See [RFC 6396](https://www.rfc-editor.org/rfc/rfc6396) for the format.
+
+use crate::asn::Asn;
+use crate::{MultiOriginPolicy, ParseErrorKind};
+use ip_network::IpNetwork;
+use std::io::{self, Read};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+const TABLE_DUMP_V2: u16 = 13;
+const PEER_INDEX_TABLE: u16 = 1;
+const RIB_IPV4_UNICAST: u16 = 2;
+const RIB_IPV6_UNICAST: u16 = 4;
+const RIB_IPV4_UNICAST_ADDPATH: u16 = 8;
+const RIB_IPV6_UNICAST_ADDPATH: u16 = 10;
+
+/// The largest record body that is read, so that a corrupt length cannot
+/// make the reader allocate gigabytes. RIB records from the busiest
+/// collectors are a few hundred kilobytes.
+pub const MAX_RECORD_LEN: u32 = 8 * 1024 * 1024;
+
+const ATTR_AS_PATH: u8 = 2;
+const ATTR_FLAG_EXTENDED_LENGTH: u8 = 0x10;
+
+const AS_SET: u8 = 1;
+const AS_SEQUENCE: u8 = 2;
+
+/// The origin of a single route, taken from the end of its `AS_PATH`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathOrigin {
+    /// The path ended in a single AS.
+    Asn(Asn),
+    /// The path ended in an AS set.
+    AsSet(Vec<Asn>),
+}
+
+/// A prefix from a RIB record, with the origin reported by each peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RibPrefix {
+    /// The announced network.
+    pub network: IpNetwork,
+    /// The origin of each RIB entry, in the order they appear in the record.
+    pub origins: Vec<PathOrigin>,
+}
+
+impl RibPrefix {
+    /// Chooses the origin ASN for the prefix according to the policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseErrorKind::MultipleOrigins` or
+    /// `ParseErrorKind::AsSetOrigin` under [`MultiOriginPolicy::Reject`] if the
+    /// peers disagree on the origin or a route ends in an AS set.
+    pub fn select_origin(&self, policy: MultiOriginPolicy) -> Result<Asn, ParseErrorKind> {
+        let asns: Vec<Asn> = self
+            .origins
+            .iter()
+            .flat_map(|origin| match origin {
+                PathOrigin::Asn(asn) => std::slice::from_ref(asn),
+                PathOrigin::AsSet(set) => set.as_slice(),
+            })
+            .copied()
+            .collect();
+
+        if policy == MultiOriginPolicy::Reject {
+            let describe = || {
+                asns.iter()
+                    .map(Asn::to_string)
+                    .collect::<Vec<_>>()
+                    .join("_")
+            };
+            if self
+                .origins
+                .iter()
+                .any(|origin| matches!(origin, PathOrigin::AsSet(_)))
+            {
+                return Err(ParseErrorKind::AsSetOrigin { value: describe() });
+            }
+            if asns.iter().any(|asn| *asn != asns[0]) {
+                return Err(ParseErrorKind::MultipleOrigins { value: describe() });
+            }
+        }
+
+        policy
+            .choose(asns.iter().copied())
+            .ok_or_else(|| invalid("RIB record has no entries"))
+    }
+}
+
+/// A streaming reader for MRT `TABLE_DUMP_V2` RIB records.
+#[derive(Debug)]
+pub struct MrtReader<R> {
+    reader: R,
+    peer_asns: Vec<Asn>,
+    record_number: usize,
+    subtype: u16,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> MrtReader<R> {
+    /// Creates a reader over an uncompressed MRT stream.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            peer_asns: Vec::new(),
+            record_number: 0,
+            subtype: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    /// The 1-based number of the last record read.
+    pub fn record_number(&self) -> usize {
+        self.record_number
+    }
+
+    /// The subtype of the last record read.
+    pub fn subtype(&self) -> u16 {
+        self.subtype
+    }
+
+    /// Reads the next unicast RIB prefix, skipping records of other types.
+    ///
+    /// Returns `Ok(None)` at the end of the stream. A record that is read in
+    /// full but cannot be decoded, or is longer than [`MAX_RECORD_LEN`],
+    /// yields `Ok(Some(Err(_)))`, and reading can continue with the next
+    /// record.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `io::Error` if the stream cannot be read or ends in the
+    /// middle of a record.
+    pub fn read_prefix(&mut self) -> io::Result<Option<Result<RibPrefix, ParseErrorKind>>> {
+        loop {
+            let mut header = [0u8; 12];
+            if !read_header(&mut self.reader, &mut header)? {
+                return Ok(None);
+            }
+            self.record_number += 1;
+            let record_type = u16::from_be_bytes([header[4], header[5]]);
+            let subtype = u16::from_be_bytes([header[6], header[7]]);
+            let length = u32::from_be_bytes([header[8], header[9], header[10], header[11]]);
+            self.subtype = subtype;
+
+            if record_type != TABLE_DUMP_V2 {
+                self.skip(length)?;
+                continue;
+            }
+            if length > MAX_RECORD_LEN {
+                self.skip(length)?;
+                return Ok(Some(Err(ParseErrorKind::InvalidMrtRecord {
+                    message: format!(
+                        "record length {length} exceeds the maximum of {MAX_RECORD_LEN} bytes"
+                    ),
+                })));
+            }
+            self.buf.resize(length as usize, 0);
+            self.reader.read_exact(&mut self.buf)?;
+
+            let mut body = Cursor::new(&self.buf);
+            let result = match subtype {
+                PEER_INDEX_TABLE => match read_peer_index_table(&mut body) {
+                    Ok(peer_asns) => {
+                        self.peer_asns = peer_asns;
+                        continue;
+                    }
+                    Err(kind) => Err(kind),
+                },
+                RIB_IPV4_UNICAST => read_rib(&mut body, &self.peer_asns, false, false),
+                RIB_IPV6_UNICAST => read_rib(&mut body, &self.peer_asns, true, false),
+                RIB_IPV4_UNICAST_ADDPATH => read_rib(&mut body, &self.peer_asns, false, true),
+                RIB_IPV6_UNICAST_ADDPATH => read_rib(&mut body, &self.peer_asns, true, true),
+                _ => continue,
+            };
+            return Ok(Some(result));
+        }
+    }
+
+    /// Skips a record body without buffering it.
+    fn skip(&mut self, length: u32) -> io::Result<()> {
+        let skipped = io::copy(
+            &mut self.reader.by_ref().take(u64::from(length)),
+            &mut io::sink(),
+        )?;
+        if skipped < u64::from(length) {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+}
+
+/// Reads a record header, returning `false` on a clean end of stream.
+fn read_header(reader: &mut impl Read, header: &mut [u8; 12]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < header.len() {
+        match reader.read(&mut header[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+fn read_peer_index_table(body: &mut Cursor<'_>) -> Result<Vec<Asn>, ParseErrorKind> {
+    body.take(4)?; // Collector BGP ID
+    let view_name_length = body.u16()?;
+    body.take(view_name_length as usize)?;
+    let peer_count = body.u16()?;
+    let mut peer_asns = Vec::with_capacity(peer_count as usize);
+    for _ in 0..peer_count {
+        let peer_type = body.u8()?;
+        body.take(4)?; // Peer BGP ID
+        body.take(if peer_type & 0x01 != 0 { 16 } else { 4 })?;
+        let asn = if peer_type & 0x02 != 0 {
+            body.u32()?
+        } else {
+            u32::from(body.u16()?)
+        };
+        peer_asns.push(Asn::new(asn));
+    }
+    Ok(peer_asns)
+}
+
+fn read_rib(
+    body: &mut Cursor<'_>,
+    peer_asns: &[Asn],
+    ipv6: bool,
+    add_path: bool,
+) -> Result<RibPrefix, ParseErrorKind> {
+    body.take(4)?; // Sequence number
+    let network = read_prefix(body, ipv6)?;
+    let entry_count = body.u16()?;
+    let mut origins = Vec::with_capacity(entry_count as usize);
+    for _ in 0..entry_count {
+        let peer_index = body.u16()?;
+        body.take(4)?; // Originated time
+        if add_path {
+            body.take(4)?; // Path identifier
+        }
+        let attribute_length = body.u16()?;
+        let attributes = body.take(attribute_length as usize)?;
+        let origin = match read_as_path_origin(&mut Cursor::new(attributes))? {
+            Some(origin) => origin,
+            None => {
+                let asn = peer_asns
+                    .get(peer_index as usize)
+                    .ok_or_else(|| invalid("peer index out of range"))?;
+                PathOrigin::Asn(*asn)
+            }
+        };
+        origins.push(origin);
+    }
+    Ok(RibPrefix { network, origins })
+}
+
+fn read_prefix(body: &mut Cursor<'_>, ipv6: bool) -> Result<IpNetwork, ParseErrorKind> {
+    let length = body.u8()?;
+    let bytes = body.take((length as usize).div_ceil(8))?;
+    let address = if ipv6 {
+        let mut octets = [0u8; 16];
+        octets
+            .get_mut(..bytes.len())
+            .ok_or_else(|| invalid("prefix length out of range"))?
+            .copy_from_slice(bytes);
+        IpAddr::V6(Ipv6Addr::from(octets))
+    } else {
+        let mut octets = [0u8; 4];
+        octets
+            .get_mut(..bytes.len())
+            .ok_or_else(|| invalid("prefix length out of range"))?
+            .copy_from_slice(bytes);
+        IpAddr::V4(Ipv4Addr::from(octets))
+    };
+    IpNetwork::new_truncate(address, length).map_err(|_| ParseErrorKind::InvalidNetwork {
+        value: format!("{address}/{length}"),
+    })
+}
+
+/// Finds the origin at the end of the `AS_PATH` attribute, if there is one.
+///
+/// `TABLE_DUMP_V2` always encodes AS numbers in the path as four bytes.
+/// Confederation segments are ignored, since they never hold the origin.
+fn read_as_path_origin(attributes: &mut Cursor<'_>) -> Result<Option<PathOrigin>, ParseErrorKind> {
+    while !attributes.is_empty() {
+        let flags = attributes.u8()?;
+        let attribute_type = attributes.u8()?;
+        let length = if flags & ATTR_FLAG_EXTENDED_LENGTH != 0 {
+            attributes.u16()? as usize
+        } else {
+            attributes.u8()? as usize
+        };
+        let value = attributes.take(length)?;
+        if attribute_type != ATTR_AS_PATH {
+            continue;
+        }
+
+        let mut path = Cursor::new(value);
+        let mut origin = None;
+        while !path.is_empty() {
+            let segment_type = path.u8()?;
+            let count = path.u8()?;
+            let mut asns = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                asns.push(Asn::new(path.u32()?));
+            }
+            origin = match segment_type {
+                AS_SEQUENCE => asns.last().map(|asn| PathOrigin::Asn(*asn)).or(origin),
+                AS_SET if !asns.is_empty() => Some(PathOrigin::AsSet(asns)),
+                _ => origin,
+            };
+        }
+        return Ok(origin);
+    }
+    Ok(None)
+}
+
+fn invalid(message: &str) -> ParseErrorKind {
+    ParseErrorKind::InvalidMrtRecord {
+        message: message.to_string(),
+    }
+}
+
+/// A bounds-checked big-endian reader over a record body.
+struct Cursor<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ParseErrorKind> {
+        if n > self.bytes.len() {
+            return Err(invalid("record is truncated"));
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, ParseErrorKind> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ParseErrorKind> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, ParseErrorKind> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_path_origin() {
+        // AS_SEQUENCE [64500, 13335]
+        let attributes = [
+            0x40, 0x02, 0x0a, 0x02, 0x02, 0x00, 0x00, 0xfb, 0xf4, 0x00, 0x00, 0x34, 0x17,
+        ];
+        assert_eq!(
+            read_as_path_origin(&mut Cursor::new(&attributes)),
+            Ok(Some(PathOrigin::Asn(Asn::new(13335))))
+        );
+
+        // An empty AS_PATH has no origin.
+        let attributes = [0x40, 0x02, 0x00];
+        assert_eq!(read_as_path_origin(&mut Cursor::new(&attributes)), Ok(None));
+
+        // A segment that overruns the attribute.
+        let attributes = [0x40, 0x02, 0x04, 0x02, 0x02, 0x00, 0x00];
+        assert!(read_as_path_origin(&mut Cursor::new(&attributes)).is_err());
+    }
+
+    #[test]
+    fn test_select_origin() {
+        let prefix = RibPrefix {
+            network: "1.0.0.0/24".parse().unwrap(),
+            origins: vec![
+                PathOrigin::Asn(Asn::new(20)),
+                PathOrigin::Asn(Asn::new(10)),
+                PathOrigin::Asn(Asn::new(10)),
+            ],
+        };
+        assert_eq!(
+            prefix.select_origin(MultiOriginPolicy::First),
+            Ok(Asn::new(20))
+        );
+        assert_eq!(
+            prefix.select_origin(MultiOriginPolicy::MostCommon),
+            Ok(Asn::new(10))
+        );
+        assert_eq!(
+            prefix.select_origin(MultiOriginPolicy::Highest),
+            Ok(Asn::new(20))
+        );
+        assert_eq!(
+            prefix.select_origin(MultiOriginPolicy::Reject),
+            Err(ParseErrorKind::MultipleOrigins {
+                value: "20_10_10".to_string()
+            })
+        );
+
+        let prefix = RibPrefix {
+            network: "1.0.0.0/24".parse().unwrap(),
+            origins: vec![PathOrigin::Asn(Asn::new(10)), PathOrigin::Asn(Asn::new(10))],
+        };
+        assert_eq!(
+            prefix.select_origin(MultiOriginPolicy::Reject),
+            Ok(Asn::new(10))
+        );
+    }
+
+    fn header(record_type: u16, subtype: u16, length: u32) -> Vec<u8> {
+        let mut header = vec![0; 4];
+        header.extend_from_slice(&record_type.to_be_bytes());
+        header.extend_from_slice(&subtype.to_be_bytes());
+        header.extend_from_slice(&length.to_be_bytes());
+        header
+    }
+
+    #[test]
+    fn test_oversized_record_is_skipped() {
+        let length = MAX_RECORD_LEN + 1;
+        let data = io::Cursor::new(header(TABLE_DUMP_V2, RIB_IPV4_UNICAST, length))
+            .chain(io::repeat(0).take(u64::from(length)));
+        let mut reader = MrtReader::new(data);
+        match reader.read_prefix() {
+            Ok(Some(Err(ParseErrorKind::InvalidMrtRecord { message }))) => {
+                assert!(message.contains("exceeds the maximum"), "{message}");
+            }
+            other => panic!("Expected an oversized record error, got {other:?}"),
+        }
+        assert_eq!(reader.subtype(), RIB_IPV4_UNICAST);
+        assert!(reader.buf.is_empty());
+        assert!(reader.read_prefix().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_oversized_record_length_is_not_allocated() {
+        // A corrupt length with only a few bytes behind it.
+        let mut data = header(TABLE_DUMP_V2, RIB_IPV4_UNICAST, u32::MAX);
+        data.extend_from_slice(&[0; 16]);
+        let mut reader = MrtReader::new(data.as_slice());
+        let err = reader.read_prefix().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(reader.buf.capacity() < 1024);
+    }
+}
@@ -0,0 +1,288 @@
+//! Contains the `Network` type, an IP network block in CIDR notation.
+//!
+//! A [`Network`] builds on `core` alone, so that lookups can return one
+//! without the `std` feature. With `std`, it converts to and from
+//! [`ip_network::IpNetwork`] and compares equal to the same network.
+
+use crate::ParseErrorKind;
+use alloc::string::ToString;
+use core::fmt;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use core::str::FromStr;
+
+/// An IPv4 or IPv6 network block, such as `192.0.2.0/24`.
+///
+/// The network address never has bits set past the prefix length.
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::network::Network;
+///
+/// let network: Network = "192.0.2.0/24".parse().unwrap();
+/// assert_eq!(network.netmask(), 24);
+/// assert_eq!(network.last_address().to_string(), "192.0.2.255");
+/// assert!(network.contains("192.0.2.1".parse().unwrap()));
+/// assert_eq!(network.to_string(), "192.0.2.0/24");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Network {
+    address: IpAddr,
+    netmask: u8,
+}
+
+impl Network {
+    /// Creates a network from its address and prefix length.
+    ///
+    /// Returns `None` if the prefix length is longer than the address, or if
+    /// the address has bits set past the prefix length.
+    pub fn new(address: IpAddr, netmask: u8) -> Option<Self> {
+        let network = Self::new_truncate(address, netmask)?;
+        (network.address == address).then_some(network)
+    }
+
+    /// Creates the network of the given prefix length that contains
+    /// `address`, clearing any bits past the prefix length.
+    ///
+    /// Returns `None` if the prefix length is longer than the address.
+    pub fn new_truncate(address: IpAddr, netmask: u8) -> Option<Self> {
+        let (bits, width) = to_bits(address);
+        if netmask > width {
+            return None;
+        }
+        Some(Network {
+            address: from_bits(bits & !host_mask(width - netmask), address.is_ipv6()),
+            netmask,
+        })
+    }
+
+    /// Returns the first address of the network.
+    pub fn network_address(&self) -> IpAddr {
+        self.address
+    }
+
+    /// Returns the prefix length.
+    pub fn netmask(&self) -> u8 {
+        self.netmask
+    }
+
+    /// Returns the last address of the network, which is the broadcast
+    /// address of an IPv4 network.
+    pub fn last_address(&self) -> IpAddr {
+        let (bits, width) = to_bits(self.address);
+        from_bits(
+            bits | host_mask(width - self.netmask),
+            self.address.is_ipv6(),
+        )
+    }
+
+    /// Returns `true` if the network contains `ip`.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        ip.is_ipv6() == self.address.is_ipv6() && ip >= self.address && ip <= self.last_address()
+    }
+
+    /// Returns `true` if this is an IPv4 network.
+    pub fn is_ipv4(&self) -> bool {
+        self.address.is_ipv4()
+    }
+
+    /// Returns `true` if this is an IPv6 network.
+    pub fn is_ipv6(&self) -> bool {
+        self.address.is_ipv6()
+    }
+}
+
+/// Returns an address as an integer, and the number of bits in its family.
+pub(crate) fn to_bits(ip: IpAddr) -> (u128, u8) {
+    match ip {
+        IpAddr::V4(v4) => (u128::from(u32::from(v4)), 32),
+        IpAddr::V6(v6) => (u128::from(v6), 128),
+    }
+}
+
+/// Returns the address of the given family for an integer.
+pub(crate) fn from_bits(bits: u128, ipv6: bool) -> IpAddr {
+    match ipv6 {
+        false => IpAddr::V4(Ipv4Addr::from(bits as u32)),
+        true => IpAddr::V6(Ipv6Addr::from(bits)),
+    }
+}
+
+/// Returns a mask of the lowest `host_bits` bits.
+pub(crate) fn host_mask(host_bits: u8) -> u128 {
+    match host_bits {
+        0 => 0,
+        bits => u128::MAX >> (128 - u32::from(bits)),
+    }
+}
+
+impl From<IpAddr> for Network {
+    /// Creates the network holding only `ip`.
+    fn from(ip: IpAddr) -> Self {
+        Network {
+            address: ip,
+            netmask: to_bits(ip).1,
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.netmask)
+    }
+}
+
+impl FromStr for Network {
+    type Err = ParseErrorKind;
+
+    /// Parses a network in CIDR notation, such as `2001:db8::/32`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseErrorKind::InvalidNetwork {
+            value: s.to_string(),
+        };
+        let (address, netmask) = s.split_once('/').ok_or_else(invalid)?;
+        let address = address.parse().map_err(|_| invalid())?;
+        let netmask = netmask.parse().map_err(|_| invalid())?;
+        Network::new(address, netmask).ok_or_else(invalid)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Network {
+    /// Serializes the network as a string in CIDR notation for human-readable
+    /// formats, and as its address and prefix length otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            (self.address, self.netmask).serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Network {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        if deserializer.is_human_readable() {
+            let network = <alloc::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+            network.parse().map_err(D::Error::custom)
+        } else {
+            let (address, netmask) = <(IpAddr, u8)>::deserialize(deserializer)?;
+            Network::new(address, netmask).ok_or_else(|| {
+                D::Error::custom(format_args!("invalid network: {address}/{netmask}"))
+            })
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ip_network::IpNetwork> for Network {
+    fn from(network: ip_network::IpNetwork) -> Self {
+        Network {
+            address: network.network_address(),
+            netmask: network.netmask(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Network> for ip_network::IpNetwork {
+    fn from(network: Network) -> Self {
+        ip_network::IpNetwork::new(network.address, network.netmask)
+            .expect("a network has no bits set past its prefix length")
+    }
+}
+
+#[cfg(feature = "std")]
+impl PartialEq<ip_network::IpNetwork> for Network {
+    fn eq(&self, other: &ip_network::IpNetwork) -> bool {
+        self.address == other.network_address() && self.netmask == other.netmask()
+    }
+}
+
+#[cfg(feature = "std")]
+impl PartialEq<Network> for ip_network::IpNetwork {
+    fn eq(&self, other: &Network) -> bool {
+        other == self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(s: &str) -> Network {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let ip: IpAddr = "192.0.2.1".parse().unwrap();
+        assert_eq!(Network::new(ip, 32), Some(network("192.0.2.1/32")));
+        assert_eq!(Network::new(ip, 24), None);
+        assert_eq!(Network::new(ip, 33), None);
+        assert_eq!(Network::new_truncate(ip, 24), Some(network("192.0.2.0/24")));
+        assert_eq!(Network::new_truncate(ip, 0), Some(network("0.0.0.0/0")));
+
+        let ip: IpAddr = "2001:db8::1".parse().unwrap();
+        assert_eq!(
+            Network::new_truncate(ip, 32),
+            Some(network("2001:db8::/32"))
+        );
+        assert_eq!(Network::new(ip, 128), Some(Network::from(ip)));
+        assert_eq!(Network::new(ip, 129), None);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let v4 = network("10.0.0.0/8");
+        assert_eq!(v4.last_address().to_string(), "10.255.255.255");
+        assert!(v4.contains("10.1.2.3".parse().unwrap()));
+        assert!(!v4.contains("11.0.0.0".parse().unwrap()));
+        assert!(!v4.contains("::a00:1".parse().unwrap()));
+
+        let v6 = network("::/0");
+        assert_eq!(
+            v6.last_address().to_string(),
+            "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"
+        );
+        assert!(v6.contains("2001:db8::1".parse().unwrap()));
+        assert!(!v6.contains("1.1.1.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(network("2001:db8::/32").to_string(), "2001:db8::/32");
+        for s in [
+            "",
+            "192.0.2.0",
+            "192.0.2.0/",
+            "/24",
+            "192.0.2.1/24",
+            "192.0.2.0/33",
+        ] {
+            assert_eq!(
+                s.parse::<Network>(),
+                Err(ParseErrorKind::InvalidNetwork {
+                    value: s.to_string()
+                }),
+                "{s}"
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_ip_network_conversion() {
+        let expected: ip_network::IpNetwork = "192.0.2.0/24".parse().unwrap();
+        assert_eq!(network("192.0.2.0/24"), expected);
+        assert_eq!(
+            ip_network::IpNetwork::from(network("192.0.2.0/24")),
+            expected
+        );
+        assert_eq!(Network::from(expected), network("192.0.2.0/24"));
+        assert_ne!(network("192.0.2.0/25"), expected);
+    }
+}
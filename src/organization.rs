@@ -0,0 +1,277 @@
+//! Contains the logic for splitting organization names into their parts.
+//!
+//! The organization field of `iptoasn.com` data usually starts with the AS
+//! handle registered with the RIR, followed by free-text description, as in
+//! `BROADBANDIDC-AS-KR BROADBANDIDC` or `GOOGLE - Google LLC`. Some names end
+//! with the registry's country, either as a `-KR` suffix on the handle or as a
+//! trailing `, DE`.
+//!
+//! The split is heuristic. The first word is taken as the handle if it is
+//! written in upper case and either is the whole name, contains a hyphen or
+//! digit, or is followed by ` - `. Otherwise, as in `AGIL TELECOMUNICACOES
+//! LTDA` or `Not routed`, the whole name is the description.
+//!
+//! A map splits each distinct organization name once while it is built, so
+//! lookups return the parts without parsing.
+
+use crate::country::Country;
+use alloc::string::{String, ToString};
+
+/// An organization name split into its AS handle, description, and the
+/// country given by a registry suffix.
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::organization::Organization;
+///
+/// let org = Organization::parse("BROADBANDIDC-AS-KR BROADBANDIDC");
+/// assert_eq!(org.handle, Some("BROADBANDIDC-AS-KR"));
+/// assert_eq!(org.description, "BROADBANDIDC");
+/// assert_eq!(org.country.map(|country| country.code()), Some("KR"));
+///
+/// let org = Organization::parse("GOOGLE - Google LLC");
+/// assert_eq!(org.handle, Some("GOOGLE"));
+/// assert_eq!(org.name(), "Google LLC");
+///
+/// let org = Organization::parse("CLOUDFLARENET");
+/// assert_eq!(org.description, "");
+/// assert_eq!(org.name(), "CLOUDFLARENET");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub struct Organization<'a> {
+    /// The AS handle, such as `GOOGLE`, if the name starts with one.
+    pub handle: Option<&'a str>,
+    /// The free-text description after the handle, without any country
+    /// suffix. Empty if the name is only a handle.
+    pub description: &'a str,
+    /// The country given by a `-CC` suffix on the handle or a trailing `, CC`,
+    /// if it is an assigned ISO 3166-1 code.
+    pub country: Option<Country>,
+}
+
+impl<'a> Organization<'a> {
+    /// Splits an organization name into its parts.
+    pub fn parse(name: &'a str) -> Self {
+        Spans::new(name).resolve(name)
+    }
+
+    /// Returns a clean name for grouping: the description, or the handle if
+    /// there is no description.
+    pub fn name(&self) -> &'a str {
+        match self.handle {
+            Some(handle) if self.description.is_empty() => handle,
+            _ => self.description,
+        }
+    }
+}
+
+/// An owned [`Organization`], as carried by [`AsnInfo`](crate::AsnInfo).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct OrganizationInfo {
+    /// The AS handle, such as `GOOGLE`, if the name starts with one.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub handle: Option<String>,
+    /// The free-text description after the handle, without any country
+    /// suffix. Empty if the name is only a handle.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub description: String,
+    /// The country given by a `-CC` suffix on the handle or a trailing `, CC`,
+    /// if it is an assigned ISO 3166-1 code.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub country: Option<Country>,
+}
+
+impl OrganizationInfo {
+    /// Returns a clean name for grouping: the description, or the handle if
+    /// there is no description.
+    pub fn name(&self) -> &str {
+        self.as_organization().name()
+    }
+
+    /// Borrows the parts as an [`Organization`].
+    pub fn as_organization(&self) -> Organization<'_> {
+        Organization {
+            handle: self.handle.as_deref(),
+            description: &self.description,
+            country: self.country,
+        }
+    }
+}
+
+impl From<Organization<'_>> for OrganizationInfo {
+    fn from(org: Organization<'_>) -> Self {
+        Self {
+            handle: org.handle.map(str::to_string),
+            description: org.description.to_string(),
+            country: org.country,
+        }
+    }
+}
+
+/// The parts of an organization name as offsets into it, so they can be
+/// stored alongside the interned name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Spans {
+    /// The end of the handle at the start of the name, or zero if none.
+    handle_end: u32,
+    description_start: u32,
+    description_end: u32,
+    country: Option<Country>,
+}
+
+impl Spans {
+    pub(crate) fn new(name: &str) -> Self {
+        let trimmed = name.trim();
+        let offset = name.len() - name.trim_start().len();
+
+        let (body, mut country) = trimmed
+            .rsplit_once(", ")
+            .and_then(|(head, code)| Some((head.trim_end(), Some(country_code(code)?))))
+            .unwrap_or((trimmed, None));
+
+        let (first, rest) = body
+            .split_once(char::is_whitespace)
+            .map_or((body, ""), |(first, rest)| (first, rest.trim_start()));
+        let separated = rest.strip_prefix("- ").or_else(|| {
+            // A name ending in `-` after the handle has no description.
+            (rest == "-").then_some("")
+        });
+        let is_handle = is_handle_word(first)
+            && (rest.is_empty()
+                || separated.is_some()
+                || first.contains(|c: char| c == '-' || c.is_ascii_digit()));
+
+        let (handle, description) = if is_handle {
+            (first, separated.unwrap_or(rest).trim_start())
+        } else {
+            ("", body)
+        };
+
+        if country.is_none() {
+            country = handle
+                .rsplit_once('-')
+                .and_then(|(head, code)| (!head.is_empty() && code != "AS").then_some(code))
+                .and_then(country_code);
+        }
+
+        let description_start = if description.is_empty() {
+            offset + trimmed.len()
+        } else {
+            description.as_ptr() as usize - name.as_ptr() as usize
+        };
+        Self {
+            handle_end: if handle.is_empty() {
+                0
+            } else {
+                (offset + handle.len()) as u32
+            },
+            description_start: description_start as u32,
+            description_end: (description_start + description.len()) as u32,
+            country,
+        }
+    }
+
+    /// Returns the parts of `name`, which must be the name the spans were
+    /// created from.
+    pub(crate) fn resolve<'a>(&self, name: &'a str) -> Organization<'a> {
+        let start = name.len() - name.trim_start().len();
+        let handle_end = self.handle_end as usize;
+        Organization {
+            handle: (handle_end > 0).then(|| &name[start..handle_end]),
+            description: &name[self.description_start as usize..self.description_end as usize],
+            country: self.country,
+        }
+    }
+}
+
+/// Returns `true` if `word` could be an AS handle: upper-case letters,
+/// digits, and punctuation, with at least one letter.
+fn is_handle_word(word: &str) -> bool {
+    word.bytes().any(|b| b.is_ascii_uppercase())
+        && word
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b"-_.".contains(&b))
+}
+
+/// Returns the country for an upper-case two-letter code.
+fn country_code(code: &str) -> Option<Country> {
+    match *code.as_bytes() {
+        [a, b] if a.is_ascii_uppercase() && b.is_ascii_uppercase() => Country::from_bytes([a, b]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(name: &str) -> (Option<&str>, &str, Option<&str>) {
+        let org = Organization::parse(name);
+        (org.handle, org.description, org.country.map(|c| c.code()))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parts("BROADBANDIDC-AS-KR BROADBANDIDC"),
+            (Some("BROADBANDIDC-AS-KR"), "BROADBANDIDC", Some("KR"))
+        );
+        assert_eq!(
+            parts("GOOGLE - Google LLC"),
+            (Some("GOOGLE"), "Google LLC", None)
+        );
+        assert_eq!(
+            parts("HETZNER-AS, DE"),
+            (Some("HETZNER-AS"), "", Some("DE"))
+        );
+        assert_eq!(parts("OVH OVH SAS, FR"), (None, "OVH OVH SAS", Some("FR")));
+        assert_eq!(
+            parts("CHINANET-BACKBONE No.31,Jin-rong Street"),
+            (Some("CHINANET-BACKBONE"), "No.31,Jin-rong Street", None)
+        );
+        assert_eq!(parts("CLOUDFLARENET"), (Some("CLOUDFLARENET"), "", None));
+        assert_eq!(parts("AMAZON-02"), (Some("AMAZON-02"), "", None));
+        assert_eq!(parts("WCG-AS"), (Some("WCG-AS"), "", None));
+        assert_eq!(
+            parts("AGIL TELECOMUNICACOES LTDA"),
+            (None, "AGIL TELECOMUNICACOES LTDA", None)
+        );
+        assert_eq!(parts("Not routed"), (None, "Not routed", None));
+        assert_eq!(parts(""), (None, "", None));
+        assert_eq!(parts("EXAMPLE -"), (Some("EXAMPLE"), "", None));
+    }
+
+    #[test]
+    fn test_spans_with_surrounding_whitespace() {
+        let name = "  GOOGLE - Google LLC, US ";
+        assert_eq!(
+            Organization::parse(name),
+            Organization {
+                handle: Some("GOOGLE"),
+                description: "Google LLC",
+                country: Country::from_code("US"),
+            }
+        );
+        assert_eq!(Organization::parse("   "), Organization::default());
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(
+            Organization::parse("GOOGLE - Google LLC").name(),
+            "Google LLC"
+        );
+        assert_eq!(Organization::parse("HETZNER-AS, DE").name(), "HETZNER-AS");
+        assert_eq!(Organization::parse("Not routed").name(), "Not routed");
+    }
+}
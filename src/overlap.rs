@@ -0,0 +1,285 @@
+//! Contains the logic for detecting and resolving overlapping ranges.
+//!
+//! When overlap handling is enabled, ranges are not inserted into the lookup
+//! table as they are read. Instead, a [`RangeSet`] keeps the address space
+//! resolved so far as sorted, non-overlapping spans, so each new range can be
+//! checked against the lines it overlaps before it is merged in according to
+//! an [`OverlapWinner`]. The spans are converted to CIDRs once the build is
+//! complete.
+
+use crate::types::AsnRecord;
+use crate::OverlapWinner;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// A resolved, inclusive span of addresses, keyed by its start address.
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    end: u128,
+    record: AsnRecord,
+    /// The line the span was read from, with its full range, which may be
+    /// wider than the span if part of it lost to another line.
+    line_number: usize,
+    line_start: u128,
+    line_end: u128,
+}
+
+impl Span {
+    fn line_size(&self) -> u128 {
+        self.line_end - self.line_start
+    }
+}
+
+/// An earlier line that a newly inserted range overlaps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Conflict {
+    pub(crate) line_number: usize,
+    pub(crate) start_ip: IpAddr,
+    pub(crate) end_ip: IpAddr,
+    pub(crate) record: AsnRecord,
+}
+
+/// The address space covered so far, as non-overlapping spans per family.
+#[derive(Debug, Default)]
+pub(crate) struct RangeSet {
+    ipv4: BTreeMap<u128, Span>,
+    ipv6: BTreeMap<u128, Span>,
+}
+
+impl RangeSet {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a range, resolving any overlap with earlier lines in favor of
+    /// `winner`, and returns the earlier lines it overlaps in address order.
+    ///
+    /// `start` and `end` must be of the same family, with `start <= end`.
+    pub(crate) fn insert(
+        &mut self,
+        start: IpAddr,
+        end: IpAddr,
+        record: AsnRecord,
+        line_number: usize,
+        winner: OverlapWinner,
+    ) -> Vec<Conflict> {
+        let is_ipv4 = start.is_ipv4();
+        let (start, end) = (to_int(start), to_int(end));
+        let spans = if is_ipv4 {
+            &mut self.ipv4
+        } else {
+            &mut self.ipv6
+        };
+
+        // The spans are disjoint, so those overlapping the range are the ones
+        // starting at or before its end, back to the first ending before it.
+        let mut overlapping: Vec<(u128, Span)> = spans
+            .range(..=end)
+            .rev()
+            .take_while(|(_, span)| span.end >= start)
+            .map(|(&key, span)| (key, *span))
+            .collect();
+        overlapping.reverse();
+
+        let mut conflicts: Vec<Conflict> = Vec::new();
+        for (_, span) in &overlapping {
+            // Spans left over from one line are reported once.
+            if conflicts.iter().any(|c| c.line_number == span.line_number) {
+                continue;
+            }
+            conflicts.push(Conflict {
+                line_number: span.line_number,
+                start_ip: from_int(span.line_start, is_ipv4),
+                end_ip: from_int(span.line_end, is_ipv4),
+                record: span.record,
+            });
+        }
+
+        let new = Span {
+            end,
+            record,
+            line_number,
+            line_start: start,
+            line_end: end,
+        };
+        let mut cursor = Some(start);
+        for (key, span) in overlapping {
+            let new_wins = match winner {
+                OverlapWinner::First => false,
+                OverlapWinner::Last => true,
+                OverlapWinner::MostSpecific => new.line_size() <= span.line_size(),
+            };
+            if new_wins {
+                // Keep only the parts of the earlier span outside the range.
+                spans.remove(&key);
+                if key < start {
+                    spans.insert(
+                        key,
+                        Span {
+                            end: start - 1,
+                            ..span
+                        },
+                    );
+                }
+                if span.end > end {
+                    spans.insert(end + 1, span);
+                }
+            } else {
+                // The new range only fills the gap before the earlier span.
+                if let Some(from) = cursor.filter(|&from| from < key) {
+                    spans.insert(
+                        from,
+                        Span {
+                            end: key - 1,
+                            ..new
+                        },
+                    );
+                }
+                cursor = span.end.checked_add(1);
+            }
+        }
+        if let Some(from) = cursor.filter(|&from| from <= end) {
+            spans.insert(from, Span { end, ..new });
+        }
+
+        conflicts
+    }
+
+    /// Consumes the set, returning its spans as address ranges in order.
+    pub(crate) fn into_ranges(self) -> impl Iterator<Item = (IpAddr, IpAddr, AsnRecord)> {
+        let ipv4 = self
+            .ipv4
+            .into_iter()
+            .map(|(start, span)| (start, span, true));
+        let ipv6 = self
+            .ipv6
+            .into_iter()
+            .map(|(start, span)| (start, span, false));
+        ipv4.chain(ipv6).map(|(start, span, is_ipv4)| {
+            (
+                from_int(start, is_ipv4),
+                from_int(span.end, is_ipv4),
+                span.record,
+            )
+        })
+    }
+}
+
+fn to_int(ip: IpAddr) -> u128 {
+    match ip {
+        IpAddr::V4(ip) => u128::from(u32::from(ip)),
+        IpAddr::V6(ip) => u128::from(ip),
+    }
+}
+
+fn from_int(value: u128, is_ipv4: bool) -> IpAddr {
+    if is_ipv4 {
+        IpAddr::V4(Ipv4Addr::from(value as u32))
+    } else {
+        IpAddr::V6(Ipv6Addr::from(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asn::Asn;
+
+    fn record(asn: u32) -> AsnRecord {
+        AsnRecord {
+            asn: Asn::new(asn),
+            country_code: *b"ZZ",
+            organization_idx: 0,
+        }
+    }
+
+    fn ip(value: u32) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::from(value))
+    }
+
+    /// Inserts `(start, end, asn)` ranges, numbering them as lines from 1,
+    /// and returns the resolved `(start, end, asn)` spans.
+    fn resolve(ranges: &[(u32, u32, u32)], winner: OverlapWinner) -> Vec<(u32, u32, u32)> {
+        let mut set = RangeSet::new();
+        for (i, &(start, end, asn)) in ranges.iter().enumerate() {
+            set.insert(ip(start), ip(end), record(asn), i + 1, winner);
+        }
+        set.into_ranges()
+            .map(|(start, end, record)| {
+                (to_int(start) as u32, to_int(end) as u32, record.asn.get())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_reports_conflicts() {
+        let mut set = RangeSet::new();
+        assert!(set
+            .insert(ip(0), ip(99), record(1), 1, OverlapWinner::Last)
+            .is_empty());
+        assert!(set
+            .insert(ip(200), ip(299), record(2), 2, OverlapWinner::Last)
+            .is_empty());
+        let conflicts = set.insert(ip(50), ip(249), record(3), 3, OverlapWinner::Last);
+        assert_eq!(
+            conflicts.iter().map(|c| c.line_number).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            (conflicts[0].start_ip, conflicts[0].end_ip),
+            (ip(0), ip(99))
+        );
+        assert_eq!(conflicts[1].record, record(2));
+    }
+
+    #[test]
+    fn test_split_line_is_reported_once() {
+        let mut set = RangeSet::new();
+        set.insert(ip(0), ip(99), record(1), 1, OverlapWinner::Last);
+        set.insert(ip(40), ip(59), record(2), 2, OverlapWinner::Last);
+        let conflicts = set.insert(ip(0), ip(99), record(3), 3, OverlapWinner::First);
+        assert_eq!(
+            conflicts.iter().map(|c| c.line_number).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn test_winners() {
+        let ranges = [(0, 99, 1), (50, 59, 2), (90, 149, 3)];
+        assert_eq!(
+            resolve(&ranges, OverlapWinner::First),
+            vec![(0, 99, 1), (100, 149, 3)]
+        );
+        assert_eq!(
+            resolve(&ranges, OverlapWinner::Last),
+            vec![(0, 49, 1), (50, 59, 2), (60, 89, 1), (90, 149, 3)]
+        );
+        assert_eq!(
+            resolve(&ranges, OverlapWinner::MostSpecific),
+            vec![(0, 49, 1), (50, 59, 2), (60, 89, 1), (90, 149, 3)]
+        );
+
+        // A wider range inserted later does not override a narrower one.
+        let ranges = [(50, 59, 2), (0, 99, 1)];
+        assert_eq!(
+            resolve(&ranges, OverlapWinner::MostSpecific),
+            vec![(0, 49, 1), (50, 59, 2), (60, 99, 1)]
+        );
+        assert_eq!(resolve(&ranges, OverlapWinner::Last), vec![(0, 99, 1)]);
+    }
+
+    #[test]
+    fn test_whole_address_space() {
+        let mut set = RangeSet::new();
+        let (min, max) = (
+            IpAddr::from(Ipv6Addr::UNSPECIFIED),
+            IpAddr::from([0xffff; 8]),
+        );
+        set.insert(min, max, record(1), 1, OverlapWinner::First);
+        let conflicts = set.insert(max, max, record(2), 2, OverlapWinner::First);
+        assert_eq!(conflicts.len(), 1);
+        let ranges: Vec<_> = set.into_ranges().collect();
+        assert_eq!(ranges, vec![(min, max, record(1))]);
+    }
+}
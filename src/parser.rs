@@ -1,8 +1,10 @@
 //! Contains the logic for parsing a single line of the `iptoasn.com` TSV data.
 
+use crate::asn::Asn;
 use crate::ParseErrorKind;
-use std::net::IpAddr;
-use std::str::FromStr;
+use alloc::string::ToString;
+use core::net::{IpAddr, Ipv4Addr};
+use core::str::FromStr;
 
 /// A temporary struct holding the successfully parsed fields from a data line.
 #[derive(Debug, PartialEq, Eq)]
@@ -12,7 +14,7 @@ pub struct ParsedLine<'a> {
     /// The end of the IP address range.
     pub end_ip: IpAddr,
     /// The Autonomous System Number (ASN).
-    pub asn: u32,
+    pub asn: Asn,
     /// The two-letter ISO 3166-1 alpha-2 country code.
     pub country_code: [u8; 2],
     /// The common name of the organization that owns the IP range.
@@ -27,36 +29,49 @@ pub struct ParsedLine<'a> {
 ///
 /// Returns a `ParseErrorKind` if the line is malformed, such as having an
 /// incorrect number of columns, invalid IP addresses, or an invalid range.
-pub fn parse_line(line: &str) -> Result<ParsedLine, ParseErrorKind> {
+///
+/// Country codes must be two bytes long, and are normalized to upper case.
+/// `None`, `Unknown`, and empty codes are normalized to `ZZ`. Whether a code is
+/// assigned in ISO 3166-1 is only checked by a strict build.
+pub fn parse_line(line: &str) -> Result<ParsedLine<'_>, ParseErrorKind> {
     const EXPECTED_COLUMNS: usize = 5;
-    let parts: Vec<&str> = line.split('\t').collect();
-    if parts.len() != EXPECTED_COLUMNS {
+    // This runs for every line of a load, so the columns go into a fixed
+    // array rather than a `Vec` to keep it free of allocations. Extra columns
+    // are still counted for the error.
+    let mut parts = [""; EXPECTED_COLUMNS];
+    let mut found = 0;
+    for part in line.split('\t') {
+        if let Some(slot) = parts.get_mut(found) {
+            *slot = part;
+        }
+        found += 1;
+    }
+    if found != EXPECTED_COLUMNS {
         return Err(ParseErrorKind::IncorrectColumnCount {
             expected: EXPECTED_COLUMNS,
-            found: parts.len(),
+            found,
         });
     }
 
-    let start_ip_str = parts[0];
-    let end_ip_str = parts[1];
-    let asn_str = parts[2];
-    let country_code_str = parts[3];
-    let organization = parts[4];
+    let [start_ip_str, end_ip_str, asn_str, country_code_str, organization] = parts;
 
-    let start_ip =
-        IpAddr::from_str(start_ip_str).map_err(|_| ParseErrorKind::InvalidIpAddress {
-            field: "start_ip".to_string(),
-            value: start_ip_str.to_string(),
-        })?;
+    let start_ip = parse_ip(start_ip_str).ok_or_else(|| ParseErrorKind::InvalidIpAddress {
+        field: "start_ip".to_string(),
+        value: start_ip_str.to_string(),
+    })?;
 
-    let end_ip = IpAddr::from_str(end_ip_str).map_err(|_| ParseErrorKind::InvalidIpAddress {
+    let end_ip = parse_ip(end_ip_str).ok_or_else(|| ParseErrorKind::InvalidIpAddress {
         field: "end_ip".to_string(),
         value: end_ip_str.to_string(),
     })?;
 
-    let asn = u32::from_str(asn_str).map_err(|_| ParseErrorKind::InvalidAsnNumber {
-        value: asn_str.to_string(),
-    })?;
+    // Only the plain notation is used by the format.
+    let asn =
+        u32::from_str(asn_str)
+            .map(Asn::new)
+            .map_err(|_| ParseErrorKind::InvalidAsnNumber {
+                value: asn_str.to_string(),
+            })?;
 
     if start_ip.is_ipv4() != end_ip.is_ipv4() {
         return Err(ParseErrorKind::IpFamilyMismatch);
@@ -71,6 +86,7 @@ pub fn parse_line(line: &str) -> Result<ParsedLine, ParseErrorKind> {
         s if s.len() == 2 => {
             let mut bytes = [0u8; 2];
             bytes.copy_from_slice(s.as_bytes());
+            bytes.make_ascii_uppercase();
             bytes
         }
         _ => {
@@ -88,3 +104,76 @@ pub fn parse_line(line: &str) -> Result<ParsedLine, ParseErrorKind> {
         organization,
     })
 }
+
+/// Parses an IP address, reading dotted-quad IPv4 addresses straight from
+/// their bytes and deferring anything else to the standard library.
+fn parse_ip(s: &str) -> Option<IpAddr> {
+    match parse_ipv4(s.as_bytes()) {
+        Some(ip) => Some(IpAddr::V4(ip)),
+        None => IpAddr::from_str(s).ok(),
+    }
+}
+
+/// Parses a dotted-quad IPv4 address.
+///
+/// Returns `None` for anything it does not recognize, including forms the
+/// standard library rejects, such as octets with leading zeros.
+fn parse_ipv4(bytes: &[u8]) -> Option<Ipv4Addr> {
+    let mut octets = [0u8; 4];
+    let mut fields = bytes.split(|&b| b == b'.');
+    for octet in &mut octets {
+        let field = fields.next()?;
+        if field.is_empty() || field.len() > 3 || (field.len() > 1 && field[0] == b'0') {
+            return None;
+        }
+        let mut value: u16 = 0;
+        for &b in field {
+            if !b.is_ascii_digit() {
+                return None;
+            }
+            value = value * 10 + u16::from(b - b'0');
+        }
+        *octet = u8::try_from(value).ok()?;
+    }
+    if fields.next().is_some() {
+        return None;
+    }
+    Some(Ipv4Addr::from(octets))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ipv4_matches_std() {
+        let cases = [
+            "0.0.0.0",
+            "1.0.0.255",
+            "255.255.255.255",
+            "256.0.0.0",
+            "01.0.0.0",
+            "1.0.0",
+            "1.0.0.0.0",
+            "1..0.0",
+            "1.0.0.0 ",
+            "+1.0.0.0",
+            "1000.0.0.0",
+            "",
+        ];
+        for case in cases {
+            assert_eq!(
+                parse_ipv4(case.as_bytes()),
+                Ipv4Addr::from_str(case).ok(),
+                "{case:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_ip_falls_back_for_ipv6() {
+        assert_eq!(parse_ip("2001:db8::1"), "2001:db8::1".parse().ok());
+        assert_eq!(parse_ip("::ffff:1.0.0.0"), "::ffff:1.0.0.0".parse().ok());
+        assert_eq!(parse_ip("not-an-ip"), None);
+    }
+}
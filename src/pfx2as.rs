@@ -0,0 +1,131 @@
+//! Contains the logic for parsing CAIDA RouteViews prefix-to-AS (`pfx2as`) data.
+//!
+//! Each line holds a tab-separated prefix, prefix length, and origin:
+//! `PREFIX\tLENGTH\tORIGIN`. A prefix announced by more than one origin AS
+//! (MOAS) lists the origins separated by `_`, and an origin that is an AS set
+//! lists its members separated by `,`. How these are reduced to a single ASN is
+//! controlled by a [`MultiOriginPolicy`].
+
+use crate::asn::Asn;
+use crate::{MultiOriginPolicy, ParseErrorKind};
+use ip_network::IpNetwork;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// A successfully parsed line of `pfx2as` data.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Pfx2AsLine {
+    /// The announced network.
+    pub network: IpNetwork,
+    /// The origin Autonomous System Number (ASN) chosen by the policy.
+    pub asn: Asn,
+}
+
+/// Parses a single line of tab-separated `pfx2as` data.
+///
+/// The expected format is: `PREFIX\tLENGTH\tORIGIN`.
+///
+/// # Errors
+///
+/// Returns a `ParseErrorKind` if the line is malformed, such as having an
+/// incorrect number of columns, an invalid prefix, or an invalid origin. Lines
+/// with multiple origins or an AS set origin are rejected when the policy is
+/// [`MultiOriginPolicy::Reject`].
+pub fn parse_line(line: &str, policy: MultiOriginPolicy) -> Result<Pfx2AsLine, ParseErrorKind> {
+    const EXPECTED_COLUMNS: usize = 3;
+    let mut parts = [""; EXPECTED_COLUMNS];
+    let mut found = 0;
+    for part in line.split('\t') {
+        if let Some(slot) = parts.get_mut(found) {
+            *slot = part;
+        }
+        found += 1;
+    }
+    if found != EXPECTED_COLUMNS {
+        return Err(ParseErrorKind::IncorrectColumnCount {
+            expected: EXPECTED_COLUMNS,
+            found,
+        });
+    }
+
+    let [prefix_str, length_str, origin_str] = parts;
+    let invalid_network = || ParseErrorKind::InvalidNetwork {
+        value: format!("{prefix_str}/{length_str}"),
+    };
+    let prefix = IpAddr::from_str(prefix_str).map_err(|_| invalid_network())?;
+    let length = u8::from_str(length_str).map_err(|_| invalid_network())?;
+    let network = IpNetwork::new(prefix, length).map_err(|_| invalid_network())?;
+
+    let asn = select_origin(origin_str, policy)?;
+
+    Ok(Pfx2AsLine { network, asn })
+}
+
+/// Reduces a `pfx2as` origin field to a single ASN according to the policy.
+fn select_origin(value: &str, policy: MultiOriginPolicy) -> Result<Asn, ParseErrorKind> {
+    let invalid_asn = || ParseErrorKind::InvalidAsnNumber {
+        value: value.to_string(),
+    };
+
+    if policy == MultiOriginPolicy::Reject {
+        if value.contains('_') {
+            return Err(ParseErrorKind::MultipleOrigins {
+                value: value.to_string(),
+            });
+        }
+        if value.contains(',') {
+            return Err(ParseErrorKind::AsSetOrigin {
+                value: value.to_string(),
+            });
+        }
+    }
+
+    let origins = value
+        .split(['_', ','])
+        .map(|asn| u32::from_str(asn).map(Asn::new));
+    if origins.clone().any(|asn| asn.is_err()) {
+        return Err(invalid_asn());
+    }
+    policy.choose(origins.flatten()).ok_or_else(invalid_asn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_origin() {
+        assert_eq!(
+            select_origin("13335", MultiOriginPolicy::Reject),
+            Ok(Asn::new(13335))
+        );
+        assert_eq!(
+            select_origin("20_10", MultiOriginPolicy::First),
+            Ok(Asn::new(20))
+        );
+        assert_eq!(
+            select_origin("20_10,5", MultiOriginPolicy::Lowest),
+            Ok(Asn::new(5))
+        );
+        assert_eq!(
+            select_origin("20_10,5", MultiOriginPolicy::Highest),
+            Ok(Asn::new(20))
+        );
+        assert_eq!(
+            select_origin("20_10", MultiOriginPolicy::Reject),
+            Err(ParseErrorKind::MultipleOrigins {
+                value: "20_10".to_string()
+            })
+        );
+        assert_eq!(
+            select_origin("10,5", MultiOriginPolicy::Reject),
+            Err(ParseErrorKind::AsSetOrigin {
+                value: "10,5".to_string()
+            })
+        );
+        assert!(matches!(
+            select_origin("10__5", MultiOriginPolicy::First),
+            Err(ParseErrorKind::InvalidAsnNumber { .. })
+        ));
+    }
+}
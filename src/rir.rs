@@ -0,0 +1,502 @@
+//! Contains the logic for parsing RIR delegated-extended statistics.
+//!
+//! Each Regional Internet Registry publishes a `delegated-<rir>-extended-latest`
+//! file listing the IP and ASN blocks it manages. Records are pipe-separated:
+//! `REGISTRY|CC|TYPE|START|VALUE|DATE|STATUS[|OPAQUE-ID[|EXTENSIONS]]`. For
+//! `ipv4` records `VALUE` is a count of addresses, for `ipv6` records it is a
+//! prefix length, and for `asn` records it is a count of ASNs. Each file starts
+//! with a version line and one summary line per record type, which are skipped.
+//!
+//! The parsed records are collected into a [`RirDelegations`], which can be
+//! overlaid onto an [`IpAsnMap`](crate::IpAsnMap) with
+//! [`IpAsnMap::with_registry_data`](crate::IpAsnMap::with_registry_data).
+
+use crate::asn::Asn;
+use crate::range::{network_bounds, range_to_cidrs};
+use crate::{create_source_from_reader, Error, ParseErrorKind};
+use ip_network::IpNetwork;
+use ip_network_table::IpNetworkTable;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::Path;
+use std::str::FromStr;
+
+/// A Regional Internet Registry, or IANA itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+#[non_exhaustive]
+pub enum Registry {
+    /// The African Network Information Centre.
+    Afrinic,
+    /// The Asia-Pacific Network Information Centre.
+    Apnic,
+    /// The American Registry for Internet Numbers.
+    Arin,
+    /// The Internet Assigned Numbers Authority.
+    Iana,
+    /// The Latin America and Caribbean Network Information Centre.
+    Lacnic,
+    /// The Réseaux IP Européens Network Coordination Centre.
+    RipeNcc,
+}
+
+impl Registry {
+    /// Returns the registry's name as written in delegated statistics files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Registry::Afrinic => "afrinic",
+            Registry::Apnic => "apnic",
+            Registry::Arin => "arin",
+            Registry::Iana => "iana",
+            Registry::Lacnic => "lacnic",
+            Registry::RipeNcc => "ripencc",
+        }
+    }
+}
+
+impl FromStr for Registry {
+    type Err = ParseErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "afrinic" => Ok(Registry::Afrinic),
+            "apnic" => Ok(Registry::Apnic),
+            "arin" => Ok(Registry::Arin),
+            "iana" => Ok(Registry::Iana),
+            "lacnic" => Ok(Registry::Lacnic),
+            "ripencc" => Ok(Registry::RipeNcc),
+            _ => Err(invalid_field("registry", s)),
+        }
+    }
+}
+
+impl fmt::Display for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The allocation status of a delegated block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+#[non_exhaustive]
+pub enum AllocationStatus {
+    /// Allocated to a local registry or ISP for further assignment.
+    Allocated,
+    /// Assigned to an end user.
+    Assigned,
+    /// Held by the registry and not yet delegated.
+    Available,
+    /// Reserved by the registry.
+    Reserved,
+}
+
+impl AllocationStatus {
+    /// Returns the status as written in delegated statistics files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AllocationStatus::Allocated => "allocated",
+            AllocationStatus::Assigned => "assigned",
+            AllocationStatus::Available => "available",
+            AllocationStatus::Reserved => "reserved",
+        }
+    }
+}
+
+impl FromStr for AllocationStatus {
+    type Err = ParseErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allocated" => Ok(AllocationStatus::Allocated),
+            "assigned" => Ok(AllocationStatus::Assigned),
+            "available" => Ok(AllocationStatus::Available),
+            "reserved" => Ok(AllocationStatus::Reserved),
+            _ => Err(invalid_field("status", s)),
+        }
+    }
+}
+
+impl fmt::Display for AllocationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The registry data for a delegated IP or ASN block.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct RegistryInfo {
+    /// The registry that manages the block.
+    pub registry: Registry,
+    /// The two-letter ISO 3166-1 alpha-2 country code registered for the
+    /// block, or `ZZ` if there is none.
+    pub country_code: String,
+    /// The allocation status of the block.
+    pub status: AllocationStatus,
+}
+
+/// The block of resources covered by a delegation record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DelegatedResource {
+    /// An inclusive range of IP addresses.
+    Ip {
+        /// The first address in the range.
+        start: IpAddr,
+        /// The last address in the range.
+        end: IpAddr,
+    },
+    /// An inclusive range of Autonomous System Numbers.
+    Asn {
+        /// The first ASN in the range.
+        start: Asn,
+        /// The last ASN in the range.
+        end: Asn,
+    },
+}
+
+/// A successfully parsed record of a delegated-extended statistics file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Delegation {
+    /// The IP or ASN block covered by the record.
+    pub resource: DelegatedResource,
+    /// The registry data for the block.
+    pub info: RegistryInfo,
+}
+
+/// Returns `true` if the line is a version or summary line rather than a
+/// record.
+///
+/// The version line starts with the numeric format version, and summary lines
+/// have `summary` in their sixth column.
+pub fn is_header(line: &str) -> bool {
+    let mut fields = line.split('|');
+    let first = fields.next().unwrap_or_default();
+    first.starts_with(|c: char| c.is_ascii_digit()) || fields.nth(4) == Some("summary")
+}
+
+/// Parses a single record of a delegated-extended statistics file.
+///
+/// The expected format is: `REGISTRY|CC|TYPE|START|VALUE|DATE|STATUS`,
+/// optionally followed by an opaque ID and extensions. The older
+/// non-extended format, which has exactly seven columns, is accepted too.
+///
+/// # Errors
+///
+/// Returns a `ParseErrorKind` if the record has too few columns, or if any of
+/// its registry, country code, type, start, value, or status is invalid.
+pub fn parse_line(line: &str) -> Result<Delegation, ParseErrorKind> {
+    const EXPECTED_COLUMNS: usize = 7;
+    // Only the first seven columns are read; the opaque ID and any extensions
+    // after them are ignored.
+    let mut parts = [""; EXPECTED_COLUMNS];
+    let mut fields = line.split('|');
+    for (found, slot) in parts.iter_mut().enumerate() {
+        *slot = fields.next().ok_or(ParseErrorKind::IncorrectColumnCount {
+            expected: EXPECTED_COLUMNS,
+            found,
+        })?;
+    }
+
+    let registry = Registry::from_str(parts[0])?;
+    let country_code = match parts[1] {
+        "" => "ZZ".to_string(),
+        cc if cc.len() == 2 && cc.bytes().all(|b| b.is_ascii_uppercase()) => cc.to_string(),
+        cc => {
+            return Err(ParseErrorKind::InvalidCountryCode {
+                value: cc.to_string(),
+            })
+        }
+    };
+    let status = AllocationStatus::from_str(parts[6])?;
+
+    let (start, value) = (parts[3], parts[4]);
+    let resource = match parts[2] {
+        "ipv4" => {
+            let first = Ipv4Addr::from_str(start).map_err(|_| invalid_ip(start))?;
+            let count = u32::from_str(value)
+                .ok()
+                .filter(|count| *count > 0)
+                .ok_or_else(|| invalid_field("value", value))?;
+            let last = u32::from(first)
+                .checked_add(count - 1)
+                .ok_or_else(|| invalid_field("value", value))?;
+            DelegatedResource::Ip {
+                start: IpAddr::V4(first),
+                end: IpAddr::V4(Ipv4Addr::from(last)),
+            }
+        }
+        "ipv6" => {
+            let first = IpAddr::from_str(start)
+                .ok()
+                .filter(IpAddr::is_ipv6)
+                .ok_or_else(|| invalid_ip(start))?;
+            let network = u8::from_str(value)
+                .ok()
+                .and_then(|length| IpNetwork::new(first, length).ok())
+                .ok_or_else(|| ParseErrorKind::InvalidNetwork {
+                    value: format!("{start}/{value}"),
+                })?;
+            let (start, end) = network_bounds(network);
+            DelegatedResource::Ip { start, end }
+        }
+        "asn" => {
+            let first = u32::from_str(start).map_err(|_| ParseErrorKind::InvalidAsnNumber {
+                value: start.to_string(),
+            })?;
+            let last = u32::from_str(value)
+                .ok()
+                .filter(|count| *count > 0)
+                .and_then(|count| first.checked_add(count - 1))
+                .ok_or_else(|| invalid_field("value", value))?;
+            DelegatedResource::Asn {
+                start: Asn::new(first),
+                end: Asn::new(last),
+            }
+        }
+        other => return Err(invalid_field("type", other)),
+    };
+
+    Ok(Delegation {
+        resource,
+        info: RegistryInfo {
+            registry,
+            country_code,
+            status,
+        },
+    })
+}
+
+fn invalid_field(field: &str, value: &str) -> ParseErrorKind {
+    ParseErrorKind::InvalidField {
+        field: field.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn invalid_ip(value: &str) -> ParseErrorKind {
+    ParseErrorKind::InvalidIpAddress {
+        field: "start".to_string(),
+        value: value.to_string(),
+    }
+}
+
+/// Registry data for IP and ASN blocks, loaded from one or more RIR
+/// delegated-extended statistics files.
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::asn::Asn;
+/// use ip2asn::rir::{Registry, RirDelegations};
+///
+/// # fn main() -> Result<(), ip2asn::Error> {
+/// let data = "apnic|AU|ipv4|1.0.0.0|256|20110811|assigned|A91872ED\n\
+///             apnic|JP|asn|173|1|20020801|allocated|A91A7A6C";
+/// let mut delegations = RirDelegations::new();
+/// delegations.load(data.as_bytes())?;
+///
+/// let (network, info) = delegations.lookup("1.0.0.1".parse().unwrap()).unwrap();
+/// assert_eq!(network.to_string(), "1.0.0.0/24");
+/// assert_eq!(info.registry, Registry::Apnic);
+/// assert_eq!(info.country_code, "AU");
+/// assert_eq!(delegations.lookup_asn(Asn::new(173)).unwrap().country_code, "JP");
+/// # Ok(())
+/// # }
+/// ```
+pub struct RirDelegations {
+    networks: IpNetworkTable<RegistryInfo>,
+    /// Disjoint ASN blocks keyed by their first ASN, with their last ASN.
+    asns: BTreeMap<u32, (u32, RegistryInfo)>,
+}
+
+impl fmt::Debug for RirDelegations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (ipv4, ipv6) = self.networks.len();
+        f.debug_struct("RirDelegations")
+            .field("networks", &(ipv4 + ipv6))
+            .field("asns", &self.asns.len())
+            .finish()
+    }
+}
+
+impl Default for RirDelegations {
+    /// Creates a new, empty set of delegations.
+    fn default() -> Self {
+        Self {
+            networks: IpNetworkTable::new(),
+            asns: BTreeMap::new(),
+        }
+    }
+}
+
+impl RirDelegations {
+    /// Creates a new, empty set of delegations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads delegations from a file path.
+    ///
+    /// Gzip decompression is handled automatically by inspecting the file's
+    /// magic bytes.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut delegations = Self::new();
+        delegations.load(BufReader::new(File::open(path.as_ref())?))?;
+        Ok(delegations)
+    }
+
+    /// Adds the delegations read from a source implementing `BufRead`.
+    ///
+    /// This can be called once per registry to combine the files of all five
+    /// RIRs. Where blocks overlap, the most specific one wins for IP lookups,
+    /// and the one loaded last wins for ASN lookups. Gzip decompression is
+    /// handled automatically by inspecting the stream's magic bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::Parse` on the first malformed record, or an
+    /// `Error::Io` if the source cannot be read.
+    pub fn load(&mut self, reader: impl BufRead + Send) -> Result<(), Error> {
+        let reader = create_source_from_reader(reader)?;
+        for (i, line_result) in reader.lines().enumerate() {
+            let line = line_result?;
+            if line.is_empty() || line.starts_with('#') || is_header(&line) {
+                continue;
+            }
+            let delegation = parse_line(&line).map_err(|kind| Error::Parse {
+                line_number: i + 1,
+                line_content: line.clone(),
+                kind,
+            })?;
+            match delegation.resource {
+                DelegatedResource::Ip { start, end } => {
+                    for cidr in range_to_cidrs(start, end) {
+                        self.networks.insert(cidr, delegation.info.clone());
+                    }
+                }
+                DelegatedResource::Asn { start, end } => {
+                    self.insert_asns(start.get(), end.get(), delegation.info);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Inserts an ASN block, trimming the parts of earlier blocks it covers so
+    /// that the blocks stay disjoint.
+    fn insert_asns(&mut self, start: u32, end: u32, info: RegistryInfo) {
+        let overlapping: Vec<u32> = self
+            .asns
+            .range(..=end)
+            .rev()
+            .take_while(|(_, (last, _))| *last >= start)
+            .map(|(first, _)| *first)
+            .collect();
+        for first in overlapping {
+            let (last, existing) = self.asns.remove(&first).expect("overlapping block");
+            if first < start {
+                self.asns.insert(first, (start - 1, existing.clone()));
+            }
+            if last > end {
+                self.asns.insert(end + 1, (last, existing));
+            }
+        }
+        self.asns.insert(start, (end, info));
+    }
+
+    /// Returns `true` if no delegations have been loaded.
+    pub fn is_empty(&self) -> bool {
+        self.networks.is_empty() && self.asns.is_empty()
+    }
+
+    /// Looks up the delegated block containing an IP address.
+    ///
+    /// Returns the most specific CIDR block of the delegation along with its
+    /// registry data.
+    pub fn lookup(&self, ip: IpAddr) -> Option<(IpNetwork, &RegistryInfo)> {
+        self.networks.longest_match(ip)
+    }
+
+    /// Looks up the delegated block containing an ASN.
+    pub fn lookup_asn(&self, asn: Asn) -> Option<&RegistryInfo> {
+        let asn = asn.get();
+        self.asns
+            .range(..=asn)
+            .next_back()
+            .filter(|(_, (last, _))| asn <= *last)
+            .map(|(_, (_, info))| info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_header() {
+        assert!(is_header("2|apnic|20240101|6|19830613|20231231|+1000"));
+        assert!(is_header("2.3|arin|1704171600|3|19700101|20240101|-0500"));
+        assert!(is_header("apnic|*|ipv4|*|3|summary"));
+        assert!(!is_header(
+            "apnic|AU|ipv4|1.0.0.0|256|20110811|assigned|A91872ED"
+        ));
+    }
+
+    #[test]
+    fn test_lookup_asn_prefers_later_loads() {
+        let mut delegations = RirDelegations::new();
+        delegations
+            .load("arin|US|asn|1|10|19840101|allocated".as_bytes())
+            .unwrap();
+        delegations
+            .load("iana|ZZ|asn|5|1||reserved".as_bytes())
+            .unwrap();
+        assert_eq!(
+            delegations.lookup_asn(Asn::new(4)).unwrap().registry,
+            Registry::Arin
+        );
+        assert_eq!(
+            delegations.lookup_asn(Asn::new(5)).unwrap().registry,
+            Registry::Iana
+        );
+        assert_eq!(
+            delegations.lookup_asn(Asn::new(10)).unwrap().registry,
+            Registry::Arin
+        );
+        assert!(delegations.lookup_asn(Asn::new(11)).is_none());
+        assert!(delegations.lookup_asn(Asn::new(0)).is_none());
+    }
+
+    #[test]
+    fn test_lookup_asn_prefers_later_loads_with_lower_starts() {
+        let mut delegations = RirDelegations::new();
+        delegations
+            .load("arin|US|asn|5|1|19840101|allocated".as_bytes())
+            .unwrap();
+        delegations
+            .load("iana|ZZ|asn|1|10||reserved".as_bytes())
+            .unwrap();
+        for asn in 1..=10 {
+            assert_eq!(
+                delegations.lookup_asn(Asn::new(asn)).unwrap().registry,
+                Registry::Iana,
+                "AS{asn}"
+            );
+        }
+        assert_eq!(delegations.asns.len(), 1);
+    }
+}
@@ -0,0 +1,320 @@
+//! Contains the logic for RPKI route origin validation.
+//!
+//! A relying party such as Routinator or rpki-client exports the Validated ROA
+//! Payloads (VRPs) it has accepted as either CSV, with the header
+//! `ASN,IP Prefix,Max Length,Trust Anchor`, or JSON, with the VRPs in a
+//! top-level `roas` array. Both exports are loaded into a [`VrpTable`], which
+//! validates a route's origin with the semantics of RFC 6811.
+
+use crate::asn::Asn;
+use crate::{create_source_from_reader, Error, ParseErrorKind};
+use ip_network::IpNetwork;
+use ip_network_table::IpNetworkTable;
+use serde::Deserialize;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+/// The RPKI validation state of a route, as defined by RFC 6811.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ValidationState {
+    /// A VRP covering the route matches its origin ASN and prefix length.
+    Valid,
+    /// At least one VRP covers the route, but none of them match it.
+    Invalid,
+    /// No VRP covers the route.
+    NotFound,
+}
+
+impl fmt::Display for ValidationState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationState::Valid => write!(f, "valid"),
+            ValidationState::Invalid => write!(f, "invalid"),
+            ValidationState::NotFound => write!(f, "not-found"),
+        }
+    }
+}
+
+/// A Validated ROA Payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vrp {
+    /// The prefix authorized by the ROA.
+    pub network: IpNetwork,
+    /// The longest prefix length the origin may announce within `network`.
+    pub max_length: u8,
+    /// The origin Autonomous System Number (ASN) authorized by the ROA.
+    pub asn: Asn,
+}
+
+impl Vrp {
+    fn new(asn: &str, prefix: &str, max_length: &str) -> Result<Self, ParseErrorKind> {
+        let asn = Asn::from_str(asn)?;
+        let network = IpNetwork::from_str(prefix).map_err(|_| ParseErrorKind::InvalidNetwork {
+            value: prefix.to_string(),
+        })?;
+        let max_bits = if network.is_ipv4() { 32 } else { 128 };
+        let max_length = u8::from_str(max_length)
+            .ok()
+            .filter(|length| (network.netmask()..=max_bits).contains(length))
+            .ok_or_else(|| ParseErrorKind::InvalidField {
+                field: "max_length".to_string(),
+                value: max_length.to_string(),
+            })?;
+        Ok(Self {
+            network,
+            max_length,
+            asn,
+        })
+    }
+}
+
+/// Parses a single line of a CSV VRP export.
+///
+/// The expected format is: `ASN,PREFIX,MAX_LENGTH`, optionally followed by
+/// further columns such as the trust anchor and expiry, which are ignored.
+///
+/// # Errors
+///
+/// Returns a `ParseErrorKind` if the line has fewer than three columns, the
+/// ASN or prefix is invalid, or the maximum length is outside the range
+/// allowed for the prefix.
+pub fn parse_csv_line(line: &str) -> Result<Vrp, ParseErrorKind> {
+    const EXPECTED_COLUMNS: usize = 3;
+    // The trust anchor and any later columns are ignored.
+    let mut parts = [""; EXPECTED_COLUMNS];
+    let mut fields = line.split(',');
+    for (found, slot) in parts.iter_mut().enumerate() {
+        *slot = fields.next().ok_or(ParseErrorKind::IncorrectColumnCount {
+            expected: EXPECTED_COLUMNS,
+            found,
+        })?;
+    }
+    let [asn, prefix, max_length] = parts;
+    Vrp::new(asn, prefix, max_length)
+}
+
+#[derive(Deserialize)]
+struct JsonExport {
+    roas: Vec<JsonRoa>,
+}
+
+#[derive(Deserialize)]
+struct JsonRoa {
+    asn: JsonAsn,
+    prefix: String,
+    #[serde(rename = "maxLength")]
+    max_length: u8,
+}
+
+/// Routinator writes ASNs as `"AS13335"`, while rpki-client writes `13335`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonAsn {
+    Number(u32),
+    Text(String),
+}
+
+/// A set of Validated ROA Payloads, indexed for route origin validation.
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::asn::Asn;
+/// use ip2asn::rpki::{ValidationState, VrpTable};
+///
+/// # fn main() -> Result<(), ip2asn::Error> {
+/// let data = "ASN,IP Prefix,Max Length,Trust Anchor\n\
+///             AS13335,1.0.0.0/24,24,apnic";
+/// let mut vrps = VrpTable::new();
+/// vrps.load(data.as_bytes())?;
+///
+/// let route = "1.0.0.0/24".parse().unwrap();
+/// assert_eq!(vrps.validate(route, Asn::new(13335)), ValidationState::Valid);
+/// assert_eq!(vrps.validate(route, Asn::new(64496)), ValidationState::Invalid);
+/// # Ok(())
+/// # }
+/// ```
+pub struct VrpTable {
+    table: IpNetworkTable<Vec<(Asn, u8)>>,
+    len: usize,
+}
+
+impl fmt::Debug for VrpTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VrpTable").field("len", &self.len).finish()
+    }
+}
+
+impl Default for VrpTable {
+    /// Creates a new, empty table.
+    fn default() -> Self {
+        Self {
+            table: IpNetworkTable::new(),
+            len: 0,
+        }
+    }
+}
+
+impl VrpTable {
+    /// Creates a new, empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads VRPs from a CSV or JSON export at a file path.
+    ///
+    /// See [`load`](#method.load) for how the format is detected.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut vrps = Self::new();
+        vrps.load(BufReader::new(File::open(path.as_ref())?))?;
+        Ok(vrps)
+    }
+
+    /// Adds the VRPs read from a source implementing `BufRead`.
+    ///
+    /// Sources starting with `{` are read as a JSON export, and any other
+    /// source as a CSV export. Gzip decompression is handled automatically by
+    /// inspecting the stream's magic bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::Json` if a JSON export is malformed, an
+    /// `Error::Parse` on the first invalid VRP, or an `Error::Io` if the source
+    /// cannot be read. For JSON exports, `line_number` is the 1-based position
+    /// of the VRP in the `roas` array.
+    pub fn load(&mut self, reader: impl BufRead + Send) -> Result<(), Error> {
+        let mut reader = create_source_from_reader(reader)?;
+        let is_json = loop {
+            let buf = reader.fill_buf()?;
+            match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+                Some(i) => break buf[i] == b'{',
+                None if buf.is_empty() => return Ok(()),
+                None => {
+                    let len = buf.len();
+                    reader.consume(len);
+                }
+            }
+        };
+        if is_json {
+            self.load_json(reader)
+        } else {
+            self.load_csv(reader)
+        }
+    }
+
+    fn load_csv(&mut self, reader: impl BufRead) -> Result<(), Error> {
+        for (i, line_result) in reader.lines().enumerate() {
+            let line = line_result?;
+            if line.is_empty() || line.starts_with('#') || line.starts_with("ASN,") {
+                continue;
+            }
+            let vrp = parse_csv_line(&line).map_err(|kind| Error::Parse {
+                line_number: i + 1,
+                line_content: line.clone(),
+                kind,
+            })?;
+            self.insert(vrp);
+        }
+        Ok(())
+    }
+
+    fn load_json(&mut self, reader: impl BufRead) -> Result<(), Error> {
+        let export: JsonExport = serde_json::from_reader(reader)?;
+        for (i, roa) in export.roas.into_iter().enumerate() {
+            let asn = match roa.asn {
+                JsonAsn::Number(asn) => asn.to_string(),
+                JsonAsn::Text(asn) => asn,
+            };
+            let max_length = roa.max_length.to_string();
+            let vrp = Vrp::new(&asn, &roa.prefix, &max_length).map_err(|kind| Error::Parse {
+                line_number: i + 1,
+                line_content: format!("{asn},{},{max_length}", roa.prefix),
+                kind,
+            })?;
+            self.insert(vrp);
+        }
+        Ok(())
+    }
+
+    /// Adds a single VRP to the table.
+    pub fn insert(&mut self, vrp: Vrp) {
+        let entry = (vrp.asn, vrp.max_length);
+        match self.table.exact_match_mut(vrp.network) {
+            Some(entries) if entries.contains(&entry) => return,
+            Some(entries) => entries.push(entry),
+            None => {
+                self.table.insert(vrp.network, vec![entry]);
+            }
+        }
+        self.len += 1;
+    }
+
+    /// Returns the number of distinct VRPs in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the table contains no VRPs.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Validates the origin of a route announcing `route`.
+    ///
+    /// Following RFC 6811, a VRP covers the route if its prefix contains the
+    /// route's prefix, and matches it if it also has the same origin ASN and a
+    /// maximum length no shorter than the route's prefix length. VRPs for
+    /// `AS0` cover routes but never match them.
+    pub fn validate(&self, route: IpNetwork, origin: Asn) -> ValidationState {
+        let mut state = ValidationState::NotFound;
+        let covering = self
+            .table
+            .matches(route.network_address())
+            .filter(|(network, _)| network.netmask() <= route.netmask());
+        for (_, entries) in covering {
+            for (asn, max_length) in entries {
+                if *asn != 0 && *asn == origin && route.netmask() <= *max_length {
+                    return ValidationState::Valid;
+                }
+                state = ValidationState::Invalid;
+            }
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_line_asn_notations() {
+        for asn in ["AS13335", "as13335", "13335"] {
+            let vrp = parse_csv_line(&format!("{asn},1.0.0.0/24,24")).unwrap();
+            assert_eq!(vrp.asn, 13335, "{asn}");
+        }
+        let vrp = parse_csv_line("AS1.10,1.0.0.0/24,24").unwrap();
+        assert_eq!(vrp.asn, 65546);
+        assert!(matches!(
+            parse_csv_line("AS,1.0.0.0/24,24"),
+            Err(ParseErrorKind::InvalidAsnNumber { .. })
+        ));
+    }
+
+    #[test]
+    fn test_insert_skips_duplicates() {
+        let vrp = parse_csv_line("AS13335,1.0.0.0/24,24,apnic").unwrap();
+        let mut vrps = VrpTable::new();
+        vrps.insert(vrp);
+        vrps.insert(vrp);
+        vrps.insert(Vrp {
+            asn: Asn::new(0),
+            ..vrp
+        });
+        assert_eq!(vrps.len(), 2);
+    }
+}
@@ -0,0 +1,87 @@
+//! Serialization of an `IpAsnMap` as a snapshot of its networks and
+//! organizations.
+//!
+//! A snapshot is a struct of two fields: `organizations`, the organization
+//! names, and `networks`, the CIDR blocks of the lookup table. Each network is
+//! a tuple of the block, its ASN, its country code, and the index of its
+//! organization name.
+
+use crate::asn::Asn;
+use crate::network::Network;
+use crate::types::AsnRecord;
+use crate::IpAsnMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::de::Error as _;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A network of a snapshot, with its country code held as `C`.
+#[derive(Serialize, Deserialize)]
+struct SnapshotNetwork<C>(Network, Asn, C, u32);
+
+/// The networks of a map, serialized without collecting them first.
+struct Networks<'a>(&'a IpAsnMap);
+
+impl Serialize for Networks<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.table.iter().map(|(network, record)| {
+            SnapshotNetwork(
+                network,
+                record.asn,
+                core::str::from_utf8(&record.country_code).unwrap_or_default(),
+                record.organization_idx,
+            )
+        }))
+    }
+}
+
+impl Serialize for IpAsnMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut snapshot = serializer.serialize_struct("IpAsnMap", 2)?;
+        snapshot.serialize_field("organizations", &self.organizations)?;
+        snapshot.serialize_field("networks", &Networks(self))?;
+        snapshot.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "IpAsnMap")]
+struct Snapshot {
+    organizations: Vec<String>,
+    networks: Vec<SnapshotNetwork<String>>,
+}
+
+impl<'de> Deserialize<'de> for IpAsnMap {
+    /// Loads a map from a snapshot, checking that every network has a
+    /// two-letter country code and a valid organization index.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let Snapshot {
+            organizations,
+            networks,
+        } = Snapshot::deserialize(deserializer)?;
+        let table = networks
+            .into_iter()
+            .map(
+                |SnapshotNetwork(network, asn, country_code, organization_idx)| {
+                    let country_code =
+                        <[u8; 2]>::try_from(country_code.as_bytes()).map_err(|_| {
+                            D::Error::custom(format_args!("invalid country code: {country_code}"))
+                        })?;
+                    if organization_idx as usize >= organizations.len() {
+                        return Err(D::Error::custom(format_args!(
+                            "organization index out of range: {organization_idx}"
+                        )));
+                    }
+                    let record = AsnRecord {
+                        asn,
+                        country_code,
+                        organization_idx,
+                    };
+                    Ok((network, record))
+                },
+            )
+            .collect::<Result<_, _>>()?;
+        Ok(IpAsnMap::from_table(table, organizations))
+    }
+}
@@ -0,0 +1,735 @@
+//! Contains the special-purpose address registry of RFC 6890.
+//!
+//! The ranges are those of the IANA IPv4 and IPv6 Special-Purpose Address
+//! Registries, together with the multicast ranges, which have registries of
+//! their own. They let a caller tell an address that is missing from a
+//! dataset apart from one that is never routed, such as private, loopback, or
+//! documentation space.
+
+use ip_network::IpNetwork;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// The category of a special-purpose address range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[non_exhaustive]
+pub enum SpecialPurpose {
+    /// `0.0.0.0/8`, addresses of this host on this network (RFC 791).
+    ThisNetwork,
+    /// The unspecified IPv6 address `::` (RFC 4291).
+    Unspecified,
+    /// Private-use IPv4 space, such as `10.0.0.0/8` (RFC 1918).
+    Private,
+    /// `100.64.0.0/10`, shared by carrier-grade NAT (RFC 6598).
+    SharedAddress,
+    /// Loopback addresses, `127.0.0.0/8` and `::1` (RFC 1122, RFC 4291).
+    Loopback,
+    /// Link-local addresses, `169.254.0.0/16` and `fe80::/10` (RFC 3927,
+    /// RFC 4291).
+    LinkLocal,
+    /// Unique local IPv6 addresses, `fc00::/7` (RFC 4193).
+    UniqueLocal,
+    /// Space reserved for IETF protocol assignments, such as `192.0.0.0/24`
+    /// and `2001::/23` (RFC 6890).
+    IetfProtocolAssignments,
+    /// Documentation ranges, such as `192.0.2.0/24` and `2001:db8::/32`
+    /// (RFC 5737, RFC 3849, RFC 9637).
+    Documentation,
+    /// Benchmarking ranges, `198.18.0.0/15` and `2001:2::/48` (RFC 2544,
+    /// RFC 5180).
+    Benchmarking,
+    /// The IPv4 Service Continuity Prefix, `192.0.0.0/29` (RFC 7335).
+    ServiceContinuity,
+    /// Dummy addresses for hosts without another address, `192.0.0.8/32` and
+    /// `100:0:0:1::/64` (RFC 7600, RFC 9780).
+    Dummy,
+    /// Anycast addresses of protocol services, such as Port Control Protocol
+    /// servers at `192.0.0.9` and `2001:1::1` (RFC 7723, RFC 8155, RFC 9665).
+    ProtocolAnycast,
+    /// AS112 DNS sink servers (RFC 7534, RFC 7535).
+    As112,
+    /// Automatic Multicast Tunneling relays (RFC 7450).
+    Amt,
+    /// The deprecated 6to4 relay anycast prefix, `192.88.99.0/24` (RFC 7526).
+    SixToFourRelay,
+    /// IPv4-mapped IPv6 addresses, `::ffff:0:0/96` (RFC 4291).
+    Ipv4Mapped,
+    /// IPv4/IPv6 translation prefixes, `64:ff9b::/96` and `64:ff9b:1::/48`,
+    /// and the NAT64/DNS64 discovery addresses `192.0.0.170/31` (RFC 6052,
+    /// RFC 8215, RFC 8880).
+    Ipv4Ipv6Translation,
+    /// The discard-only prefix `100::/64` (RFC 6666).
+    DiscardOnly,
+    /// Teredo tunneling, `2001::/32` (RFC 4380).
+    Teredo,
+    /// 6to4 tunneling, `2002::/16` (RFC 3056).
+    SixToFour,
+    /// Overlay routable cryptographic hash identifiers, `2001:20::/28` and
+    /// the deprecated `2001:10::/28` (RFC 7343, RFC 4843).
+    Orchid,
+    /// Drone Remote ID entity tags, `2001:30::/28` (RFC 9374).
+    DroneRemoteId,
+    /// SRv6 segment identifiers, `5f00::/16` (RFC 9602).
+    Srv6Sid,
+    /// Multicast addresses, `224.0.0.0/4` and `ff00::/8` (RFC 5771,
+    /// RFC 4291).
+    Multicast,
+    /// The limited broadcast address `255.255.255.255` (RFC 919).
+    Broadcast,
+    /// `240.0.0.0/4`, reserved for future use (RFC 1112).
+    Reserved,
+}
+
+impl SpecialPurpose {
+    /// Returns the name of the category, such as `"shared_address"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpecialPurpose::ThisNetwork => "this_network",
+            SpecialPurpose::Unspecified => "unspecified",
+            SpecialPurpose::Private => "private",
+            SpecialPurpose::SharedAddress => "shared_address",
+            SpecialPurpose::Loopback => "loopback",
+            SpecialPurpose::LinkLocal => "link_local",
+            SpecialPurpose::UniqueLocal => "unique_local",
+            SpecialPurpose::IetfProtocolAssignments => "ietf_protocol_assignments",
+            SpecialPurpose::Documentation => "documentation",
+            SpecialPurpose::Benchmarking => "benchmarking",
+            SpecialPurpose::ServiceContinuity => "service_continuity",
+            SpecialPurpose::Dummy => "dummy",
+            SpecialPurpose::ProtocolAnycast => "protocol_anycast",
+            SpecialPurpose::As112 => "as112",
+            SpecialPurpose::Amt => "amt",
+            SpecialPurpose::SixToFourRelay => "six_to_four_relay",
+            SpecialPurpose::Ipv4Mapped => "ipv4_mapped",
+            SpecialPurpose::Ipv4Ipv6Translation => "ipv4_ipv6_translation",
+            SpecialPurpose::DiscardOnly => "discard_only",
+            SpecialPurpose::Teredo => "teredo",
+            SpecialPurpose::SixToFour => "six_to_four",
+            SpecialPurpose::Orchid => "orchid",
+            SpecialPurpose::DroneRemoteId => "drone_remote_id",
+            SpecialPurpose::Srv6Sid => "srv6_sid",
+            SpecialPurpose::Multicast => "multicast",
+            SpecialPurpose::Broadcast => "broadcast",
+            SpecialPurpose::Reserved => "reserved",
+        }
+    }
+}
+
+impl fmt::Display for SpecialPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An entry of the special-purpose address registry.
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::special::{self, SpecialPurpose};
+///
+/// let range = special::classify("100.64.1.2".parse().unwrap()).unwrap();
+/// assert_eq!(range.purpose(), SpecialPurpose::SharedAddress);
+/// assert_eq!(range.network().to_string(), "100.64.0.0/10");
+/// assert_eq!(range.rfc(), "RFC 6598");
+///
+/// assert!(special::classify("1.1.1.1".parse().unwrap()).is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpecialRange {
+    address: IpAddr,
+    prefix: u8,
+    purpose: SpecialPurpose,
+    name: &'static str,
+    rfc: &'static str,
+    globally_reachable: bool,
+}
+
+impl SpecialRange {
+    /// Returns the network of the range.
+    pub fn network(&self) -> IpNetwork {
+        IpNetwork::new(self.address, self.prefix).expect("valid special-purpose network")
+    }
+
+    /// Returns the category of the range.
+    pub fn purpose(&self) -> SpecialPurpose {
+        self.purpose
+    }
+
+    /// Returns the name of the range in the IANA registry, such as
+    /// `"Shared Address Space"`.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the RFC that reserved the range, such as `"RFC 6598"`.
+    pub fn rfc(&self) -> &'static str {
+        self.rfc
+    }
+
+    /// Returns `true` if the registry marks the range as globally reachable,
+    /// so that it can be announced in the global routing table.
+    ///
+    /// Ranges the registry marks as not applicable count as reachable if
+    /// they are still announced, such as 6to4 and Teredo, and not if they
+    /// are deprecated, such as the 6to4 relay anycast range.
+    pub fn is_globally_reachable(&self) -> bool {
+        self.globally_reachable
+    }
+
+    /// Returns `true` if the range contains `ip`.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.address, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(ip) & mask == u32::from(network)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(ip) & mask == u128::from(network)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Returns the most specific special-purpose range containing `ip`, or `None`
+/// if `ip` is an ordinary unicast address.
+///
+/// Nested ranges, such as Teredo's `2001::/32` within `2001::/23`, resolve to
+/// the inner one.
+pub fn classify(ip: IpAddr) -> Option<&'static SpecialRange> {
+    RANGES
+        .iter()
+        .filter(|range| range.contains(ip))
+        .max_by_key(|range| range.prefix)
+}
+
+/// Returns every range in the registry.
+pub fn ranges() -> &'static [SpecialRange] {
+    &RANGES
+}
+
+const fn v4(
+    address: [u8; 4],
+    prefix: u8,
+    purpose: SpecialPurpose,
+    name: &'static str,
+    rfc: &'static str,
+    globally_reachable: bool,
+) -> SpecialRange {
+    let [a, b, c, d] = address;
+    SpecialRange {
+        address: IpAddr::V4(Ipv4Addr::new(a, b, c, d)),
+        prefix,
+        purpose,
+        name,
+        rfc,
+        globally_reachable,
+    }
+}
+
+const fn v6(
+    address: [u16; 8],
+    prefix: u8,
+    purpose: SpecialPurpose,
+    name: &'static str,
+    rfc: &'static str,
+    globally_reachable: bool,
+) -> SpecialRange {
+    let [a, b, c, d, e, f, g, h] = address;
+    SpecialRange {
+        address: IpAddr::V6(Ipv6Addr::new(a, b, c, d, e, f, g, h)),
+        prefix,
+        purpose,
+        name,
+        rfc,
+        globally_reachable,
+    }
+}
+
+/// The registered ranges, by address.
+const RANGES: [SpecialRange; 50] = [
+    v4(
+        [0, 0, 0, 0],
+        8,
+        SpecialPurpose::ThisNetwork,
+        "This network",
+        "RFC 791",
+        false,
+    ),
+    v4(
+        [10, 0, 0, 0],
+        8,
+        SpecialPurpose::Private,
+        "Private-Use",
+        "RFC 1918",
+        false,
+    ),
+    v4(
+        [100, 64, 0, 0],
+        10,
+        SpecialPurpose::SharedAddress,
+        "Shared Address Space",
+        "RFC 6598",
+        false,
+    ),
+    v4(
+        [127, 0, 0, 0],
+        8,
+        SpecialPurpose::Loopback,
+        "Loopback",
+        "RFC 1122",
+        false,
+    ),
+    v4(
+        [169, 254, 0, 0],
+        16,
+        SpecialPurpose::LinkLocal,
+        "Link Local",
+        "RFC 3927",
+        false,
+    ),
+    v4(
+        [172, 16, 0, 0],
+        12,
+        SpecialPurpose::Private,
+        "Private-Use",
+        "RFC 1918",
+        false,
+    ),
+    v4(
+        [192, 0, 0, 0],
+        24,
+        SpecialPurpose::IetfProtocolAssignments,
+        "IETF Protocol Assignments",
+        "RFC 6890",
+        false,
+    ),
+    v4(
+        [192, 0, 0, 0],
+        29,
+        SpecialPurpose::ServiceContinuity,
+        "IPv4 Service Continuity Prefix",
+        "RFC 7335",
+        false,
+    ),
+    v4(
+        [192, 0, 0, 8],
+        32,
+        SpecialPurpose::Dummy,
+        "IPv4 dummy address",
+        "RFC 7600",
+        false,
+    ),
+    v4(
+        [192, 0, 0, 9],
+        32,
+        SpecialPurpose::ProtocolAnycast,
+        "Port Control Protocol Anycast",
+        "RFC 7723",
+        true,
+    ),
+    v4(
+        [192, 0, 0, 10],
+        32,
+        SpecialPurpose::ProtocolAnycast,
+        "Traversal Using Relays around NAT Anycast",
+        "RFC 8155",
+        true,
+    ),
+    v4(
+        [192, 0, 0, 170],
+        31,
+        SpecialPurpose::Ipv4Ipv6Translation,
+        "NAT64/DNS64 Discovery",
+        "RFC 8880",
+        false,
+    ),
+    v4(
+        [192, 0, 2, 0],
+        24,
+        SpecialPurpose::Documentation,
+        "Documentation (TEST-NET-1)",
+        "RFC 5737",
+        false,
+    ),
+    v4(
+        [192, 31, 196, 0],
+        24,
+        SpecialPurpose::As112,
+        "AS112-v4",
+        "RFC 7535",
+        true,
+    ),
+    v4(
+        [192, 52, 193, 0],
+        24,
+        SpecialPurpose::Amt,
+        "AMT",
+        "RFC 7450",
+        true,
+    ),
+    v4(
+        [192, 88, 99, 0],
+        24,
+        SpecialPurpose::SixToFourRelay,
+        "Deprecated (6to4 Relay Anycast)",
+        "RFC 7526",
+        false,
+    ),
+    v4(
+        [192, 168, 0, 0],
+        16,
+        SpecialPurpose::Private,
+        "Private-Use",
+        "RFC 1918",
+        false,
+    ),
+    v4(
+        [192, 175, 48, 0],
+        24,
+        SpecialPurpose::As112,
+        "Direct Delegation AS112 Service",
+        "RFC 7534",
+        true,
+    ),
+    v4(
+        [198, 18, 0, 0],
+        15,
+        SpecialPurpose::Benchmarking,
+        "Benchmarking",
+        "RFC 2544",
+        false,
+    ),
+    v4(
+        [198, 51, 100, 0],
+        24,
+        SpecialPurpose::Documentation,
+        "Documentation (TEST-NET-2)",
+        "RFC 5737",
+        false,
+    ),
+    v4(
+        [203, 0, 113, 0],
+        24,
+        SpecialPurpose::Documentation,
+        "Documentation (TEST-NET-3)",
+        "RFC 5737",
+        false,
+    ),
+    v4(
+        [224, 0, 0, 0],
+        4,
+        SpecialPurpose::Multicast,
+        "Multicast",
+        "RFC 5771",
+        false,
+    ),
+    v4(
+        [240, 0, 0, 0],
+        4,
+        SpecialPurpose::Reserved,
+        "Reserved",
+        "RFC 1112",
+        false,
+    ),
+    v4(
+        [255, 255, 255, 255],
+        32,
+        SpecialPurpose::Broadcast,
+        "Limited Broadcast",
+        "RFC 919",
+        false,
+    ),
+    v6(
+        [0; 8],
+        128,
+        SpecialPurpose::Unspecified,
+        "Unspecified Address",
+        "RFC 4291",
+        false,
+    ),
+    v6(
+        [0, 0, 0, 0, 0, 0, 0, 1],
+        128,
+        SpecialPurpose::Loopback,
+        "Loopback Address",
+        "RFC 4291",
+        false,
+    ),
+    v6(
+        [0, 0, 0, 0, 0, 0xffff, 0, 0],
+        96,
+        SpecialPurpose::Ipv4Mapped,
+        "IPv4-mapped Address",
+        "RFC 4291",
+        false,
+    ),
+    v6(
+        [0x64, 0xff9b, 0, 0, 0, 0, 0, 0],
+        96,
+        SpecialPurpose::Ipv4Ipv6Translation,
+        "IPv4-IPv6 Translat.",
+        "RFC 6052",
+        true,
+    ),
+    v6(
+        [0x64, 0xff9b, 1, 0, 0, 0, 0, 0],
+        48,
+        SpecialPurpose::Ipv4Ipv6Translation,
+        "IPv4-IPv6 Translat.",
+        "RFC 8215",
+        false,
+    ),
+    v6(
+        [0x100, 0, 0, 0, 0, 0, 0, 0],
+        64,
+        SpecialPurpose::DiscardOnly,
+        "Discard-Only Address Block",
+        "RFC 6666",
+        false,
+    ),
+    v6(
+        [0x100, 0, 0, 1, 0, 0, 0, 0],
+        64,
+        SpecialPurpose::Dummy,
+        "Dummy IPv6 Prefix",
+        "RFC 9780",
+        false,
+    ),
+    v6(
+        [0x2001, 0, 0, 0, 0, 0, 0, 0],
+        23,
+        SpecialPurpose::IetfProtocolAssignments,
+        "IETF Protocol Assignments",
+        "RFC 2928",
+        false,
+    ),
+    v6(
+        [0x2001, 0, 0, 0, 0, 0, 0, 0],
+        32,
+        SpecialPurpose::Teredo,
+        "TEREDO",
+        "RFC 4380",
+        true,
+    ),
+    v6(
+        [0x2001, 1, 0, 0, 0, 0, 0, 1],
+        128,
+        SpecialPurpose::ProtocolAnycast,
+        "Port Control Protocol Anycast",
+        "RFC 7723",
+        true,
+    ),
+    v6(
+        [0x2001, 1, 0, 0, 0, 0, 0, 2],
+        128,
+        SpecialPurpose::ProtocolAnycast,
+        "Traversal Using Relays around NAT Anycast",
+        "RFC 8155",
+        true,
+    ),
+    v6(
+        [0x2001, 1, 0, 0, 0, 0, 0, 3],
+        128,
+        SpecialPurpose::ProtocolAnycast,
+        "DNS-SD Service Registration Protocol Anycast",
+        "RFC 9665",
+        true,
+    ),
+    v6(
+        [0x2001, 2, 0, 0, 0, 0, 0, 0],
+        48,
+        SpecialPurpose::Benchmarking,
+        "Benchmarking",
+        "RFC 5180",
+        false,
+    ),
+    v6(
+        [0x2001, 3, 0, 0, 0, 0, 0, 0],
+        32,
+        SpecialPurpose::Amt,
+        "AMT",
+        "RFC 7450",
+        true,
+    ),
+    v6(
+        [0x2001, 4, 0x112, 0, 0, 0, 0, 0],
+        48,
+        SpecialPurpose::As112,
+        "AS112-v6",
+        "RFC 7535",
+        true,
+    ),
+    v6(
+        [0x2001, 0x10, 0, 0, 0, 0, 0, 0],
+        28,
+        SpecialPurpose::Orchid,
+        "Deprecated (previously ORCHID)",
+        "RFC 4843",
+        false,
+    ),
+    v6(
+        [0x2001, 0x20, 0, 0, 0, 0, 0, 0],
+        28,
+        SpecialPurpose::Orchid,
+        "ORCHIDv2",
+        "RFC 7343",
+        true,
+    ),
+    v6(
+        [0x2001, 0x30, 0, 0, 0, 0, 0, 0],
+        28,
+        SpecialPurpose::DroneRemoteId,
+        "Drone Remote ID Protocol Entity Tags (DETs) Prefix",
+        "RFC 9374",
+        true,
+    ),
+    v6(
+        [0x2001, 0xdb8, 0, 0, 0, 0, 0, 0],
+        32,
+        SpecialPurpose::Documentation,
+        "Documentation",
+        "RFC 3849",
+        false,
+    ),
+    v6(
+        [0x2002, 0, 0, 0, 0, 0, 0, 0],
+        16,
+        SpecialPurpose::SixToFour,
+        "6to4",
+        "RFC 3056",
+        true,
+    ),
+    v6(
+        [0x2620, 0x4f, 0x8000, 0, 0, 0, 0, 0],
+        48,
+        SpecialPurpose::As112,
+        "Direct Delegation AS112 Service",
+        "RFC 7534",
+        true,
+    ),
+    v6(
+        [0x3fff, 0, 0, 0, 0, 0, 0, 0],
+        20,
+        SpecialPurpose::Documentation,
+        "Documentation",
+        "RFC 9637",
+        false,
+    ),
+    v6(
+        [0x5f00, 0, 0, 0, 0, 0, 0, 0],
+        16,
+        SpecialPurpose::Srv6Sid,
+        "Segment Routing (SRv6) SIDs",
+        "RFC 9602",
+        false,
+    ),
+    v6(
+        [0xfc00, 0, 0, 0, 0, 0, 0, 0],
+        7,
+        SpecialPurpose::UniqueLocal,
+        "Unique-Local",
+        "RFC 4193",
+        false,
+    ),
+    v6(
+        [0xfe80, 0, 0, 0, 0, 0, 0, 0],
+        10,
+        SpecialPurpose::LinkLocal,
+        "Link-Local Unicast",
+        "RFC 4291",
+        false,
+    ),
+    v6(
+        [0xff00, 0, 0, 0, 0, 0, 0, 0],
+        8,
+        SpecialPurpose::Multicast,
+        "Multicast",
+        "RFC 4291",
+        false,
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranges_are_networks() {
+        for range in ranges() {
+            let network = range.network();
+            assert_eq!(network.network_address(), range.address, "{network}");
+            assert!(range.contains(range.address), "{network}");
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        use SpecialPurpose::*;
+
+        for (ip, purpose) in [
+            ("0.1.2.3", Some(ThisNetwork)),
+            ("10.1.2.3", Some(Private)),
+            ("172.31.255.255", Some(Private)),
+            ("172.32.0.0", None),
+            ("100.127.255.255", Some(SharedAddress)),
+            ("127.0.0.1", Some(Loopback)),
+            ("192.0.0.1", Some(ServiceContinuity)),
+            ("192.0.0.8", Some(Dummy)),
+            ("192.0.0.9", Some(ProtocolAnycast)),
+            ("192.0.0.10", Some(ProtocolAnycast)),
+            ("192.0.0.11", Some(IetfProtocolAssignments)),
+            ("192.0.0.171", Some(Ipv4Ipv6Translation)),
+            ("192.0.2.1", Some(Documentation)),
+            ("198.19.0.1", Some(Benchmarking)),
+            ("239.255.255.250", Some(Multicast)),
+            ("255.255.255.254", Some(Reserved)),
+            ("255.255.255.255", Some(Broadcast)),
+            ("8.8.8.8", None),
+            ("::", Some(Unspecified)),
+            ("::1", Some(Loopback)),
+            ("::2", None),
+            ("::ffff:10.0.0.1", Some(Ipv4Mapped)),
+            ("64:ff9b::8.8.8.8", Some(Ipv4Ipv6Translation)),
+            ("2001::1", Some(Teredo)),
+            ("2001:1::1", Some(ProtocolAnycast)),
+            ("2001:1::3", Some(ProtocolAnycast)),
+            ("2001:1::4", Some(IetfProtocolAssignments)),
+            ("2001:20::1", Some(Orchid)),
+            ("2001:30::1", Some(DroneRemoteId)),
+            ("2620:4f:8000::1", Some(As112)),
+            ("5f00::1", Some(Srv6Sid)),
+            ("2001:200::1", None),
+            ("2001:db8::1", Some(Documentation)),
+            ("2002:808:808::1", Some(SixToFour)),
+            ("fd00::1", Some(UniqueLocal)),
+            ("fe80::1", Some(LinkLocal)),
+            ("ff02::1", Some(Multicast)),
+            ("2606:4700::1111", None),
+        ] {
+            let ip: IpAddr = ip.parse().unwrap();
+            assert_eq!(classify(ip).map(SpecialRange::purpose), purpose, "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_reachable_host_within_unreachable_block() {
+        let range = classify("192.0.0.9".parse().unwrap()).unwrap();
+        assert_eq!(range.network().to_string(), "192.0.0.9/32");
+        assert!(range.is_globally_reachable());
+        assert!(!classify("192.0.0.11".parse().unwrap())
+            .unwrap()
+            .is_globally_reachable());
+    }
+}
@@ -0,0 +1,304 @@
+//! A longest-prefix-match table of network blocks that builds on `core` and
+//! `alloc` alone.
+//!
+//! The table is built once from its networks and is read-only afterwards.
+//! Each family keeps its networks sorted by address and prefix length, with
+//! the index of the nearest network that contains each one. A lookup finds
+//! the last network starting at or before the address with a binary search,
+//! then walks up its containing networks to the first one that holds the
+//! address.
+
+use crate::network::{from_bits, host_mask, Network};
+use alloc::vec::Vec;
+use core::net::IpAddr;
+
+/// Marks a network that no other network in the table contains.
+const NO_PARENT: u32 = u32::MAX;
+
+/// The integer type an address family is held in.
+trait Bits: Copy + Ord {
+    const WIDTH: u8;
+    const IPV6: bool;
+
+    fn to_u128(self) -> u128;
+}
+
+impl Bits for u32 {
+    const WIDTH: u8 = 32;
+    const IPV6: bool = false;
+
+    fn to_u128(self) -> u128 {
+        u128::from(self)
+    }
+}
+
+impl Bits for u128 {
+    const WIDTH: u8 = 128;
+    const IPV6: bool = true;
+
+    fn to_u128(self) -> u128 {
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry<K, T> {
+    start: K,
+    netmask: u8,
+    /// The index of the nearest entry that contains this one, or
+    /// `NO_PARENT`.
+    parent: u32,
+    value: T,
+}
+
+impl<K: Bits, T> Entry<K, T> {
+    fn network(&self) -> Network {
+        Network::new(from_bits(self.start.to_u128(), K::IPV6), self.netmask)
+            .expect("table entries have no bits set past their prefix length")
+    }
+
+    fn contains(&self, bits: K) -> bool {
+        let last = self.start.to_u128() | host_mask(K::WIDTH - self.netmask);
+        self.start <= bits && bits.to_u128() <= last
+    }
+}
+
+/// The networks of one address family.
+#[derive(Debug, Clone)]
+struct Family<K, T> {
+    entries: Vec<Entry<K, T>>,
+}
+
+impl<K: Bits, T> Family<K, T> {
+    /// Sorts the networks and links each one to its nearest containing
+    /// network. Of networks given more than once, the last one is kept.
+    fn new(mut networks: Vec<(K, u8, T)>) -> Self {
+        // The sort is stable, so the last of equal networks stays last.
+        networks.sort_by_key(|&(start, netmask, _)| (start, netmask));
+        let mut entries: Vec<Entry<K, T>> = Vec::with_capacity(networks.len());
+        let mut ancestors: Vec<u32> = Vec::new();
+        for (start, netmask, value) in networks {
+            if let Some(last) = entries.last_mut() {
+                if last.start == start && last.netmask == netmask {
+                    last.value = value;
+                    continue;
+                }
+            }
+            while let Some(&idx) = ancestors.last() {
+                if entries[idx as usize].contains(start) {
+                    break;
+                }
+                ancestors.pop();
+            }
+            let idx = u32::try_from(entries.len()).expect("fewer than 2^32 networks");
+            entries.push(Entry {
+                start,
+                netmask,
+                parent: ancestors.last().copied().unwrap_or(NO_PARENT),
+                value,
+            });
+            ancestors.push(idx);
+        }
+        Family { entries }
+    }
+
+    /// Returns the networks containing `bits`, from the most to the least
+    /// specific.
+    fn matches(&self, bits: K) -> impl Iterator<Item = (Network, &T)> + '_ {
+        // Every network containing the address contains, or is, the last
+        // network starting at or before it.
+        let mut idx = match self.entries.partition_point(|entry| entry.start <= bits) {
+            0 => NO_PARENT,
+            after => (after - 1) as u32,
+        };
+        core::iter::from_fn(move || {
+            while idx != NO_PARENT {
+                let entry = &self.entries[idx as usize];
+                idx = entry.parent;
+                if entry.contains(bits) {
+                    return Some((entry.network(), &entry.value));
+                }
+            }
+            None
+        })
+    }
+
+    #[cfg(any(feature = "serde", test))]
+    fn iter(&self) -> impl Iterator<Item = (Network, &T)> + '_ {
+        self.entries
+            .iter()
+            .map(|entry| (entry.network(), &entry.value))
+    }
+}
+
+/// A read-only table of IPv4 and IPv6 networks with longest-prefix-match
+/// lookups.
+#[derive(Debug, Clone)]
+pub(crate) struct NetworkTable<T> {
+    ipv4: Family<u32, T>,
+    ipv6: Family<u128, T>,
+}
+
+impl<T> NetworkTable<T> {
+    /// Creates an empty table.
+    pub(crate) fn new() -> Self {
+        NetworkTable {
+            ipv4: Family {
+                entries: Vec::new(),
+            },
+            ipv6: Family {
+                entries: Vec::new(),
+            },
+        }
+    }
+
+    /// Returns the most specific network containing `ip`.
+    pub(crate) fn longest_match(&self, ip: IpAddr) -> Option<(Network, &T)> {
+        self.matches(ip).next()
+    }
+
+    /// Returns the networks containing `ip`, from the most to the least
+    /// specific.
+    pub(crate) fn matches(&self, ip: IpAddr) -> impl Iterator<Item = (Network, &T)> + '_ {
+        let (ipv4, ipv6) = match ip {
+            IpAddr::V4(v4) => (Some(self.ipv4.matches(u32::from(v4))), None),
+            IpAddr::V6(v6) => (None, Some(self.ipv6.matches(u128::from(v6)))),
+        };
+        ipv4.into_iter().flatten().chain(ipv6.into_iter().flatten())
+    }
+
+    /// Returns every network in the table, IPv4 before IPv6, sorted by
+    /// address and then prefix length.
+    #[cfg(any(feature = "serde", test))]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (Network, &T)> + '_ {
+        self.ipv4.iter().chain(self.ipv6.iter())
+    }
+
+    /// Returns the IPv4 networks in the table, sorted by address and then
+    /// prefix length.
+    #[cfg(feature = "std")]
+    pub(crate) fn iter_ipv4(&self) -> impl Iterator<Item = (ip_network::Ipv4Network, &T)> + '_ {
+        self.ipv4.entries.iter().map(|entry| {
+            let network = ip_network::Ipv4Network::new(entry.start.into(), entry.netmask)
+                .expect("table entries have no bits set past their prefix length");
+            (network, &entry.value)
+        })
+    }
+
+    /// Returns the IPv6 networks in the table, sorted by address and then
+    /// prefix length.
+    #[cfg(feature = "std")]
+    pub(crate) fn iter_ipv6(&self) -> impl Iterator<Item = (ip_network::Ipv6Network, &T)> + '_ {
+        self.ipv6.entries.iter().map(|entry| {
+            let network = ip_network::Ipv6Network::new(entry.start.into(), entry.netmask)
+                .expect("table entries have no bits set past their prefix length");
+            (network, &entry.value)
+        })
+    }
+}
+
+impl<T> Default for NetworkTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<(Network, T)> for NetworkTable<T> {
+    /// Builds a table from networks and their values. Of networks given more
+    /// than once, the last one is kept.
+    fn from_iter<I: IntoIterator<Item = (Network, T)>>(networks: I) -> Self {
+        let mut ipv4 = Vec::new();
+        let mut ipv6 = Vec::new();
+        for (network, value) in networks {
+            match network.network_address() {
+                IpAddr::V4(v4) => ipv4.push((u32::from(v4), network.netmask(), value)),
+                IpAddr::V6(v6) => ipv6.push((u128::from(v6), network.netmask(), value)),
+            }
+        }
+        NetworkTable {
+            ipv4: Family::new(ipv4),
+            ipv6: Family::new(ipv6),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(s: &str) -> Network {
+        s.parse().unwrap()
+    }
+
+    fn lookup(table: &NetworkTable<u32>, ip: &str) -> Option<(String, u32)> {
+        table
+            .longest_match(ip.parse().unwrap())
+            .map(|(network, value)| (network.to_string(), *value))
+    }
+
+    #[test]
+    fn test_longest_match() {
+        let table: NetworkTable<u32> = [
+            (network("10.0.0.0/8"), 1),
+            (network("10.1.0.0/16"), 2),
+            (network("10.1.2.0/24"), 3),
+            (network("10.2.0.0/16"), 4),
+            (network("2001:db8::/32"), 5),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(table.iter().count(), 5);
+        assert_eq!(lookup(&table, "10.1.2.3"), Some(("10.1.2.0/24".into(), 3)));
+        assert_eq!(lookup(&table, "10.1.3.0"), Some(("10.1.0.0/16".into(), 2)));
+        // The last network before the address does not hold it, but one of
+        // the networks containing it does.
+        assert_eq!(
+            lookup(&table, "10.1.255.255"),
+            Some(("10.1.0.0/16".into(), 2))
+        );
+        assert_eq!(lookup(&table, "10.3.0.0"), Some(("10.0.0.0/8".into(), 1)));
+        assert_eq!(lookup(&table, "9.255.255.255"), None);
+        assert_eq!(lookup(&table, "11.0.0.0"), None);
+        assert_eq!(
+            lookup(&table, "2001:db8::1"),
+            Some(("2001:db8::/32".into(), 5))
+        );
+        assert_eq!(lookup(&table, "::a01:203"), None);
+
+        let matches: Vec<_> = table
+            .matches("10.1.2.3".parse().unwrap())
+            .map(|(_, value)| *value)
+            .collect();
+        assert_eq!(matches, [3, 2, 1]);
+    }
+
+    #[test]
+    fn test_later_duplicates_replace_earlier_ones() {
+        let table: NetworkTable<u32> = [
+            (network("10.0.0.0/8"), 1),
+            (network("0.0.0.0/0"), 2),
+            (network("10.0.0.0/8"), 3),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(lookup(&table, "10.0.0.1"), Some(("10.0.0.0/8".into(), 3)));
+        assert_eq!(
+            lookup(&table, "255.255.255.255"),
+            Some(("0.0.0.0/0".into(), 2))
+        );
+        let networks: Vec<_> = table
+            .iter()
+            .map(|(network, _)| network.to_string())
+            .collect();
+        assert_eq!(networks, ["0.0.0.0/0", "10.0.0.0/8"]);
+    }
+
+    #[test]
+    fn test_empty() {
+        let table = NetworkTable::<u32>::new();
+        assert_eq!(lookup(&table, "0.0.0.0"), None);
+        assert_eq!(lookup(&table, "::"), None);
+    }
+}
@@ -1,6 +1,8 @@
 //! This module contains the primary data structures for the `ip2asn` crate,
 //! with a focus on memory efficiency and query performance.
 
+use crate::asn::Asn;
+
 /// Represents a single, optimized record for the ASN lookup table.
 ///
 /// This struct is designed to be as small as possible to minimize the memory
@@ -10,10 +12,10 @@
 /// - `country_code`: A 2-byte array representing the ISO 3166-1 alpha-2 country code.
 /// - `organization_idx`: An index into a string interning table, pointing to the
 ///   full organization name. This avoids storing duplicate strings.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct AsnRecord {
     /// The Autonomous System Number.
-    pub asn: u32,
+    pub asn: Asn,
     /// A 2-byte array representing the ISO 3166-1 alpha-2 country code.
     pub country_code: [u8; 2],
     /// An index into a string interning table for the organization name.
@@ -0,0 +1,495 @@
+//! Contains the logic for validating a dataset without building a map.
+//!
+//! [`Builder::validate`](crate::Builder::validate) reads the same sources and
+//! formats as [`Builder::build`](crate::Builder::build), but instead of
+//! inserting records into a lookup table, it checks them for problems that
+//! would make the dataset unsafe to roll out, and summarizes them in a
+//! [`ValidationReport`].
+
+use crate::asn::Asn;
+use crate::interner::StringInterner;
+use crate::overlap::RangeSet;
+use crate::special::{self, SpecialRange};
+use crate::types::AsnRecord;
+use crate::{
+    country, CancellationToken, Error, OverlapWinner, ParseErrorKind, RecordSink, SourceRecord,
+};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::net::IpAddr;
+
+/// The kind of problem found in a dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+#[non_exhaustive]
+pub enum IssueKind {
+    /// A line could not be parsed.
+    Malformed,
+    /// A record starts before the record preceding it.
+    Unsorted,
+    /// A record overlaps an earlier record with a different range or data.
+    Overlap,
+    /// A record repeats an earlier record exactly.
+    Duplicate,
+    /// Address space between two records of the same ASN is not covered by
+    /// any record, suggesting a hole in that ASN's block.
+    ///
+    /// Any uncovered space between one ASN's records is reported, however
+    /// far apart they are, so this only suits datasets that cover the whole
+    /// address space, as the `iptoasn.com` format does with its unrouted
+    /// records. The check is skipped for the other formats, and for input
+    /// that is not sorted.
+    Gap,
+    /// A record assigns an ASN to reserved or special-purpose address space.
+    BogonWithAsn,
+    /// A record uses an ASN reserved for private use by RFC 6996.
+    PrivateAsn,
+    /// A record's country code is not an assigned ISO 3166-1 alpha-2 code.
+    /// `ZZ`, used for unknown countries, is accepted.
+    UnknownCountry,
+}
+
+impl IssueKind {
+    /// Returns the name of the kind, such as `"bogon_with_asn"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            IssueKind::Malformed => "malformed",
+            IssueKind::Unsorted => "unsorted",
+            IssueKind::Overlap => "overlap",
+            IssueKind::Duplicate => "duplicate",
+            IssueKind::Gap => "gap",
+            IssueKind::BogonWithAsn => "bogon_with_asn",
+            IssueKind::PrivateAsn => "private_asn",
+            IssueKind::UnknownCountry => "unknown_country",
+        }
+    }
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A single problem found in a dataset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct Issue {
+    /// The kind of problem.
+    pub kind: IssueKind,
+    /// The 1-based line number of the record with the problem.
+    pub line_number: usize,
+    /// The content of the line.
+    pub line_content: String,
+    /// The line of the other record involved, such as the one overlapped.
+    pub related_line: Option<usize>,
+    /// A message describing the problem.
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} on line {}: {} in line: \"{}\"",
+            self.kind, self.line_number, self.message, self.line_content
+        )
+    }
+}
+
+/// The issues of a single kind in a [`ValidationReport`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct IssueSummary {
+    /// The number of issues of this kind.
+    pub count: usize,
+    /// The first issues of this kind, up to the limit set with
+    /// [`Builder::warning_samples`](crate::Builder::warning_samples).
+    pub samples: Vec<Issue>,
+}
+
+/// The result of validating a dataset.
+///
+/// With the `serde` feature, the report can be serialized, for example as
+/// JSON for a CI job to gate on.
+///
+/// # Example
+///
+/// ```
+/// use ip2asn::validate::IssueKind;
+/// use ip2asn::Builder;
+///
+/// # fn main() -> Result<(), ip2asn::Error> {
+/// let data = "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n\
+///             10.0.0.0\t10.0.0.255\t64512\tXX\tPRIVATE";
+/// let report = Builder::new().with_source(data.as_bytes())?.validate()?;
+///
+/// assert!(!report.is_clean());
+/// assert_eq!(report.count(IssueKind::BogonWithAsn), 1);
+/// assert_eq!(report.count(IssueKind::PrivateAsn), 1);
+/// assert_eq!(report.count(IssueKind::UnknownCountry), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct ValidationReport {
+    /// The number of lines read, or of records for binary formats.
+    pub lines_read: usize,
+    /// The number of records that were parsed and checked.
+    pub records_checked: usize,
+    /// The issues found, grouped by kind.
+    pub issues: BTreeMap<IssueKind, IssueSummary>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no issues were found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns the number of issues of one kind.
+    pub fn count(&self, kind: IssueKind) -> usize {
+        self.issues.get(&kind).map_or(0, |summary| summary.count)
+    }
+
+    /// Returns the total number of issues of all kinds.
+    pub fn issue_count(&self) -> usize {
+        self.issues.values().map(|summary| summary.count).sum()
+    }
+}
+
+/// The position of a record in address order, with IPv4 before IPv6.
+fn sort_key(ip: IpAddr) -> (bool, u128) {
+    match ip {
+        IpAddr::V4(ip) => (false, u128::from(u32::from(ip))),
+        IpAddr::V6(ip) => (true, u128::from(ip)),
+    }
+}
+
+/// The first and last positions of a range, as given by [`sort_key`].
+type Bounds = ((bool, u128), (bool, u128));
+
+/// The last record read, for the sort order check.
+struct Previous {
+    line_number: usize,
+    start: (bool, u128),
+}
+
+/// The state of the gap check, which runs as the records are read and so
+/// relies on them being sorted.
+#[derive(Default)]
+struct GapCheck {
+    /// The furthest end of each ASN's records so far, with the line of the
+    /// record it came from.
+    ends: HashMap<Asn, ((bool, u128), usize)>,
+    /// The address space covered by the records so far, as sorted disjoint
+    /// ranges.
+    covered: Vec<Bounds>,
+}
+
+impl GapCheck {
+    /// Adds a record, returning the line of the earlier record of its ASN if
+    /// no record fills the space between the two.
+    ///
+    /// As the records are sorted, every record that could fill the space has
+    /// been read by the time the record after it is.
+    fn check(
+        &mut self,
+        asn: Asn,
+        start: (bool, u128),
+        end: (bool, u128),
+        line_number: usize,
+    ) -> Option<usize> {
+        let mut gap = None;
+        if asn != 0 {
+            match self.ends.entry(asn) {
+                Entry::Occupied(mut entry) => {
+                    let (last_end, last_line) = *entry.get();
+                    if last_end.0 == start.0 && start.1 > last_end.1.saturating_add(1) {
+                        let gap_start = (last_end.0, last_end.1 + 1);
+                        let i = self
+                            .covered
+                            .partition_point(|(covered_start, _)| *covered_start <= gap_start);
+                        if i == 0 || self.covered[i - 1].1 < (start.0, start.1 - 1) {
+                            gap = Some(last_line);
+                        }
+                    }
+                    // Keep the furthest end, in case the ASN's records overlap.
+                    if end > last_end {
+                        entry.insert((end, line_number));
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert((end, line_number));
+                }
+            }
+        }
+        match self.covered.last_mut() {
+            Some((_, covered_end))
+                if covered_end.0 == start.0 && start.1 <= covered_end.1.saturating_add(1) =>
+            {
+                *covered_end = (*covered_end).max(end);
+            }
+            _ => self.covered.push((start, end)),
+        }
+        gap
+    }
+}
+
+/// Checks each record read from a source.
+pub(crate) struct Validator<'b> {
+    samples: usize,
+    cancellation_token: Option<&'b CancellationToken>,
+    /// Special-purpose ranges that should not be routed.
+    bogons: Vec<Bounds>,
+    /// Globally reachable ranges, some nested in `bogons`, such as AS112
+    /// within the IETF protocol assignments.
+    reachable: Vec<Bounds>,
+    ranges: RangeSet,
+    interner: StringInterner,
+    previous: Option<Previous>,
+    /// The gap check, or `None` if it is skipped.
+    gaps: Option<GapCheck>,
+    report: ValidationReport,
+}
+
+impl<'b> Validator<'b> {
+    /// Creates a validator, with the gap check run if `check_gaps` is set.
+    pub(crate) fn new(
+        samples: usize,
+        check_gaps: bool,
+        cancellation_token: Option<&'b CancellationToken>,
+    ) -> Self {
+        let bounds = |range: &&SpecialRange| {
+            let (start, end) = crate::range::network_bounds(range.network());
+            (sort_key(start), sort_key(end))
+        };
+        let (reachable, bogons): (Vec<_>, Vec<_>) = special::ranges()
+            .iter()
+            .partition(|range| range.is_globally_reachable());
+        let bogons = bogons.iter().map(bounds).collect();
+        let reachable = reachable.iter().map(bounds).collect();
+        Self {
+            samples,
+            cancellation_token,
+            bogons,
+            reachable,
+            ranges: RangeSet::new(),
+            interner: StringInterner::new(),
+            previous: None,
+            gaps: check_gaps.then(GapCheck::default),
+            report: ValidationReport::default(),
+        }
+    }
+
+    pub(crate) fn finish(self) -> ValidationReport {
+        self.report
+    }
+
+    fn issue(
+        &mut self,
+        kind: IssueKind,
+        line_number: usize,
+        line_content: &str,
+        related_line: Option<usize>,
+        message: impl FnOnce() -> String,
+    ) {
+        let summary = self.report.issues.entry(kind).or_default();
+        summary.count += 1;
+        if summary.samples.len() < self.samples {
+            summary.samples.push(Issue {
+                kind,
+                line_number,
+                line_content: line_content.to_string(),
+                related_line,
+                message: message(),
+            });
+        }
+    }
+}
+
+impl RecordSink for Validator<'_> {
+    fn line(&mut self, line_number: usize) -> Result<(), Error> {
+        if self
+            .cancellation_token
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(Error::Cancelled);
+        }
+        self.report.lines_read = line_number;
+        Ok(())
+    }
+
+    fn record(
+        &mut self,
+        line_number: usize,
+        line_content: &str,
+        parsed: Result<SourceRecord<'_>, ParseErrorKind>,
+    ) -> Result<(), Error> {
+        let record = match parsed {
+            Ok(record) => record,
+            Err(kind) => {
+                self.issue(
+                    IssueKind::Malformed,
+                    line_number,
+                    line_content,
+                    None,
+                    || kind.to_string(),
+                );
+                return Ok(());
+            }
+        };
+        self.report.records_checked += 1;
+        let (start, end) = (sort_key(record.start_ip), sort_key(record.end_ip));
+
+        if let Some(previous) = self.previous.take() {
+            if start < previous.start {
+                self.issue(
+                    IssueKind::Unsorted,
+                    line_number,
+                    line_content,
+                    Some(previous.line_number),
+                    || format!("{} sorts before the previous record", record.start_ip),
+                );
+                // A record out of order may fill a gap already reported, so
+                // gaps are not checked in unsorted input.
+                if self.gaps.take().is_some() {
+                    self.report.issues.remove(&IssueKind::Gap);
+                }
+            }
+        }
+        self.previous = Some(Previous { line_number, start });
+
+        let gap = self
+            .gaps
+            .as_mut()
+            .and_then(|gaps| gaps.check(record.asn, start, end, line_number));
+        if let Some(related_line) = gap {
+            self.issue(
+                IssueKind::Gap,
+                line_number,
+                line_content,
+                Some(related_line),
+                || format!("gap before {} within AS{}", record.start_ip, record.asn),
+            );
+        }
+
+        let asn_record = AsnRecord {
+            asn: record.asn,
+            country_code: record.country_code,
+            organization_idx: self.interner.get_or_intern(&record.organization),
+        };
+        let conflicts = self.ranges.insert(
+            record.start_ip,
+            record.end_ip,
+            asn_record,
+            line_number,
+            OverlapWinner::First,
+        );
+        for conflict in conflicts {
+            let duplicate = conflict.start_ip == record.start_ip
+                && conflict.end_ip == record.end_ip
+                && conflict.record == asn_record;
+            let kind = if duplicate {
+                IssueKind::Duplicate
+            } else {
+                IssueKind::Overlap
+            };
+            self.issue(
+                kind,
+                line_number,
+                line_content,
+                Some(conflict.line_number),
+                || {
+                    format!(
+                        "{}-{} overlaps {}-{} from line {}",
+                        record.start_ip,
+                        record.end_ip,
+                        conflict.start_ip,
+                        conflict.end_ip,
+                        conflict.line_number
+                    )
+                },
+            );
+        }
+
+        if record.asn != 0 {
+            let bogon = self
+                .bogons
+                .iter()
+                .any(|&(bogon_start, bogon_end)| start <= bogon_end && end >= bogon_start)
+                && !self
+                    .reachable
+                    .iter()
+                    .any(|&(range_start, range_end)| start >= range_start && end <= range_end);
+            if bogon {
+                self.issue(
+                    IssueKind::BogonWithAsn,
+                    line_number,
+                    line_content,
+                    None,
+                    || format!("reserved address space is assigned to AS{}", record.asn),
+                );
+            }
+        }
+
+        if record.asn.is_private() {
+            self.issue(
+                IssueKind::PrivateAsn,
+                line_number,
+                line_content,
+                None,
+                || format!("AS{} is reserved for private use", record.asn),
+            );
+        }
+
+        if record.country_code != *b"ZZ" && !country::is_assigned(record.country_code) {
+            self.issue(
+                IssueKind::UnknownCountry,
+                line_number,
+                line_content,
+                None,
+                || {
+                    format!(
+                        "{} is not an ISO 3166-1 alpha-2 code",
+                        String::from_utf8_lossy(&record.country_code)
+                    )
+                },
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bogons_come_from_special_ranges() {
+        let validator = Validator::new(0, true, None);
+        assert_eq!(
+            validator.bogons.len() + validator.reachable.len(),
+            special::ranges().len()
+        );
+        let contains = |ranges: &[Bounds], ip: &str| {
+            let key = sort_key(ip.parse().unwrap());
+            ranges
+                .iter()
+                .any(|&(start, end)| start <= key && key <= end)
+        };
+        assert!(contains(&validator.bogons, "192.88.99.1"));
+        assert!(contains(&validator.bogons, "2001:2::1"));
+        assert!(contains(&validator.reachable, "192.175.48.1"));
+        assert!(!contains(&validator.bogons, "192.175.48.1"));
+    }
+}
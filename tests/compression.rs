@@ -0,0 +1,118 @@
+//! Integration tests for compressed data sources.
+#![cfg(feature = "std")]
+
+use ip2asn::compression::CompressionFormat;
+use ip2asn::{Builder, Error, IpAsnMap};
+use rstest::rstest;
+use std::error::Error as StdError;
+
+fn build(path: &str) -> Result<IpAsnMap, Error> {
+    Builder::new().from_path(path)?.build()
+}
+
+fn assert_matches_plain(map: &IpAsnMap) {
+    let plain = build("testdata/testdata-small-ip2asn.tsv").unwrap();
+    for ip in ["154.16.226.100", "45.234.212.10", "2001:67c:2309::1"] {
+        let ip = ip.parse().unwrap();
+        assert_eq!(map.lookup(ip), plain.lookup(ip), "lookup of {ip}");
+    }
+    let (mut actual, mut expected) = (Vec::new(), Vec::new());
+    map.write_tsv(&mut actual).unwrap();
+    plain.write_tsv(&mut expected).unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[rstest]
+#[case("testdata/testdata-small-ip2asn.tsv.gz")]
+#[case("testdata/testdata-small-ip2asn-multi.tsv.gz")]
+fn test_gzip(#[case] path: &str) {
+    assert_matches_plain(&build(path).unwrap());
+}
+
+#[cfg(feature = "bzip2")]
+#[test]
+fn test_bzip2() {
+    assert_matches_plain(&build("testdata/testdata-small-ip2asn.tsv.bz2").unwrap());
+}
+
+#[cfg(feature = "xz")]
+#[test]
+fn test_xz() {
+    assert_matches_plain(&build("testdata/testdata-small-ip2asn.tsv.xz").unwrap());
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_zstd() {
+    assert_matches_plain(&build("testdata/testdata-small-ip2asn.tsv.zst").unwrap());
+}
+
+#[rstest]
+#[case("testdata/testdata-small-ip2asn.tsv.gz", CompressionFormat::Gzip)]
+#[cfg_attr(
+    feature = "bzip2",
+    case("testdata/testdata-small-ip2asn.tsv.bz2", CompressionFormat::Bzip2)
+)]
+#[cfg_attr(
+    feature = "xz",
+    case("testdata/testdata-small-ip2asn.tsv.xz", CompressionFormat::Xz)
+)]
+#[cfg_attr(
+    feature = "zstd",
+    case("testdata/testdata-small-ip2asn.tsv.zst", CompressionFormat::Zstd)
+)]
+fn test_corrupt_stream(#[case] path: &str, #[case] expected: CompressionFormat) {
+    let mut data = std::fs::read(path).unwrap();
+    // Keep the header intact so the format is still detected, and clobber
+    // the compressed payload after it.
+    for byte in &mut data[16..] {
+        *byte ^= 0x5a;
+    }
+
+    let err = Builder::new()
+        .with_source(data.as_slice())
+        .and_then(|builder| builder.build())
+        .unwrap_err();
+    match &err {
+        Error::Decompression { format, .. } => assert_eq!(*format, expected),
+        other => panic!("Expected a Decompression error, got {other:?}"),
+    }
+    assert!(err
+        .to_string()
+        .starts_with(&format!("{expected} decompression error: ")));
+    assert!(err.source().is_some());
+}
+
+#[cfg(not(feature = "bzip2"))]
+#[test]
+fn test_disabled_format_is_rejected() {
+    let data = b"BZh91AY&SY\x00\x00";
+    let err = Builder::new()
+        .with_source(&data[..])
+        .and_then(|builder| builder.build())
+        .unwrap_err();
+    match &err {
+        Error::Decompression { format, source } => {
+            assert_eq!(*format, CompressionFormat::Bzip2);
+            assert_eq!(source.kind(), std::io::ErrorKind::Unsupported);
+        }
+        other => panic!("Expected a Decompression error, got {other:?}"),
+    }
+    assert!(err.to_string().contains("enable the `bzip2` feature"));
+}
+
+#[test]
+fn test_truncated_gzip_stream() {
+    let data = std::fs::read("testdata/testdata-small-ip2asn.tsv.gz").unwrap();
+    let err = Builder::new()
+        .with_source(&data[..data.len() / 2])
+        .and_then(|builder| builder.build())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Decompression {
+            format: CompressionFormat::Gzip,
+            ..
+        }
+    ));
+}
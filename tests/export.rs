@@ -0,0 +1,171 @@
+//! Integration tests for exporting a map as TSV.
+#![cfg(feature = "std")]
+
+use flate2::read::GzDecoder;
+use ip2asn::export::{IpFamily, TsvOptions};
+use ip2asn::{Builder, IpAsnMap};
+use std::io::Read;
+use std::net::IpAddr;
+
+fn load(data: &[u8]) -> IpAsnMap {
+    Builder::new()
+        .with_source(data)
+        .unwrap()
+        .strict()
+        .build()
+        .unwrap()
+}
+
+fn export(map: &IpAsnMap, options: TsvOptions) -> String {
+    let mut output = Vec::new();
+    map.write_tsv_with(&mut output, options).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_write_tsv_round_trip() {
+    let map = Builder::new()
+        .from_path("testdata/testdata-small-ip2asn.tsv")
+        .unwrap()
+        .strict()
+        .build()
+        .unwrap();
+    let mut output = Vec::new();
+    map.write_tsv(&mut output).unwrap();
+    let reloaded = load(&output);
+
+    let source = std::fs::read_to_string("testdata/testdata-small-ip2asn.tsv").unwrap();
+    for line in source.lines() {
+        let mut fields = line.split('\t');
+        for field in [fields.next(), fields.next()] {
+            let ip: IpAddr = field.unwrap().parse().unwrap();
+            assert_eq!(map.lookup_owned(ip), reloaded.lookup_owned(ip), "{ip}");
+            assert!(reloaded.lookup(ip).is_some());
+        }
+    }
+
+    // Writing the reloaded map gives the same output again.
+    let mut second = Vec::new();
+    reloaded.write_tsv(&mut second).unwrap();
+    assert_eq!(output, second);
+}
+
+#[test]
+fn test_write_tsv_merges_ranges() {
+    // The first range is split into three CIDRs and the next one is adjacent
+    // with the same record, so both come out as a single line. The unknown
+    // country is written in the iptoasn style.
+    let data = "1.0.0.0\t1.0.2.255\t13335\tAU\tCLOUDFLARENET\n\
+                1.0.3.0\t1.0.3.255\t13335\tAU\tCLOUDFLARENET\n\
+                1.0.4.0\t1.0.4.255\t0\tNone\tNot routed\n";
+    let map = load(data.as_bytes());
+    assert_eq!(
+        export(&map, TsvOptions::new()),
+        "1.0.0.0\t1.0.3.255\t13335\tAU\tCLOUDFLARENET\n\
+         1.0.4.0\t1.0.4.255\t0\tNone\tNot routed\n"
+    );
+}
+
+#[test]
+fn test_write_tsv_nested_ranges() {
+    // A more specific range overrides the middle of a larger one.
+    let data = "10.0.0.0\t10.0.255.255\t64496\tUS\tOUTER\n\
+                10.0.16.0\t10.0.31.255\t64497\tCA\tINNER\n";
+    let map = load(data.as_bytes());
+    let output = export(&map, TsvOptions::new());
+    assert_eq!(
+        output,
+        "10.0.0.0\t10.0.15.255\t64496\tUS\tOUTER\n\
+         10.0.16.0\t10.0.31.255\t64497\tCA\tINNER\n\
+         10.0.32.0\t10.0.255.255\t64496\tUS\tOUTER\n"
+    );
+
+    let reloaded = load(output.as_bytes());
+    for ip in ["10.0.0.1", "10.0.20.1", "10.0.200.1"] {
+        let ip: IpAddr = ip.parse().unwrap();
+        assert_eq!(
+            map.lookup(ip).map(|info| (info.asn, info.organization)),
+            reloaded
+                .lookup(ip)
+                .map(|info| (info.asn, info.organization))
+        );
+    }
+}
+
+#[test]
+fn test_write_tsv_options() {
+    let data = "1.0.0.0\t1.0.0.255\t13335\tAU\tCLOUDFLARENET\n\
+                2001:db8::\t2001:db8::ffff\t64496\tZZ\tDOC\tNET\n\
+                2606:4700::\t2606:4700:ffff:ffff:ffff:ffff:ffff:ffff\t13335\tUS\tCLOUDFLARENET\n";
+    // The second line has a tab in its organization and is skipped.
+    let map = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        export(&map, TsvOptions::new().family(IpFamily::Ipv4)),
+        "1.0.0.0\t1.0.0.255\t13335\tAU\tCLOUDFLARENET\n"
+    );
+    assert_eq!(
+        export(&map, TsvOptions::new().family(IpFamily::Ipv6)),
+        "2606:4700::\t2606:4700:ffff:ffff:ffff:ffff:ffff:ffff\t13335\tUS\tCLOUDFLARENET\n"
+    );
+
+    let mut compressed = Vec::new();
+    map.write_tsv_with(&mut compressed, TsvOptions::new().gzip(true))
+        .unwrap();
+    let mut decompressed = String::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_string(&mut decompressed)
+        .unwrap();
+    assert_eq!(decompressed, export(&map, TsvOptions::new()));
+
+    // Gzip output is detected when loading it back.
+    let reloaded = load(&compressed);
+    assert_eq!(
+        reloaded.lookup_owned("2606:4700::1".parse().unwrap()),
+        map.lookup_owned("2606:4700::1".parse().unwrap())
+    );
+}
+
+#[test]
+fn test_write_tsv_replaces_control_characters() {
+    let data = "network,autonomous_system_number,autonomous_system_organization\n\
+                1.0.0.0/24,13335,\"CLOUDFLARE\tNET\"\n\
+                1.0.1.0/24,13335,\"CLOUDFLARE\rNET\"\n";
+    let map = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .format(ip2asn::InputFormat::GeoLite2AsnCsv)
+        .build()
+        .unwrap();
+    assert_eq!(
+        export(&map, TsvOptions::new()),
+        "1.0.0.0\t1.0.0.255\t13335\tNone\tCLOUDFLARE NET\n\
+         1.0.1.0\t1.0.1.255\t13335\tNone\tCLOUDFLARE NET\n"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_write_tsv_round_trip_with_newline() {
+    let snapshot = r#"{
+        "organizations": ["CLOUDFLARE\nNET", "GOOGLE"],
+        "networks": [["1.0.0.0/24", 13335, "AU", 0], ["1.0.1.0/24", 15169, "US", 1]]
+    }"#;
+    let map: IpAsnMap = serde_json::from_str(snapshot).unwrap();
+    let output = export(&map, TsvOptions::new());
+    assert_eq!(
+        output,
+        "1.0.0.0\t1.0.0.255\t13335\tAU\tCLOUDFLARE NET\n\
+         1.0.1.0\t1.0.1.255\t15169\tUS\tGOOGLE\n"
+    );
+
+    let reloaded = load(output.as_bytes());
+    assert_eq!(export(&reloaded, TsvOptions::new()), output);
+    let info = reloaded.lookup("1.0.0.1".parse().unwrap()).unwrap();
+    assert_eq!(info.organization, "CLOUDFLARE NET");
+    assert_eq!(info.asn, 13335);
+}
@@ -0,0 +1,233 @@
+//! Integration tests for cached, conditional, and resumable fetching.
+#![cfg(feature = "fetch")]
+
+use ip2asn::fetch::{FetchOutcome, Fetcher};
+use ip2asn::{Builder, Error};
+use mockito::{Matcher, Server};
+use std::fs;
+use std::path::Path;
+
+const DATA: &str = "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n";
+const ETAG: &str = "\"v1\"";
+const LAST_MODIFIED: &str = "Wed, 21 Oct 2015 07:28:00 GMT";
+
+fn fetcher(server: &Server, dir: &Path) -> Fetcher {
+    Fetcher::new(
+        format!("{}/ip2asn.tsv", server.url()),
+        dir.join("cache").join("ip2asn.tsv"),
+    )
+}
+
+#[test]
+fn test_fetch_downloads_and_saves_metadata() {
+    let mut server = Server::new();
+    let dir = tempfile::tempdir().unwrap();
+    let mock = server
+        .mock("GET", "/ip2asn.tsv")
+        .match_header("if-none-match", Matcher::Missing)
+        .with_header("etag", ETAG)
+        .with_header("last-modified", LAST_MODIFIED)
+        .with_body(DATA)
+        .create();
+
+    let fetcher = fetcher(&server, dir.path());
+    let outcome = fetcher.fetch().unwrap();
+    assert_eq!(
+        outcome,
+        FetchOutcome::Downloaded {
+            size: DATA.len() as u64
+        }
+    );
+    mock.assert();
+
+    assert_eq!(fs::read_to_string(fetcher.cache_path()).unwrap(), DATA);
+    let metadata = fetcher.metadata().unwrap().unwrap();
+    assert_eq!(metadata.etag.as_deref(), Some(ETAG));
+    assert_eq!(metadata.last_modified.as_deref(), Some(LAST_MODIFIED));
+
+    let map = Builder::new()
+        .from_path(fetcher.cache_path())
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(map.lookup("1.0.0.1".parse().unwrap()).unwrap().asn, 13335);
+}
+
+#[test]
+fn test_fetch_not_modified() {
+    let mut server = Server::new();
+    let dir = tempfile::tempdir().unwrap();
+    let fetcher = fetcher(&server, dir.path());
+    let first = server
+        .mock("GET", "/ip2asn.tsv")
+        .with_header("etag", ETAG)
+        .with_header("last-modified", LAST_MODIFIED)
+        .with_body(DATA)
+        .create();
+    fetcher.fetch().unwrap();
+    first.remove();
+
+    let mock = server
+        .mock("GET", "/ip2asn.tsv")
+        .match_header("if-none-match", ETAG)
+        .match_header("if-modified-since", LAST_MODIFIED)
+        .with_status(304)
+        .create();
+    assert_eq!(fetcher.fetch().unwrap(), FetchOutcome::NotModified);
+    mock.assert();
+    assert_eq!(fs::read_to_string(fetcher.cache_path()).unwrap(), DATA);
+}
+
+#[test]
+fn test_fetch_without_cache_file_is_unconditional() {
+    let mut server = Server::new();
+    let dir = tempfile::tempdir().unwrap();
+    let fetcher = fetcher(&server, dir.path());
+    fs::create_dir_all(fetcher.cache_path().parent().unwrap()).unwrap();
+    // Metadata left behind by a cache file that has since been deleted.
+    let meta_path = fetcher.cache_path().with_extension("tsv.meta");
+    fs::write(&meta_path, format!("ETag: {ETAG}\n")).unwrap();
+
+    let mock = server
+        .mock("GET", "/ip2asn.tsv")
+        .match_header("if-none-match", Matcher::Missing)
+        .with_header("etag", "\"v2\"")
+        .with_body(DATA)
+        .create();
+    let outcome = fetcher.fetch().unwrap();
+    assert_eq!(
+        outcome,
+        FetchOutcome::Downloaded {
+            size: DATA.len() as u64
+        }
+    );
+    mock.assert();
+    let metadata = fetcher.metadata().unwrap().unwrap();
+    assert_eq!(metadata.etag.as_deref(), Some("\"v2\""));
+    assert_eq!(metadata.last_modified, None);
+}
+
+fn write_partial(fetcher: &Fetcher, len: usize) {
+    let cache_path = fetcher.cache_path();
+    fs::create_dir_all(cache_path.parent().unwrap()).unwrap();
+    fs::write(
+        cache_path.with_extension("tsv.part"),
+        &DATA.as_bytes()[..len],
+    )
+    .unwrap();
+    fs::write(
+        cache_path.with_extension("tsv.part.meta"),
+        format!("ETag: {ETAG}\nLast-Modified: {LAST_MODIFIED}\n"),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_fetch_resumes_partial_download() {
+    let mut server = Server::new();
+    let dir = tempfile::tempdir().unwrap();
+    let fetcher = fetcher(&server, dir.path());
+    write_partial(&fetcher, 10);
+
+    let mock = server
+        .mock("GET", "/ip2asn.tsv")
+        .match_header("range", "bytes=10-")
+        .match_header("if-range", ETAG)
+        .with_status(206)
+        .with_header(
+            "content-range",
+            &format!("bytes 10-{}/{}", DATA.len() - 1, DATA.len()),
+        )
+        .with_header("etag", ETAG)
+        .with_body(&DATA[10..])
+        .create();
+
+    let outcome = fetcher.fetch().unwrap();
+    assert_eq!(
+        outcome,
+        FetchOutcome::Resumed {
+            offset: 10,
+            size: DATA.len() as u64
+        }
+    );
+    mock.assert();
+    assert_eq!(fs::read_to_string(fetcher.cache_path()).unwrap(), DATA);
+    assert!(!fetcher.cache_path().with_extension("tsv.part").exists());
+    let metadata = fetcher.metadata().unwrap().unwrap();
+    assert_eq!(metadata.etag.as_deref(), Some(ETAG));
+    assert_eq!(metadata.last_modified.as_deref(), Some(LAST_MODIFIED));
+}
+
+#[test]
+fn test_fetch_restarts_when_remote_file_changed() {
+    let mut server = Server::new();
+    let dir = tempfile::tempdir().unwrap();
+    let fetcher = fetcher(&server, dir.path());
+    write_partial(&fetcher, 10);
+
+    // The `If-Range` validator no longer matches, so the whole file is sent.
+    let changed = "8.8.8.0\t8.8.8.255\t15169\tUS\tGOOGLE\n";
+    let mock = server
+        .mock("GET", "/ip2asn.tsv")
+        .match_header("range", "bytes=10-")
+        .with_header("etag", "\"v2\"")
+        .with_body(changed)
+        .create();
+
+    let outcome = fetcher.fetch().unwrap();
+    assert_eq!(
+        outcome,
+        FetchOutcome::Downloaded {
+            size: changed.len() as u64
+        }
+    );
+    mock.assert();
+    assert_eq!(fs::read_to_string(fetcher.cache_path()).unwrap(), changed);
+    let metadata = fetcher.metadata().unwrap().unwrap();
+    assert_eq!(metadata.etag.as_deref(), Some("\"v2\""));
+}
+
+#[test]
+fn test_fetch_discards_unsatisfiable_partial_download() {
+    let mut server = Server::new();
+    let dir = tempfile::tempdir().unwrap();
+    let fetcher = fetcher(&server, dir.path());
+    write_partial(&fetcher, 10);
+
+    let range = server
+        .mock("GET", "/ip2asn.tsv")
+        .match_header("range", "bytes=10-")
+        .with_status(416)
+        .create();
+    let full = server
+        .mock("GET", "/ip2asn.tsv")
+        .match_header("range", Matcher::Missing)
+        .with_header("etag", ETAG)
+        .with_body(DATA)
+        .create();
+
+    let outcome = fetcher.fetch().unwrap();
+    assert_eq!(
+        outcome,
+        FetchOutcome::Downloaded {
+            size: DATA.len() as u64
+        }
+    );
+    range.assert();
+    full.assert();
+    assert_eq!(fs::read_to_string(fetcher.cache_path()).unwrap(), DATA);
+}
+
+#[test]
+fn test_fetch_http_error_keeps_cache() {
+    let mut server = Server::new();
+    let dir = tempfile::tempdir().unwrap();
+    let fetcher = fetcher(&server, dir.path());
+    fs::create_dir_all(fetcher.cache_path().parent().unwrap()).unwrap();
+    fs::write(fetcher.cache_path(), DATA).unwrap();
+
+    let mock = server.mock("GET", "/ip2asn.tsv").with_status(500).create();
+    assert!(matches!(fetcher.fetch(), Err(Error::Http(_))));
+    mock.assert();
+    assert_eq!(fs::read_to_string(fetcher.cache_path()).unwrap(), DATA);
+}
@@ -0,0 +1,123 @@
+//! Integration tests for the GeoLite2-ASN CSV input format.
+#![cfg(feature = "std")]
+
+use ip2asn::geolite2::parse_asn_line;
+use ip2asn::{Builder, Error, InputFormat, ParseErrorKind};
+use ip_network::IpNetwork;
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+fn open(path: &str) -> BufReader<File> {
+    BufReader::new(File::open(path).unwrap())
+}
+
+#[test]
+fn test_parse_asn_line() {
+    let block = parse_asn_line("1.0.0.0/24,13335,CLOUDFLARENET").unwrap();
+    assert_eq!(block.network, "1.0.0.0/24".parse::<IpNetwork>().unwrap());
+    assert_eq!(block.asn, 13335);
+    assert_eq!(block.organization, "CLOUDFLARENET");
+
+    let block = parse_asn_line("1.0.4.0/22,38803,\"Wirelessnet, Pty Ltd\"").unwrap();
+    assert_eq!(block.organization, "Wirelessnet, Pty Ltd");
+
+    assert!(matches!(
+        parse_asn_line("1.0.0.1/24,13335,CLOUDFLARENET"),
+        Err(ParseErrorKind::InvalidNetwork { .. })
+    ));
+    assert!(matches!(
+        parse_asn_line("1.0.0.0/24,AS13335,CLOUDFLARENET"),
+        Err(ParseErrorKind::InvalidAsnNumber { .. })
+    ));
+    assert!(matches!(
+        parse_asn_line("1.0.0.0/24,13335"),
+        Err(ParseErrorKind::IncorrectColumnCount {
+            expected: 3,
+            found: 2
+        })
+    ));
+}
+
+#[test]
+fn test_builder_geolite2_ipv4_and_ipv6() {
+    let source = open("testdata/geolite2-asn-blocks-ipv4.csv")
+        .chain(open("testdata/geolite2-asn-blocks-ipv6.csv"));
+    let map = Builder::new()
+        .with_source(source)
+        .unwrap()
+        .format(InputFormat::GeoLite2AsnCsv)
+        .strict()
+        .build()
+        .unwrap();
+
+    let info = map.lookup("1.0.5.1".parse().unwrap()).unwrap();
+    assert_eq!(info.network, "1.0.4.0/22".parse::<IpNetwork>().unwrap());
+    assert_eq!(info.asn, 38803);
+    assert_eq!(info.country_code, "ZZ");
+    assert_eq!(info.organization, "Wirelessnet, Pty Ltd");
+
+    let info = map.lookup("31.13.100.100".parse().unwrap()).unwrap();
+    assert_eq!(info.organization, "FACEBOOK \"META\"");
+
+    let info = map.lookup("2606:4700::1111".parse().unwrap()).unwrap();
+    assert_eq!(info.network, "2606:4700::/32".parse::<IpNetwork>().unwrap());
+    assert_eq!(info.asn, 13335);
+    assert_eq!(info.organization, "CLOUDFLARENET");
+}
+
+#[test]
+fn test_builder_geolite2_with_countries() {
+    let map = Builder::new()
+        .from_path("testdata/geolite2-asn-blocks-ipv4.csv")
+        .unwrap()
+        .format(InputFormat::GeoLite2AsnCsv)
+        .with_geolite2_countries(
+            open("testdata/geolite2-country-blocks-ipv4.csv"),
+            open("testdata/geolite2-country-locations-en.csv"),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let info = map.lookup("1.0.0.1".parse().unwrap()).unwrap();
+    assert_eq!(info.country_code, "AU");
+
+    // The block has no geoname_id, so the registered country is used.
+    let info = map.lookup("1.0.4.1".parse().unwrap()).unwrap();
+    assert_eq!(info.country_code, "AU");
+
+    let info = map.lookup("8.8.8.8".parse().unwrap()).unwrap();
+    assert_eq!(info.country_code, "US");
+
+    // No country block covers this network.
+    let info = map.lookup("31.13.64.1".parse().unwrap()).unwrap();
+    assert_eq!(info.country_code, "ZZ");
+}
+
+#[test]
+fn test_builder_geolite2_strict_mode() {
+    let data = "network,autonomous_system_number,autonomous_system_organization\n\
+                1.0.0.0/24,13335,CLOUDFLARENET\n\
+                not-a-network,15169,GOOGLE\n";
+    let result = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .format(InputFormat::GeoLite2AsnCsv)
+        .strict()
+        .build();
+
+    match result {
+        Err(Error::Parse {
+            line_number, kind, ..
+        }) => {
+            assert_eq!(line_number, 3);
+            assert_eq!(
+                kind,
+                ParseErrorKind::InvalidNetwork {
+                    value: "not-a-network".to_string()
+                }
+            );
+        }
+        _ => panic!("Expected a Parse error"),
+    }
+}
@@ -0,0 +1,132 @@
+//! Integration tests for time-versioned lookups with `MapHistory`.
+#![cfg(feature = "std")]
+
+use ip2asn::history::MapHistory;
+use ip2asn::{Builder, IpAsnMap};
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime};
+
+const JANUARY: &str = "\
+1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET
+8.8.8.0\t8.8.8.255\t15169\tUS\tGOOGLE
+2001:db8::\t2001:db8::ffff\t64496\tZZ\tDOCUMENTATION
+";
+
+const FEBRUARY: &str = "\
+1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET
+8.8.8.0\t8.8.8.127\t15169\tUS\tGOOGLE
+8.8.8.128\t8.8.8.255\t64500\tDE\tTRANSFERRED
+";
+
+const MARCH: &str = "\
+1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET
+8.8.8.0\t8.8.8.255\t15169\tUS\tGOOGLE
+";
+
+fn map(data: &str) -> IpAsnMap {
+    Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+fn day(n: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(n * 86_400)
+}
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+fn history() -> MapHistory {
+    let mut history = MapHistory::new();
+    // Added out of order, to check the versions are kept sorted.
+    history.insert(day(60), &map(MARCH));
+    history.insert(day(1), &map(JANUARY));
+    history.insert(day(32), &map(FEBRUARY));
+    history
+}
+
+#[test]
+fn test_versions_are_sorted() {
+    let history = history();
+    assert_eq!(history.len(), 3);
+    assert!(!history.is_empty());
+    assert_eq!(
+        history.versions().collect::<Vec<_>>(),
+        [day(1), day(32), day(60)]
+    );
+    assert!(MapHistory::new().is_empty());
+}
+
+#[test]
+fn test_lookup_at() {
+    let history = history();
+    let asn = |addr: &str, time| history.lookup_at(ip(addr), time).map(|o| o.asn.get());
+
+    assert_eq!(asn("8.8.8.200", day(0)), None);
+    assert_eq!(asn("8.8.8.200", day(1)), Some(15169));
+    assert_eq!(asn("8.8.8.200", day(31)), Some(15169));
+    assert_eq!(asn("8.8.8.200", day(32)), Some(64500));
+    assert_eq!(asn("8.8.8.200", day(59)), Some(64500));
+    assert_eq!(asn("8.8.8.200", day(60)), Some(15169));
+    assert_eq!(asn("8.8.8.200", day(1000)), Some(15169));
+    assert_eq!(asn("8.8.8.8", day(40)), Some(15169));
+    assert_eq!(asn("2001:db8::1", day(10)), Some(64496));
+    assert_eq!(asn("2001:db8::1", day(40)), None);
+    assert_eq!(asn("9.9.9.9", day(10)), None);
+
+    let owner = history.lookup_at(ip("8.8.8.200"), day(40)).unwrap();
+    assert_eq!(owner.country_code, "DE");
+    assert_eq!(owner.organization, "TRANSFERRED");
+}
+
+#[test]
+fn test_history() {
+    let history = history();
+
+    let changes = history.history(ip("8.8.8.200"));
+    let summary: Vec<_> = changes
+        .iter()
+        .map(|change| (change.since, change.owner.map(|o| o.asn.get())))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (day(1), Some(15169)),
+            (day(32), Some(64500)),
+            (day(60), Some(15169)),
+        ]
+    );
+
+    let changes = history.history(ip("1.0.0.1"));
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].since, day(1));
+    assert_eq!(changes[0].owner.unwrap().organization, "CLOUDFLARENET");
+
+    let changes = history.history(ip("2001:db8::1"));
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[1].since, day(32));
+    assert!(changes[1].owner.is_none());
+
+    let changes = history.history(ip("9.9.9.9"));
+    assert_eq!(changes.len(), 1);
+    assert!(changes[0].owner.is_none());
+
+    assert!(MapHistory::new().history(ip("1.0.0.1")).is_empty());
+}
+
+#[test]
+fn test_insert_replaces_same_time() {
+    let mut history = history();
+    history.insert(day(32), &map(MARCH));
+    assert_eq!(history.len(), 3);
+    assert_eq!(
+        history
+            .lookup_at(ip("8.8.8.200"), day(40))
+            .map(|o| o.asn.get()),
+        Some(15169)
+    );
+    assert_eq!(history.history(ip("8.8.8.200")).len(), 1);
+}
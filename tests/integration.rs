@@ -1,6 +1,12 @@
-use ip2asn::{Builder, Error, IpAsnMap, ParseErrorKind, Warning};
+#![cfg(feature = "std")]
+
+use ip2asn::asn::{Asn, AsnKind, AsnNotation};
+use ip2asn::country::{Continent, Region};
+use ip2asn::embedded::Ipv4Embedding;
+use ip2asn::special::SpecialPurpose;
+use ip2asn::{BuildLimit, Builder, Error, IpAsnMap, ParseErrorKind, Warning};
 use ip_network::IpNetwork;
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
@@ -156,6 +162,165 @@ fn test_builder_strict_mode() {
     }
 }
 
+#[test]
+fn test_builder_build_with_report() {
+    let bad_lines = "1.0.9.0\t1.0.9.255\tAS1\tUS\tBAD\n".repeat(3);
+    let data = format!("{MALFORMED_DATA}{bad_lines}");
+    let warnings = Arc::new(AtomicUsize::new(0));
+    let warnings_clone = warnings.clone();
+    let (map, report) = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .warning_samples(2)
+        .on_warning(move |_| {
+            warnings_clone.fetch_add(1, Ordering::SeqCst);
+        })
+        .build_with_report()
+        .unwrap();
+
+    assert!(map.lookup(Ipv4Addr::new(8, 8, 8, 8).into()).is_some());
+    assert_eq!(report.lines_read, 11);
+    assert_eq!(report.comments_skipped, 2);
+    assert_eq!(report.records_inserted, 3);
+    // 1.0.1.0-1.0.3.255 is split into a /24 and a /23.
+    assert_eq!(report.cidrs_generated, 4);
+    assert_eq!(report.warning_count(), 5);
+    assert_eq!(warnings.load(Ordering::SeqCst), 5);
+
+    let column_count = &report.warnings["IncorrectColumnCount"];
+    assert_eq!(column_count.count, 1);
+    assert_eq!(
+        column_count.samples,
+        vec![Warning::Parse {
+            line_number: 5,
+            line_content: "invalid line format".to_string(),
+            kind: ParseErrorKind::IncorrectColumnCount {
+                expected: 5,
+                found: 1
+            },
+        }]
+    );
+
+    // Only the first two of the four invalid ASNs are kept as samples.
+    let invalid_asn = &report.warnings["InvalidAsnNumber"];
+    assert_eq!(invalid_asn.count, 4);
+    let sample_lines: Vec<_> = invalid_asn
+        .samples
+        .iter()
+        .map(|warning| match warning {
+            Warning::Parse {
+                line_number, kind, ..
+            } => {
+                assert_eq!(kind.name(), "InvalidAsnNumber");
+                *line_number
+            }
+            other => panic!("Expected a Parse warning, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(sample_lines, vec![6, 9]);
+}
+
+#[test]
+fn test_builder_max_warnings() {
+    // MALFORMED_DATA has two malformed lines, on lines 5 and 6.
+    let build = |max| {
+        Builder::new()
+            .with_source(MALFORMED_DATA.as_bytes())
+            .unwrap()
+            .max_warnings(max)
+            .build()
+    };
+    assert!(build(2).is_ok());
+
+    match build(1).unwrap_err() {
+        Error::Limit { limit, report } => {
+            assert_eq!(limit, BuildLimit::MaxWarnings(1));
+            // The build stops at the line that crossed the limit.
+            assert_eq!(report.lines_read, 6);
+            assert_eq!(report.records_inserted, 2);
+            assert_eq!(report.warning_count(), 2);
+        }
+        other => panic!("Expected a Limit error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_builder_min_records() {
+    let build = |min| {
+        Builder::new()
+            .with_source(MALFORMED_DATA.as_bytes())
+            .unwrap()
+            .min_records(min)
+            .build_with_report()
+    };
+    let (_, report) = build(3).unwrap();
+    assert_eq!(report.records_inserted, 3);
+
+    let err = build(4).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Limit error: 3 records were inserted from 8 lines, fewer than the minimum of 4 \
+         (skipped: IncorrectColumnCount x1, InvalidAsnNumber x1)"
+    );
+    match err {
+        Error::Limit { limit, report } => {
+            assert_eq!(limit, BuildLimit::MinRecords(4));
+            assert_eq!(report.lines_read, 8);
+        }
+        other => panic!("Expected a Limit error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_builder_min_records_truncated_source() {
+    let text = std::fs::read_to_string("testdata/testdata-small-ip2asn.tsv").unwrap();
+    let truncated = &text[..text.len() / 4];
+    let result = Builder::new()
+        .with_source(truncated.as_bytes())
+        .unwrap()
+        .min_records(20)
+        .build();
+    assert!(matches!(
+        result,
+        Err(Error::Limit {
+            limit: BuildLimit::MinRecords(20),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_builder_crlf_line_endings() {
+    let data = "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\r\n\r\n\
+                8.8.8.0\t8.8.8.255\t15169\tUS\tGOOGLE\r\n\
+                bad line\r\n";
+    let err = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .strict()
+        .build()
+        .unwrap_err();
+    match err {
+        Error::Parse {
+            line_number,
+            line_content,
+            ..
+        } => {
+            assert_eq!(line_number, 4);
+            assert_eq!(line_content, "bad line");
+        }
+        other => panic!("Expected a Parse error, got {other:?}"),
+    }
+
+    let map = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .build()
+        .unwrap();
+    let result = map.lookup(Ipv4Addr::new(8, 8, 8, 8).into()).unwrap();
+    assert_eq!(result.organization, "GOOGLE");
+}
+
 #[test]
 fn test_builder_warning_callback() {
     let warning_count = Arc::new(AtomicUsize::new(0));
@@ -184,7 +349,6 @@ fn test_builder_warning_callback() {
 #[cfg(feature = "fetch")]
 mod fetch_tests {
     use super::*;
-    use mockito;
     use std::io::Read;
 
     #[tokio::test]
@@ -314,6 +478,209 @@ fn test_lookup_owned() {
     assert!(map.lookup_owned("127.0.0.1".parse().unwrap()).is_none());
 }
 
+#[test]
+fn test_lookup_country() {
+    let data = "1.0.0.0\t1.0.0.255\t13335\tus\tCLOUDFLARENET\n\
+                1.0.1.0\t1.0.1.255\t0\tNone\tNot routed\n\
+                1.0.2.0\t1.0.2.255\t38040\tEU\tGTELECOM";
+    let map = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let info = map.lookup("1.0.0.1".parse().unwrap()).unwrap();
+    let country = info.country().unwrap();
+    assert_eq!(country.code(), "US");
+    assert_eq!(country.alpha3(), "USA");
+    assert_eq!(country.continent(), Continent::NorthAmerica);
+    assert_eq!(country.region(), Some(Region::NorthernAmerica));
+
+    let owned = map.lookup_owned("1.0.0.1".parse().unwrap()).unwrap();
+    assert_eq!(owned.country(), Some(country));
+
+    // Unknown and unassigned codes are kept, but have no country.
+    let unrouted = map.lookup("1.0.1.1".parse().unwrap()).unwrap();
+    assert_eq!(unrouted.country_code, "ZZ");
+    assert_eq!(unrouted.country(), None);
+    let unassigned = map.lookup("1.0.2.1".parse().unwrap()).unwrap();
+    assert_eq!(unassigned.country_code, "EU");
+    assert_eq!(unassigned.country(), None);
+}
+
+#[test]
+fn test_lookup_asn() {
+    let data = "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n\
+                1.0.1.0\t1.0.1.255\t0\tNone\tNot routed\n\
+                1.0.2.0\t1.0.2.255\t4200000001\tUS\tPRIVATE\n\
+                1.0.3.0\t1.0.3.255\t65546\tUS\tDOCUMENTATION";
+    let map = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let info = map.lookup("1.0.0.1".parse().unwrap()).unwrap();
+    assert_eq!(info.asn, Asn::new(13335));
+    assert_eq!(info.asn, "AS13335".parse::<Asn>().unwrap());
+    assert_eq!(info.asn.kind(), AsnKind::Public);
+    assert_eq!(
+        map.lookup_owned("1.0.0.1".parse().unwrap()).unwrap().asn,
+        13335
+    );
+
+    let kind = |ip: &str| map.lookup(ip.parse().unwrap()).unwrap().asn.kind();
+    assert_eq!(kind("1.0.1.1"), AsnKind::Reserved);
+    assert_eq!(kind("1.0.2.1"), AsnKind::Private);
+    assert_eq!(kind("1.0.3.1"), AsnKind::Documentation);
+
+    let info = map.lookup("1.0.3.1".parse().unwrap()).unwrap();
+    assert_eq!(info.asn.display(AsnNotation::Dot).to_string(), "1.10");
+    assert_eq!(format!("{:#}", info.asn), "AS65546");
+}
+
+#[test]
+fn test_classify_explains_missing_addresses() {
+    let map = Builder::new()
+        .with_source(TEST_DATA.as_bytes())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    for (ip, purpose) in [
+        ("10.1.2.3", SpecialPurpose::Private),
+        ("100.64.0.1", SpecialPurpose::SharedAddress),
+        ("127.0.0.1", SpecialPurpose::Loopback),
+        ("203.0.113.7", SpecialPurpose::Documentation),
+        ("224.0.0.251", SpecialPurpose::Multicast),
+        ("fe80::1", SpecialPurpose::LinkLocal),
+        ("2001:db8::1", SpecialPurpose::Documentation),
+    ] {
+        let ip = ip.parse().unwrap();
+        assert!(map.lookup(ip).is_none(), "{ip}");
+        assert_eq!(map.classify(ip).map(|range| range.purpose()), Some(purpose));
+    }
+
+    // Ordinary addresses have no category, whether or not they are found.
+    assert!(map.classify("1.0.0.1".parse().unwrap()).is_none());
+    assert!(map.classify("9.9.9.9".parse().unwrap()).is_none());
+}
+
+#[test]
+fn test_lookup_embedded() {
+    let data = "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n\
+                2002::\t2002:ffff:ffff:ffff:ffff:ffff:ffff:ffff\t6939\tUS\tHURRICANE";
+    let map = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    for (ip, embedding) in [
+        ("::ffff:1.0.0.1", Ipv4Embedding::Mapped),
+        ("64:ff9b::1.0.0.1", Ipv4Embedding::Nat64),
+        ("2002:100:1::1", Ipv4Embedding::SixToFour),
+        (
+            "2001:0:4136:e378:8000:63bf:feff:fffe",
+            Ipv4Embedding::Teredo,
+        ),
+    ] {
+        let ip = ip.parse().unwrap();
+        let result = map.lookup_embedded(ip).unwrap();
+        assert_eq!(result.embedding, Some(embedding), "{ip}");
+        assert_eq!(result.ip, "1.0.0.1".parse::<IpAddr>().unwrap());
+        assert_eq!(result.info.asn, 13335);
+    }
+
+    // Without an embedded match, the address itself is looked up.
+    let ip = "2002:900:1::1".parse().unwrap();
+    let result = map.lookup_embedded(ip).unwrap();
+    assert_eq!(result.embedding, None);
+    assert_eq!(result.ip, ip);
+    assert_eq!(result.info.asn, 6939);
+
+    let result = map.lookup_embedded("1.0.0.1".parse().unwrap()).unwrap();
+    assert_eq!(result.embedding, None);
+    assert!(map
+        .lookup_embedded("::ffff:9.9.9.9".parse().unwrap())
+        .is_none());
+
+    // Plain lookups are unchanged.
+    assert!(map.lookup("::ffff:1.0.0.1".parse().unwrap()).is_none());
+}
+
+#[test]
+fn test_lookup_organization_parts() {
+    let data = "1.0.0.0\t1.0.0.255\t45370\tKR\tBROADBANDIDC-AS-KR BROADBANDIDC\n\
+                1.0.1.0\t1.0.1.255\t15169\tUS\tGOOGLE - Google LLC\n\
+                1.0.2.0\t1.0.2.255\t0\tNone\tNot routed";
+    let map = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let info = map.lookup("1.0.0.1".parse().unwrap()).unwrap();
+    let parts = info.organization_parts;
+    assert_eq!(parts.handle, Some("BROADBANDIDC-AS-KR"));
+    assert_eq!(parts.description, "BROADBANDIDC");
+    assert_eq!(parts.country.map(|country| country.code()), Some("KR"));
+
+    let owned = map.lookup_owned("1.0.1.1".parse().unwrap()).unwrap();
+    assert_eq!(owned.organization_parts.handle.as_deref(), Some("GOOGLE"));
+    assert_eq!(owned.organization_parts.description, "Google LLC");
+    assert_eq!(owned.organization_parts.country, None);
+    assert_eq!(owned.organization_parts.name(), "Google LLC");
+
+    let unrouted = map.lookup("1.0.2.1".parse().unwrap()).unwrap();
+    assert_eq!(unrouted.organization_parts.handle, None);
+    assert_eq!(unrouted.organization_parts.name(), "Not routed");
+}
+
+#[test]
+fn test_builder_strict_mode_rejects_unassigned_country_codes() {
+    let data = "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n\
+                1.0.1.0\t1.0.1.255\t0\tNone\tNot routed\n\
+                1.0.2.0\t1.0.2.255\t38040\tEU\tGTELECOM";
+    let err = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .strict()
+        .build()
+        .unwrap_err();
+    match err {
+        Error::Parse {
+            line_number, kind, ..
+        } => {
+            assert_eq!(line_number, 3);
+            assert_eq!(
+                kind,
+                ParseErrorKind::InvalidCountryCode {
+                    value: "EU".to_string()
+                }
+            );
+        }
+        other => panic!("Expected a Parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_builder_resilient_mode_keeps_unassigned_country_codes() {
+    let data = "1.0.0.0\t1.0.0.255\t38040\tEU\tGTELECOM\n\
+                1.0.1.0\t1.0.1.255\t13335\t1x\tCLOUDFLARENET";
+    let map = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .on_warning(|warning| panic!("unexpected warning: {warning:?}"))
+        .build()
+        .unwrap();
+    let eu = map.lookup("1.0.0.1".parse().unwrap()).unwrap();
+    assert_eq!(eu.country_code, "EU");
+    assert_eq!(eu.country(), None);
+    let other = map.lookup("1.0.1.1".parse().unwrap()).unwrap();
+    assert_eq!(other.country_code, "1X");
+}
+
 #[cfg(feature = "serde")]
 #[test]
 fn test_asn_info_serde() {
@@ -330,3 +697,27 @@ fn test_asn_info_serde() {
 
     assert_eq!(info, deserialized);
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_asn_info_serde_organization_parts() {
+    let data = "1.0.0.0\t1.0.0.255\t45370\tKR\tBROADBANDIDC-AS-KR BROADBANDIDC";
+    let map = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .build()
+        .unwrap();
+    let info = map.lookup_owned("1.0.0.1".parse().unwrap()).unwrap();
+
+    let value = serde_json::to_value(&info).unwrap();
+    assert_eq!(
+        value["organization_parts"],
+        serde_json::json!({
+            "handle": "BROADBANDIDC-AS-KR",
+            "description": "BROADBANDIDC",
+            "country": "KR",
+        })
+    );
+    let deserialized: ip2asn::AsnInfo = serde_json::from_value(value).unwrap();
+    assert_eq!(deserialized.organization_parts, info.organization_parts);
+}
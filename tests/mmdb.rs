@@ -0,0 +1,323 @@
+//! Integration tests for writing and reading MaxMind DB files.
+#![cfg(feature = "mmdb")]
+
+use ip2asn::mmdb::{MmdbFields, MmdbOptions};
+use ip2asn::{Builder, Error, IpAsnMap, ParseErrorKind};
+use maxminddb::{geoip2, Reader};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+
+fn write(map: &IpAsnMap, options: &MmdbOptions) -> Reader<Vec<u8>> {
+    let mut output = Vec::new();
+    map.write_mmdb_with(&mut output, options).unwrap();
+    Reader::from_source(output).unwrap()
+}
+
+#[test]
+fn test_write_mmdb_round_trip() {
+    let map = Builder::new()
+        .from_path("testdata/testdata-small-ip2asn.tsv")
+        .unwrap()
+        .strict()
+        .build()
+        .unwrap();
+    let reader = write(&map, &MmdbOptions::new());
+
+    let source = std::fs::read_to_string("testdata/testdata-small-ip2asn.tsv").unwrap();
+    for line in source.lines() {
+        let mut fields = line.split('\t');
+        for field in [fields.next(), fields.next()] {
+            let ip: IpAddr = field.unwrap().parse().unwrap();
+            let expected = map.lookup(ip).unwrap();
+
+            let asn: geoip2::Asn = reader.lookup(ip).unwrap();
+            assert_eq!(
+                asn.autonomous_system_number,
+                Some(expected.asn.get()),
+                "{ip}"
+            );
+            assert_eq!(
+                asn.autonomous_system_organization,
+                Some(expected.organization)
+            );
+
+            let country: geoip2::Country = reader.lookup(ip).unwrap();
+            let iso_code = country.country.and_then(|c| c.iso_code);
+            if expected.country_code == "ZZ" {
+                assert_eq!(iso_code, None);
+            } else {
+                assert_eq!(iso_code, Some(expected.country_code));
+            }
+        }
+    }
+
+    let missing: Result<geoip2::Asn, _> = reader.lookup("192.0.2.1".parse().unwrap());
+    assert!(missing.is_err());
+}
+
+#[test]
+fn test_write_mmdb_nested_networks() {
+    let data = "10.0.0.0\t10.0.255.255\t64496\tUS\tOUTER\n\
+                10.0.16.0\t10.0.31.255\t64497\tCA\tINNER\n\
+                2001:db8::\t2001:db8:ffff:ffff:ffff:ffff:ffff:ffff\t64498\tNone\tDOC\n";
+    let map = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .build()
+        .unwrap();
+    let reader = write(&map, &MmdbOptions::new());
+
+    for (ip, asn, prefix_len) in [
+        ("10.0.0.1", 64496, 20),
+        ("10.0.20.1", 64497, 20),
+        ("10.0.200.1", 64496, 17),
+        ("2001:db8::1", 64498, 32),
+    ] {
+        let ip: IpAddr = ip.parse().unwrap();
+        let (record, len): (geoip2::Asn, usize) = reader.lookup_prefix(ip).unwrap();
+        assert_eq!(record.autonomous_system_number, Some(asn), "{ip}");
+        assert_eq!(len, prefix_len, "{ip}");
+    }
+}
+
+#[test]
+fn test_write_mmdb_metadata() {
+    let map = Builder::new()
+        .with_source("1.0.0.0\t1.0.0.255\t13335\tAU\tCLOUDFLARENET".as_bytes())
+        .unwrap()
+        .build()
+        .unwrap();
+    let options = MmdbOptions::new()
+        .database_type("iptoasn-ASN")
+        .description("test data")
+        .build_epoch(1_700_000_000);
+    let reader = write(&map, &options);
+
+    let metadata = &reader.metadata;
+    assert_eq!(metadata.binary_format_major_version, 2);
+    assert_eq!(metadata.database_type, "iptoasn-ASN");
+    assert_eq!(metadata.description["en"], "test data");
+    assert_eq!(metadata.build_epoch, 1_700_000_000);
+    assert_eq!(metadata.ip_version, 6);
+    assert_eq!(metadata.record_size, 24);
+
+    // The output is reproducible once the build epoch is fixed.
+    let (mut first, mut second) = (Vec::new(), Vec::new());
+    map.write_mmdb_with(&mut first, &options).unwrap();
+    map.write_mmdb_with(&mut second, &options).unwrap();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_write_mmdb_empty_map() {
+    let reader = write(&IpAsnMap::new(), &MmdbOptions::new());
+    assert_eq!(reader.metadata.node_count, 1);
+    let missing: Result<geoip2::Asn, _> = reader.lookup("1.1.1.1".parse().unwrap());
+    assert!(missing.is_err());
+}
+
+#[test]
+fn test_from_mmdb_round_trip() {
+    let map = Builder::new()
+        .from_path("testdata/testdata-small-ip2asn.tsv")
+        .unwrap()
+        .build()
+        .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ip2asn.mmdb");
+    map.write_mmdb(std::fs::File::create(&path).unwrap())
+        .unwrap();
+
+    let reloaded = Builder::new()
+        .from_mmdb(&path)
+        .unwrap()
+        .strict()
+        .build()
+        .unwrap();
+
+    let source = std::fs::read_to_string("testdata/testdata-small-ip2asn.tsv").unwrap();
+    for line in source.lines() {
+        let mut fields = line.split('\t');
+        for field in [fields.next(), fields.next()] {
+            let ip: IpAddr = field.unwrap().parse().unwrap();
+            assert_eq!(map.lookup_owned(ip), reloaded.lookup_owned(ip), "{ip}");
+        }
+    }
+    assert!(reloaded.lookup("192.0.2.1".parse().unwrap()).is_none());
+}
+
+/// Encodes a string in the MaxMind DB data format.
+fn string(value: &str) -> Vec<u8> {
+    assert!(value.len() < 29);
+    let mut buf = vec![0x40 | value.len() as u8];
+    buf.extend_from_slice(value.as_bytes());
+    buf
+}
+
+/// Builds an IPv4 database with one node, whose left and right records point
+/// at the two data section values given.
+fn ipv4_database(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let node_count = 1u32;
+    let left_value = node_count + 16;
+    let right_value = left_value + left.len() as u32;
+    let mut db = Vec::new();
+    db.extend_from_slice(&left_value.to_be_bytes()[1..]);
+    db.extend_from_slice(&right_value.to_be_bytes()[1..]);
+    db.extend_from_slice(&[0; 16]);
+    db.extend_from_slice(left);
+    db.extend_from_slice(right);
+    db.extend_from_slice(b"\xAB\xCD\xEFMaxMind.com");
+    db.push(0xe0 | 6);
+    db.extend(string("binary_format_major_version"));
+    db.extend([0xa1, 0x02]);
+    db.extend(string("binary_format_minor_version"));
+    db.push(0xa0);
+    db.extend(string("database_type"));
+    db.extend(string("Test-ISP"));
+    db.extend(string("ip_version"));
+    db.extend([0xa1, 0x04]);
+    db.extend(string("node_count"));
+    db.extend([0xc1, node_count as u8]);
+    db.extend(string("record_size"));
+    db.extend([0xa1, 24]);
+    db
+}
+
+#[test]
+fn test_from_mmdb_custom_fields() {
+    // { "asn": "AS64496", "isp": "EXAMPLE", "registered_country": { "iso_code": "NL" } }
+    let mut left = vec![0xe3];
+    left.extend(string("asn"));
+    left.extend(string("AS64496"));
+    left.extend(string("isp"));
+    let isp_offset = left.len();
+    left.extend(string("EXAMPLE"));
+    left.extend(string("registered_country"));
+    left.push(0xe1);
+    left.extend(string("iso_code"));
+    left.extend(string("NL"));
+
+    // { "asn": 64497, "isp": <pointer to "EXAMPLE"> }
+    let mut right = vec![0xe2];
+    right.extend(string("asn"));
+    right.extend([0xc2, 0xfb, 0xf1]);
+    right.extend(string("isp"));
+    right.extend([0x20, isp_offset as u8]);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("isp.mmdb");
+    std::fs::write(&path, ipv4_database(&left, &right)).unwrap();
+
+    let map = Builder::new()
+        .from_mmdb(&path)
+        .unwrap()
+        .mmdb_fields(
+            MmdbFields::new()
+                .asn("asn")
+                .organization("isp")
+                .country("registered_country.iso_code"),
+        )
+        .strict()
+        .build()
+        .unwrap();
+
+    let info = map.lookup("10.0.0.1".parse().unwrap()).unwrap();
+    assert_eq!(info.network.to_string(), "0.0.0.0/1");
+    assert_eq!(info.asn, 64496);
+    assert_eq!(info.organization, "EXAMPLE");
+    assert_eq!(info.country_code, "NL");
+
+    let info = map.lookup("200.0.0.1".parse().unwrap()).unwrap();
+    assert_eq!(info.network.to_string(), "128.0.0.0/1");
+    assert_eq!(info.asn, 64497);
+    assert_eq!(info.organization, "EXAMPLE");
+    assert_eq!(info.country_code, "ZZ");
+}
+
+#[test]
+fn test_from_mmdb_asn_notations() {
+    let mut left = vec![0xe1];
+    left.extend(string("asn"));
+    left.extend(string("as13335"));
+    let mut right = vec![0xe1];
+    right.extend(string("asn"));
+    right.extend(string("1.10"));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("isp.mmdb");
+    std::fs::write(&path, ipv4_database(&left, &right)).unwrap();
+
+    let map = Builder::new()
+        .from_mmdb(&path)
+        .unwrap()
+        .mmdb_fields(MmdbFields::new().asn("asn"))
+        .strict()
+        .build()
+        .unwrap();
+    assert_eq!(map.lookup("10.0.0.1".parse().unwrap()).unwrap().asn, 13335);
+    assert_eq!(map.lookup("200.0.0.1".parse().unwrap()).unwrap().asn, 65546);
+}
+
+#[test]
+fn test_from_mmdb_missing_asn() {
+    let mut left = vec![0xe1];
+    left.extend(string("asn"));
+    left.extend([0xc1, 0x01]);
+    let mut right = vec![0xe1];
+    right.extend(string("isp"));
+    right.extend(string("NO-ASN"));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("isp.mmdb");
+    std::fs::write(&path, ipv4_database(&left, &right)).unwrap();
+    let fields = MmdbFields::new().asn("asn");
+
+    let result = Builder::new()
+        .from_mmdb(&path)
+        .unwrap()
+        .mmdb_fields(fields.clone())
+        .strict()
+        .build();
+    match result {
+        Err(Error::Parse {
+            line_number,
+            line_content,
+            kind,
+        }) => {
+            assert_eq!(line_number, 2);
+            assert_eq!(line_content, "128.0.0.0/1");
+            assert_eq!(
+                kind,
+                ParseErrorKind::MissingField {
+                    field: "asn".to_string()
+                }
+            );
+        }
+        other => panic!("Expected a Parse error, got {other:?}"),
+    }
+
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let warnings_clone = warnings.clone();
+    let map = Builder::new()
+        .from_mmdb(&path)
+        .unwrap()
+        .mmdb_fields(fields)
+        .on_warning(move |warning| warnings_clone.lock().unwrap().push(warning.to_string()))
+        .build()
+        .unwrap();
+    assert_eq!(map.lookup("1.0.0.1".parse().unwrap()).unwrap().asn, 1);
+    assert!(map.lookup("200.0.0.1".parse().unwrap()).is_none());
+    assert_eq!(warnings.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn test_from_mmdb_invalid_file() {
+    let result = Builder::new()
+        .from_mmdb("testdata/testdata-small-ip2asn.tsv")
+        .unwrap()
+        .build();
+    match result {
+        Err(Error::Io(err)) => assert_eq!(err.kind(), std::io::ErrorKind::InvalidData),
+        other => panic!("Expected an Io error, got {other:?}"),
+    }
+}
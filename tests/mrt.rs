@@ -0,0 +1,152 @@
+//! Integration tests for the MRT `TABLE_DUMP_V2` input format.
+#![cfg(feature = "mrt")]
+
+use ip2asn::asn::Asn;
+use ip2asn::mrt::{MrtReader, PathOrigin};
+use ip2asn::{Builder, Error, InputFormat, IpAsnMap, MultiOriginPolicy, ParseErrorKind};
+use ip_network::IpNetwork;
+use rstest::rstest;
+use std::fs::File;
+
+fn build(path: &str, policy: MultiOriginPolicy) -> Result<IpAsnMap, Error> {
+    Builder::new()
+        .from_path(path)?
+        .format(InputFormat::Mrt)
+        .multi_origin_policy(policy)
+        .build()
+}
+
+#[test]
+fn test_mrt_reader() {
+    let file = File::open("testdata/testdata-small-rib.mrt").unwrap();
+    let mut reader = MrtReader::new(file);
+
+    let prefix = reader.read_prefix().unwrap().unwrap().unwrap();
+    assert_eq!(reader.record_number(), 2);
+    assert_eq!(prefix.network, "1.0.0.0/24".parse::<IpNetwork>().unwrap());
+    assert_eq!(
+        prefix.origins,
+        vec![
+            PathOrigin::Asn(Asn::new(13335)),
+            PathOrigin::Asn(Asn::new(13335))
+        ]
+    );
+
+    let mut prefixes = vec![prefix];
+    while let Some(result) = reader.read_prefix().unwrap() {
+        prefixes.push(result.unwrap());
+    }
+    assert_eq!(prefixes.len(), 6);
+    // The BGP4MP record is skipped, but still counted.
+    assert_eq!(reader.record_number(), 8);
+    assert_eq!(
+        prefixes[3].origins,
+        vec![PathOrigin::AsSet(vec![Asn::new(134548), Asn::new(134549)])]
+    );
+    // An empty AS_PATH is originated by the peer itself.
+    assert_eq!(prefixes[4].origins, vec![PathOrigin::Asn(Asn::new(64501))]);
+}
+
+#[rstest]
+#[case("testdata/testdata-small-rib.mrt")]
+#[case("testdata/testdata-small-rib.mrt.gz")]
+#[case("testdata/testdata-small-rib.mrt.bz2")]
+fn test_builder_mrt(#[case] path: &str) {
+    let map = build(path, MultiOriginPolicy::MostCommon).unwrap();
+
+    let info = map.lookup("1.0.0.1".parse().unwrap()).unwrap();
+    assert_eq!(info.network, "1.0.0.0/24".parse::<IpNetwork>().unwrap());
+    assert_eq!(info.asn, 13335);
+    assert_eq!(info.country_code, "ZZ");
+    assert_eq!(info.organization, "");
+
+    assert_eq!(map.lookup("36.0.1.1".parse().unwrap()).unwrap().asn, 4837);
+    assert_eq!(
+        map.lookup("45.64.0.1".parse().unwrap()).unwrap().asn,
+        134548
+    );
+    assert_eq!(map.lookup("192.0.2.1".parse().unwrap()).unwrap().asn, 64501);
+    assert_eq!(
+        map.lookup("2001:4860::8888".parse().unwrap()).unwrap().asn,
+        15169
+    );
+}
+
+#[rstest]
+#[case(MultiOriginPolicy::First, 4134)]
+#[case(MultiOriginPolicy::Lowest, 4134)]
+#[case(MultiOriginPolicy::Highest, 4837)]
+#[case(MultiOriginPolicy::MostCommon, 4837)]
+fn test_builder_mrt_origin_policy(#[case] policy: MultiOriginPolicy, #[case] expected: u32) {
+    let map = build("testdata/testdata-small-rib.mrt", policy).unwrap();
+    assert_eq!(
+        map.lookup("36.0.1.1".parse().unwrap()).unwrap().asn,
+        expected
+    );
+}
+
+#[test]
+fn test_builder_mrt_reject_strict() {
+    let result = Builder::new()
+        .from_path("testdata/testdata-small-rib.mrt")
+        .unwrap()
+        .format(InputFormat::Mrt)
+        .multi_origin_policy(MultiOriginPolicy::Reject)
+        .strict()
+        .build();
+
+    match result {
+        Err(Error::Parse {
+            line_number,
+            line_content,
+            kind,
+        }) => {
+            assert_eq!(line_number, 4);
+            assert_eq!(line_content, "36.0.0.0/22");
+            assert_eq!(
+                kind,
+                ParseErrorKind::MultipleOrigins {
+                    value: "4134_4837_4837".to_string()
+                }
+            );
+        }
+        _ => panic!("Expected a Parse error"),
+    }
+}
+
+#[test]
+fn test_builder_mrt_truncated() {
+    let data = std::fs::read("testdata/testdata-small-rib.mrt").unwrap();
+    let result = Builder::new()
+        .with_source(&data[..data.len() - 3])
+        .unwrap()
+        .format(InputFormat::Mrt)
+        .build();
+    match result {
+        Err(Error::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof),
+        _ => panic!("Expected an I/O error"),
+    }
+}
+
+#[test]
+fn test_builder_mrt_invalid_record_content() {
+    // A RIB_IPV4_UNICAST record whose body is too short to decode.
+    let data = [0, 0, 0, 0, 0, 13, 0, 2, 0, 0, 0, 1, 0];
+    let result = Builder::new()
+        .with_source(&data[..])
+        .unwrap()
+        .format(InputFormat::Mrt)
+        .strict()
+        .build();
+    match result {
+        Err(Error::Parse {
+            line_number,
+            line_content,
+            kind: ParseErrorKind::InvalidMrtRecord { .. },
+        }) => {
+            assert_eq!(line_number, 1);
+            assert_eq!(line_content, "MRT record 1 subtype 2");
+        }
+        _ => panic!("Expected a Parse error"),
+    }
+}
@@ -0,0 +1,107 @@
+//! Integration tests for overlap detection during a build.
+#![cfg(feature = "std")]
+
+use ip2asn::{BuildReport, Builder, Error, IpAsnMap, OverlapAction, OverlapWinner, Warning};
+use ip_network::IpNetwork;
+use rstest::rstest;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+
+const OVERLAPPING_DATA: &str = "\
+1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET
+1.0.0.128\t1.0.0.191\t64496\tAU\tNESTED
+1.0.0.192\t1.0.1.255\t64497\tJP\tSTRADDLING
+8.8.8.0\t8.8.8.255\t15169\tUS\tGOOGLE
+";
+
+fn build(action: OverlapAction, winner: OverlapWinner) -> (IpAsnMap, BuildReport, Vec<Warning>) {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sink = warnings.clone();
+    let (map, report) = Builder::new()
+        .with_source(OVERLAPPING_DATA.as_bytes())
+        .unwrap()
+        .on_overlap(action)
+        .overlap_winner(winner)
+        .on_warning(move |warning| sink.lock().unwrap().push(warning))
+        .build_with_report()
+        .unwrap();
+    let warnings = warnings.lock().unwrap().clone();
+    (map, report, warnings)
+}
+
+fn asn_at(map: &IpAsnMap, ip: &str) -> Option<u32> {
+    map.lookup(ip.parse::<IpAddr>().unwrap())
+        .map(|info| info.asn.get())
+}
+
+#[test]
+fn test_overlap_warnings() {
+    let (_, report, warnings) = build(OverlapAction::Warn, OverlapWinner::Last);
+    assert_eq!(report.overlaps, 2);
+    assert_eq!(report.records_inserted, 4);
+
+    let pairs: Vec<_> = warnings
+        .iter()
+        .map(|warning| match warning {
+            Warning::Overlap { existing, new } => (existing.line_number, new.line_number),
+            other => panic!("Expected an Overlap warning, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(pairs, vec![(1, 2), (1, 3)]);
+
+    let Warning::Overlap { existing, new } = &warnings[0] else {
+        unreachable!();
+    };
+    assert_eq!(existing.start_ip, "1.0.0.0".parse::<IpAddr>().unwrap());
+    assert_eq!(existing.end_ip, "1.0.0.255".parse::<IpAddr>().unwrap());
+    assert_eq!(existing.asn, 13335);
+    assert_eq!(existing.organization, "CLOUDFLARENET");
+    assert_eq!(new.asn, 64496);
+    assert_eq!(new.country_code, "AU");
+}
+
+#[test]
+fn test_overlap_ignored() {
+    let (_, report, warnings) = build(OverlapAction::Ignore, OverlapWinner::Last);
+    assert_eq!(report.overlaps, 0);
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_overlap_error() {
+    let err = Builder::new()
+        .with_source(OVERLAPPING_DATA.as_bytes())
+        .unwrap()
+        .on_overlap(OverlapAction::Error)
+        .build()
+        .unwrap_err();
+    match err {
+        Error::Overlap { existing, new } => {
+            assert_eq!(existing.line_number, 1);
+            assert_eq!(new.line_number, 2);
+        }
+        other => panic!("Expected an Overlap error, got {other:?}"),
+    }
+}
+
+#[rstest]
+#[case(OverlapWinner::First, [13335, 13335, 13335, 64497])]
+#[case(OverlapWinner::Last, [13335, 64496, 64497, 64497])]
+#[case(OverlapWinner::MostSpecific, [13335, 64496, 13335, 64497])]
+fn test_overlap_winner(#[case] winner: OverlapWinner, #[case] expected: [u32; 4]) {
+    let (map, _, _) = build(OverlapAction::Warn, winner);
+    let actual =
+        ["1.0.0.1", "1.0.0.130", "1.0.0.200", "1.0.1.1"].map(|ip| asn_at(&map, ip).unwrap());
+    assert_eq!(actual, expected);
+    assert_eq!(asn_at(&map, "8.8.8.8"), Some(15169));
+}
+
+#[test]
+fn test_overlap_winner_network() {
+    let (map, report, _) = build(OverlapAction::Ignore, OverlapWinner::MostSpecific);
+    let info = map.lookup("1.0.0.100".parse().unwrap()).unwrap();
+    assert_eq!(info.network, "1.0.0.0/25".parse::<IpNetwork>().unwrap());
+    // 1.0.0.0/25 and 1.0.0.192/26 from the first record, 1.0.0.128/26 from
+    // the nested one, 1.0.1.0/24 from the straddling one, and 8.8.8.0/24.
+    assert_eq!(report.cidrs_generated, 5);
+}
@@ -1,5 +1,6 @@
 //! Integration tests for the line parser.
 
+use ip2asn::asn::Asn;
 use ip2asn::parser::{parse_line, ParsedLine};
 use std::net::{Ipv4Addr, Ipv6Addr};
 
@@ -13,7 +14,7 @@ fn test_parse_line_happy_path() {
         ParsedLine {
             start_ip: Ipv4Addr::new(1, 0, 0, 0).into(),
             end_ip: Ipv4Addr::new(1, 0, 0, 255).into(),
-            asn: 13335,
+            asn: Asn::new(13335),
             country_code: [b'U', b'S'],
             organization: "CLOUDFLARENET",
         }
@@ -58,6 +59,19 @@ fn test_parse_line_invalid_asn() {
     ));
 }
 
+#[test]
+fn test_parse_line_rejects_asn_notations() {
+    // The format only uses the plain notation, so prefixed and `asdot` ASNs
+    // are malformed even though `Asn` can parse them.
+    for asn in ["AS13335", "0.13335"] {
+        let line = format!("1.0.0.0\t1.0.0.255\t{asn}\tUS\tCLOUDFLARENET");
+        assert!(matches!(
+            parse_line(&line),
+            Err(ip2asn::ParseErrorKind::InvalidAsnNumber { .. })
+        ));
+    }
+}
+
 #[test]
 fn test_parse_line_ip_family_mismatch() {
     let line = "1.0.0.0\t::1\t13335\tUS\tCLOUDFLARENET";
@@ -85,6 +99,13 @@ fn test_country_code_normalization() {
     assert_eq!(result.country_code, [b'Z', b'Z']);
 }
 
+#[test]
+fn test_country_code_case_normalization() {
+    let line = "1.0.0.0\t1.0.0.255\t13335\tuS\tCLOUDFLARENET";
+    let result = parse_line(line).unwrap();
+    assert_eq!(result.country_code, *b"US");
+}
+
 #[test]
 fn test_parse_line_from_real_data() {
     // Case 1: Standard IPv4
@@ -148,3 +169,31 @@ fn test_parse_line_malformed_country_code() {
         Err(ip2asn::ParseErrorKind::InvalidCountryCode { .. })
     ));
 }
+
+#[test]
+fn test_parse_line_keeps_unassigned_country_codes() {
+    // Only strict builds check codes against ISO 3166-1.
+    let line = "1.0.0.0\t1.0.0.255\t13335\tu1\tCLOUDFLARENET";
+    assert_eq!(parse_line(line).unwrap().country_code, *b"U1");
+}
+
+#[test]
+fn test_parse_line_counts_extra_columns() {
+    let line = "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\textra";
+    assert_eq!(
+        parse_line(line),
+        Err(ip2asn::ParseErrorKind::IncorrectColumnCount {
+            expected: 5,
+            found: 6
+        })
+    );
+}
+
+#[test]
+fn test_parse_line_rejects_leading_zero_octets() {
+    let line = "01.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET";
+    assert!(matches!(
+        parse_line(line),
+        Err(ip2asn::ParseErrorKind::InvalidIpAddress { .. })
+    ));
+}
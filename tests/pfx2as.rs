@@ -0,0 +1,151 @@
+//! Integration tests for the CAIDA prefix-to-AS input format.
+#![cfg(feature = "std")]
+
+use ip2asn::asn::Asn;
+use ip2asn::pfx2as::{parse_line, Pfx2AsLine};
+use ip2asn::{Builder, Error, InputFormat, MultiOriginPolicy, ParseErrorKind, Warning};
+use ip_network::IpNetwork;
+use std::sync::{Arc, Mutex};
+
+const PFX2AS_PATH: &str = "testdata/testdata-small.pfx2as";
+
+#[test]
+fn test_parse_line_happy_path() {
+    let result = parse_line("1.0.0.0\t24\t13335", MultiOriginPolicy::Reject).unwrap();
+    assert_eq!(
+        result,
+        Pfx2AsLine {
+            network: "1.0.0.0/24".parse().unwrap(),
+            asn: Asn::new(13335),
+        }
+    );
+
+    let result = parse_line("2001:4860::\t32\t15169", MultiOriginPolicy::Reject).unwrap();
+    assert_eq!(
+        result.network,
+        "2001:4860::/32".parse::<IpNetwork>().unwrap()
+    );
+    assert_eq!(result.asn, 15169);
+}
+
+#[test]
+fn test_parse_line_malformed() {
+    assert!(matches!(
+        parse_line("1.0.0.0\t24", MultiOriginPolicy::First),
+        Err(ParseErrorKind::IncorrectColumnCount {
+            expected: 3,
+            found: 2
+        })
+    ));
+    assert!(matches!(
+        parse_line("1.0.0.0\t33\t13335", MultiOriginPolicy::First),
+        Err(ParseErrorKind::InvalidNetwork { .. })
+    ));
+    assert!(matches!(
+        parse_line("1.0.0.1\t24\t13335", MultiOriginPolicy::First),
+        Err(ParseErrorKind::InvalidNetwork { .. })
+    ));
+    assert!(matches!(
+        parse_line("1.0.0.0\t24\tAS13335", MultiOriginPolicy::First),
+        Err(ParseErrorKind::InvalidAsnNumber { .. })
+    ));
+}
+
+#[test]
+fn test_parse_line_multiple_origins() {
+    let line = "103.4.96.0\t22\t58511_17660,4826";
+    assert_eq!(
+        parse_line(line, MultiOriginPolicy::First).unwrap().asn,
+        58511
+    );
+    assert_eq!(
+        parse_line(line, MultiOriginPolicy::Lowest).unwrap().asn,
+        4826
+    );
+    assert_eq!(
+        parse_line(line, MultiOriginPolicy::Highest).unwrap().asn,
+        58511
+    );
+    assert_eq!(
+        parse_line(line, MultiOriginPolicy::Reject),
+        Err(ParseErrorKind::MultipleOrigins {
+            value: "58511_17660,4826".to_string()
+        })
+    );
+    assert_eq!(
+        parse_line("45.64.0.0\t24\t134548,134549", MultiOriginPolicy::Reject),
+        Err(ParseErrorKind::AsSetOrigin {
+            value: "134548,134549".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_builder_pfx2as() {
+    let map = Builder::new()
+        .from_path(PFX2AS_PATH)
+        .unwrap()
+        .format(InputFormat::Pfx2As)
+        .multi_origin_policy(MultiOriginPolicy::Lowest)
+        .strict()
+        .build()
+        .unwrap();
+
+    let info = map.lookup("1.0.5.1".parse().unwrap()).unwrap();
+    assert_eq!(info.network, "1.0.4.0/22".parse::<IpNetwork>().unwrap());
+    assert_eq!(info.asn, 38803);
+    assert_eq!(info.country_code, "ZZ");
+    assert_eq!(info.organization, "");
+
+    let info = map.lookup("36.0.1.1".parse().unwrap()).unwrap();
+    assert_eq!(info.asn, 4134);
+
+    let info = map.lookup("2606:4700::1".parse().unwrap()).unwrap();
+    assert_eq!(info.asn, 13335);
+}
+
+#[test]
+fn test_builder_pfx2as_reject_strict() {
+    let result = Builder::new()
+        .from_path(PFX2AS_PATH)
+        .unwrap()
+        .format(InputFormat::Pfx2As)
+        .multi_origin_policy(MultiOriginPolicy::Reject)
+        .strict()
+        .build();
+
+    match result {
+        Err(Error::Parse {
+            line_number, kind, ..
+        }) => {
+            assert_eq!(line_number, 4);
+            assert_eq!(
+                kind,
+                ParseErrorKind::MultipleOrigins {
+                    value: "4134_4837".to_string()
+                }
+            );
+        }
+        _ => panic!("Expected a Parse error"),
+    }
+}
+
+#[test]
+fn test_builder_pfx2as_reject_resilient() {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let warnings_clone = warnings.clone();
+
+    let map = Builder::new()
+        .from_path(PFX2AS_PATH)
+        .unwrap()
+        .format(InputFormat::Pfx2As)
+        .multi_origin_policy(MultiOriginPolicy::Reject)
+        .on_warning(move |w: Warning| warnings_clone.lock().unwrap().push(w))
+        .build()
+        .unwrap();
+
+    assert_eq!(warnings.lock().unwrap().len(), 3);
+    assert!(map.lookup("36.0.1.1".parse().unwrap()).is_none());
+    assert!(map.lookup("45.64.0.1".parse().unwrap()).is_none());
+    assert_eq!(map.lookup("8.8.8.8".parse().unwrap()).unwrap().asn, 15169);
+}
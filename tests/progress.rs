@@ -0,0 +1,92 @@
+//! Integration tests for build progress reporting and cancellation.
+#![cfg(feature = "std")]
+
+use ip2asn::{Builder, CancellationToken, Error, Progress};
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+fn collect_progress(builder: Builder<'_>, interval: usize) -> Vec<Progress> {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink = reports.clone();
+    builder
+        .progress_interval(interval)
+        .on_progress(move |progress| sink.lock().unwrap().push(progress))
+        .build()
+        .unwrap();
+    let reports = reports.lock().unwrap();
+    reports.clone()
+}
+
+#[test]
+fn test_progress_plain_source() {
+    let path = "testdata/testdata-small-ip2asn.tsv";
+    let builder = Builder::new().from_path(path).unwrap();
+    let reports = collect_progress(builder, 5);
+
+    // Every fifth line, plus a final report.
+    assert_eq!(
+        reports.iter().map(|p| p.lines).collect::<Vec<_>>(),
+        vec![5, 10, 15, 20, 22]
+    );
+    assert!(reports.windows(2).all(|w| w[0].records <= w[1].records));
+    assert!(reports
+        .windows(2)
+        .all(|w| w[0].bytes_read <= w[1].bytes_read));
+
+    let last = reports.last().unwrap();
+    assert_eq!(last.records, 22);
+    assert_eq!(last.bytes_read, fs::metadata(path).unwrap().len());
+    assert_eq!(last.bytes_decompressed, None);
+}
+
+#[test]
+fn test_progress_compressed_source() {
+    let path = "testdata/testdata-small-ip2asn.tsv.gz";
+    let builder = Builder::new().from_path(path).unwrap();
+    let reports = collect_progress(builder, 1000);
+
+    // Only the final report is made.
+    assert_eq!(reports.len(), 1);
+    let last = reports[0];
+    assert_eq!(last.lines, 22);
+    assert_eq!(last.bytes_read, fs::metadata(path).unwrap().len());
+    assert_eq!(
+        last.bytes_decompressed,
+        Some(
+            fs::metadata("testdata/testdata-small-ip2asn.tsv")
+                .unwrap()
+                .len()
+        )
+    );
+}
+
+#[test]
+fn test_cancellation() {
+    let token = CancellationToken::new();
+    let canceller = token.clone();
+    let result = Builder::new()
+        .from_path("testdata/testdata-small-ip2asn.tsv")
+        .unwrap()
+        .progress_interval(3)
+        .on_progress(move |progress| {
+            if progress.lines >= 6 {
+                canceller.cancel();
+            }
+        })
+        .cancellation_token(token.clone())
+        .build();
+
+    assert!(matches!(result, Err(Error::Cancelled)));
+    assert!(token.is_cancelled());
+}
+
+#[test]
+fn test_uncancelled_token() {
+    let map = Builder::new()
+        .from_path("testdata/testdata-small-ip2asn.tsv")
+        .unwrap()
+        .cancellation_token(CancellationToken::new())
+        .build()
+        .unwrap();
+    assert!(map.lookup("154.16.226.100".parse().unwrap()).is_some());
+}
@@ -0,0 +1,168 @@
+//! Integration tests for RIR delegated-extended statistics.
+#![cfg(feature = "std")]
+
+use ip2asn::asn::Asn;
+use ip2asn::rir::{parse_line, AllocationStatus, DelegatedResource, Registry, RirDelegations};
+use ip2asn::{Builder, Error, ParseErrorKind};
+use ip_network::IpNetwork;
+
+#[test]
+fn test_parse_line() {
+    let delegation = parse_line("apnic|AU|ipv4|1.0.4.0|1024|20110412|allocated|A9192210").unwrap();
+    assert_eq!(
+        delegation.resource,
+        DelegatedResource::Ip {
+            start: "1.0.4.0".parse().unwrap(),
+            end: "1.0.7.255".parse().unwrap(),
+        }
+    );
+    assert_eq!(delegation.info.registry, Registry::Apnic);
+    assert_eq!(delegation.info.country_code, "AU");
+    assert_eq!(delegation.info.status, AllocationStatus::Allocated);
+
+    let delegation = parse_line("ripencc||ipv6|2001:678::|29|20040101|assigned").unwrap();
+    assert_eq!(
+        delegation.resource,
+        DelegatedResource::Ip {
+            start: "2001:678::".parse().unwrap(),
+            end: "2001:67f:ffff:ffff:ffff:ffff:ffff:ffff".parse().unwrap(),
+        }
+    );
+    assert_eq!(delegation.info.registry, Registry::RipeNcc);
+    assert_eq!(delegation.info.country_code, "ZZ");
+
+    let delegation = parse_line("arin|US|asn|1|5|19840101|assigned|ee").unwrap();
+    assert_eq!(
+        delegation.resource,
+        DelegatedResource::Asn {
+            start: Asn::new(1),
+            end: Asn::new(5)
+        }
+    );
+
+    assert!(matches!(
+        parse_line("apnic|AU|ipv4|1.0.0.0|256|20110811"),
+        Err(ParseErrorKind::IncorrectColumnCount {
+            expected: 7,
+            found: 6
+        })
+    ));
+    assert_eq!(
+        parse_line("apnic|AU|ipv4|1.0.0.0|256|20110811|lost"),
+        Err(ParseErrorKind::InvalidField {
+            field: "status".to_string(),
+            value: "lost".to_string()
+        })
+    );
+    assert_eq!(
+        parse_line("nic|AU|ipv4|1.0.0.0|256|20110811|assigned"),
+        Err(ParseErrorKind::InvalidField {
+            field: "registry".to_string(),
+            value: "nic".to_string()
+        })
+    );
+    assert!(matches!(
+        parse_line("apnic|AU|ipv4|255.255.255.0|512|20110811|assigned"),
+        Err(ParseErrorKind::InvalidField { .. })
+    ));
+    assert!(matches!(
+        parse_line("apnic|Aus|ipv4|1.0.0.0|256|20110811|assigned"),
+        Err(ParseErrorKind::InvalidCountryCode { .. })
+    ));
+    assert!(matches!(
+        parse_line("apnic|AU|ipv6|2001:200::|129|20110811|assigned"),
+        Err(ParseErrorKind::InvalidNetwork { .. })
+    ));
+}
+
+#[test]
+fn test_delegations_from_path() {
+    let delegations = RirDelegations::from_path("testdata/delegated-small-extended.txt").unwrap();
+
+    // A block of 2048 addresses is stored as a single /21.
+    let (network, info) = delegations.lookup("1.0.9.1".parse().unwrap()).unwrap();
+    assert_eq!(network, "1.0.8.0/21".parse::<IpNetwork>().unwrap());
+    assert_eq!(info.status, AllocationStatus::Available);
+    assert_eq!(info.country_code, "ZZ");
+
+    let (network, info) = delegations.lookup("2001:200::1".parse().unwrap()).unwrap();
+    assert_eq!(network, "2001:200::/35".parse::<IpNetwork>().unwrap());
+    assert_eq!(info.country_code, "JP");
+
+    assert_eq!(
+        delegations
+            .lookup_asn(Asn::new(13335))
+            .unwrap()
+            .country_code,
+        "AU"
+    );
+    assert!(delegations.lookup_asn(Asn::new(15169)).is_none());
+    assert!(delegations.lookup("8.8.8.8".parse().unwrap()).is_none());
+}
+
+#[test]
+fn test_delegations_strict_errors() {
+    let data = "2|apnic|20240101|2|19830613|20231231|+1000\n\
+                apnic|AU|ipv4|1.0.0.0|256|20110811|assigned\n\
+                apnic|AU|ipv4|not-an-ip|256|20110811|assigned\n";
+    let mut delegations = RirDelegations::new();
+    match delegations.load(data.as_bytes()) {
+        Err(Error::Parse {
+            line_number, kind, ..
+        }) => {
+            assert_eq!(line_number, 3);
+            assert_eq!(
+                kind,
+                ParseErrorKind::InvalidIpAddress {
+                    field: "start".to_string(),
+                    value: "not-an-ip".to_string()
+                }
+            );
+        }
+        _ => panic!("Expected a Parse error"),
+    }
+}
+
+#[test]
+fn test_map_with_registry_data() {
+    let map = Builder::new()
+        .from_path("testdata/testdata-small-ip2asn.tsv")
+        .unwrap()
+        .build()
+        .unwrap();
+    assert!(map
+        .lookup("1.1.1.1".parse().unwrap())
+        .unwrap()
+        .registry
+        .is_none());
+
+    let map = map.with_registry_data(
+        RirDelegations::from_path("testdata/delegated-small-extended.txt").unwrap(),
+    );
+
+    // iptoasn places 1.1.1.0/24 in the US, while APNIC registers it to AU.
+    let info = map.lookup("1.1.1.1".parse().unwrap()).unwrap();
+    assert_eq!(info.country_code, "US");
+    let registry = info.registry.unwrap();
+    assert_eq!(registry.registry, Registry::Apnic);
+    assert_eq!(registry.country_code, "AU");
+    assert_eq!(registry.status, AllocationStatus::Assigned);
+    assert!(info.country_mismatch());
+
+    let owned = map.lookup_owned("1.1.1.1".parse().unwrap()).unwrap();
+    assert_eq!(owned.registry.as_ref(), Some(registry));
+    assert!(owned.country_mismatch());
+
+    // No delegation covers this address.
+    let info = map.lookup("8.8.8.8".parse().unwrap()).unwrap();
+    assert!(info.registry.is_none());
+    assert!(!info.country_mismatch());
+
+    assert_eq!(
+        map.registry_data()
+            .lookup_asn(Asn::new(13335))
+            .unwrap()
+            .registry,
+        Registry::Apnic
+    );
+}
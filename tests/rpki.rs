@@ -0,0 +1,183 @@
+//! Integration tests for RPKI route origin validation.
+#![cfg(feature = "rpki")]
+
+use ip2asn::asn::Asn;
+use ip2asn::rpki::{parse_csv_line, ValidationState, VrpTable};
+use ip2asn::{Builder, Error, IpAsnMap, ParseErrorKind};
+use ip_network::IpNetwork;
+use rstest::rstest;
+use std::net::IpAddr;
+
+fn map_with_vrps(path: &str) -> IpAsnMap {
+    Builder::new()
+        .from_path("testdata/testdata-small-ip2asn.tsv")
+        .unwrap()
+        .build()
+        .unwrap()
+        .with_vrps(VrpTable::from_path(path).unwrap())
+}
+
+#[test]
+fn test_parse_csv_line() {
+    let vrp = parse_csv_line("AS13335,1.1.1.0/24,24,apnic").unwrap();
+    assert_eq!(vrp.asn, 13335);
+    assert_eq!(vrp.network, "1.1.1.0/24".parse::<IpNetwork>().unwrap());
+    assert_eq!(vrp.max_length, 24);
+
+    assert_eq!(
+        parse_csv_line("AS13335,1.1.1.0/24,23"),
+        Err(ParseErrorKind::InvalidField {
+            field: "max_length".to_string(),
+            value: "23".to_string()
+        })
+    );
+    assert!(matches!(
+        parse_csv_line("AS13335,1.1.1.0/24,33"),
+        Err(ParseErrorKind::InvalidField { .. })
+    ));
+    assert!(matches!(
+        parse_csv_line("ASX,1.1.1.0/24,24"),
+        Err(ParseErrorKind::InvalidAsnNumber { .. })
+    ));
+    assert!(matches!(
+        parse_csv_line("AS13335,1.1.1.1/24,24"),
+        Err(ParseErrorKind::InvalidNetwork { .. })
+    ));
+    assert!(matches!(
+        parse_csv_line("AS13335,1.1.1.0/24"),
+        Err(ParseErrorKind::IncorrectColumnCount {
+            expected: 3,
+            found: 2
+        })
+    ));
+}
+
+#[rstest]
+#[case("testdata/vrps-small.csv")]
+#[case("testdata/vrps-small.json")]
+fn test_validate_origin(#[case] path: &str) {
+    let map = map_with_vrps(path);
+    let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+
+    assert_eq!(map.validate_origin(ip("1.1.1.1")), ValidationState::Valid);
+    assert_eq!(map.validate_origin(ip("8.8.8.8")), ValidationState::Valid);
+    // The map's /20 is covered by a /16 VRP allowing up to /24.
+    assert_eq!(
+        map.validate_origin(ip("202.135.1.1")),
+        ValidationState::Valid
+    );
+    assert_eq!(
+        map.validate_origin(ip("2804:1638:4030::1")),
+        ValidationState::Valid
+    );
+    // The VRP authorizes a different origin.
+    assert_eq!(
+        map.validate_origin(ip("12.104.218.1")),
+        ValidationState::Invalid
+    );
+    // AS0 VRPs never match.
+    assert_eq!(
+        map.validate_origin(ip("23.140.152.1")),
+        ValidationState::Invalid
+    );
+    // The map's /24 is longer than the VRP's maximum length of 22.
+    assert_eq!(
+        map.validate_origin(ip("185.87.121.1")),
+        ValidationState::Invalid
+    );
+    // No VRP covers the route.
+    assert_eq!(
+        map.validate_origin(ip("64.72.199.1")),
+        ValidationState::NotFound
+    );
+    // No route in the map.
+    assert_eq!(
+        map.validate_origin(ip("192.0.2.1")),
+        ValidationState::NotFound
+    );
+}
+
+#[test]
+fn test_validate_origin_prefix() {
+    let map = map_with_vrps("testdata/vrps-small.csv");
+    let net = |s: &str| s.parse::<IpNetwork>().unwrap();
+
+    assert_eq!(
+        map.validate_origin(net("1.1.1.0/24")),
+        ValidationState::Valid
+    );
+    assert_eq!(
+        map.validate_origin(net("1.1.1.128/25")),
+        ValidationState::Valid
+    );
+    // No single network in the map contains the whole /16.
+    assert_eq!(
+        map.validate_origin(net("202.135.0.0/16")),
+        ValidationState::NotFound
+    );
+}
+
+#[test]
+fn test_vrp_table_asn_notations() {
+    let json = r#"{"roas": [
+        {"asn": "as13335", "prefix": "1.0.0.0/24", "maxLength": 24},
+        {"asn": "AS1.10", "prefix": "1.0.1.0/24", "maxLength": 24}
+    ]}"#;
+    let mut vrps = VrpTable::new();
+    vrps.load(json.as_bytes()).unwrap();
+    vrps.load("ASN,IP Prefix,Max Length\n1.10,1.0.2.0/24,24\n".as_bytes())
+        .unwrap();
+
+    let route = |s: &str| s.parse::<IpNetwork>().unwrap();
+    assert_eq!(
+        vrps.validate(route("1.0.0.0/24"), Asn::new(13335)),
+        ValidationState::Valid
+    );
+    assert_eq!(
+        vrps.validate(route("1.0.1.0/24"), Asn::new(65546)),
+        ValidationState::Valid
+    );
+    assert_eq!(
+        vrps.validate(route("1.0.2.0/24"), Asn::new(65546)),
+        ValidationState::Valid
+    );
+    assert_eq!(
+        vrps.validate(route("1.0.2.0/24"), Asn::new(10)),
+        ValidationState::Invalid
+    );
+}
+
+#[test]
+fn test_vrp_table_errors() {
+    let mut vrps = VrpTable::new();
+    match vrps.load("ASN,IP Prefix,Max Length\nAS1,1.0.0.0/24,24\nAS2,1.0.0.0/33,33\n".as_bytes()) {
+        Err(Error::Parse {
+            line_number, kind, ..
+        }) => {
+            assert_eq!(line_number, 3);
+            assert!(matches!(kind, ParseErrorKind::InvalidNetwork { .. }));
+        }
+        other => panic!("Expected a Parse error, got {other:?}"),
+    }
+
+    let json = r#"{"roas": [{"asn": "AS1", "prefix": "1.0.0.0/24", "maxLength": 16}]}"#;
+    match VrpTable::new().load(json.as_bytes()) {
+        Err(Error::Parse {
+            line_number,
+            line_content,
+            kind,
+        }) => {
+            assert_eq!(line_number, 1);
+            assert_eq!(line_content, "AS1,1.0.0.0/24,16");
+            assert!(matches!(kind, ParseErrorKind::InvalidField { .. }));
+        }
+        other => panic!("Expected a Parse error, got {other:?}"),
+    }
+
+    let result = VrpTable::new().load("{\"roas\": [".as_bytes());
+    assert!(matches!(result, Err(Error::Json(_))));
+
+    let mut vrps = VrpTable::new();
+    vrps.load("  \n".as_bytes()).unwrap();
+    assert!(vrps.is_empty());
+}
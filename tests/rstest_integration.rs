@@ -1,3 +1,5 @@
+#![cfg(feature = "std")]
+
 use ip2asn::Builder;
 use ip2asn::IpAsnMap;
 use rstest::fixture;
@@ -0,0 +1,98 @@
+//! Integration tests for saving and loading map snapshots.
+//!
+//! Apart from the round trip, these tests build without the `std` feature,
+//! and are run by `just check-no-std` to exercise lookups on `alloc` alone.
+#![cfg(feature = "serde")]
+
+use ip2asn::network::Network;
+use ip2asn::IpAsnMap;
+
+const SNAPSHOT: &str = r#"{
+    "organizations": ["CLOUDFLARENET", "GOOGLE - Google LLC"],
+    "networks": [
+        ["1.0.0.0/16", 13335, "AU", 0],
+        ["1.0.4.0/24", 15169, "US", 1],
+        ["2001:db8::/32", 15169, "ZZ", 1]
+    ]
+}"#;
+
+#[test]
+fn test_snapshot_lookup() {
+    let map: IpAsnMap = serde_json::from_str(SNAPSHOT).unwrap();
+
+    let info = map.lookup("1.0.4.1".parse().unwrap()).unwrap();
+    assert_eq!(info.network, "1.0.4.0/24".parse::<Network>().unwrap());
+    assert_eq!(info.asn, 15169);
+    assert_eq!(info.country_code, "US");
+    assert_eq!(info.organization, "GOOGLE - Google LLC");
+    assert_eq!(info.organization_parts.handle, Some("GOOGLE"));
+
+    let info = map.lookup("1.0.5.1".parse().unwrap()).unwrap();
+    assert_eq!(info.network, "1.0.0.0/16".parse::<Network>().unwrap());
+    assert_eq!(info.asn, 13335);
+
+    let info = map.lookup_owned("2001:db8::1".parse().unwrap()).unwrap();
+    assert_eq!(
+        info.to_string(),
+        "AS15169 GOOGLE - Google LLC (ZZ) in 2001:db8::/32"
+    );
+
+    assert!(map.lookup("1.1.0.0".parse().unwrap()).is_none());
+    assert!(map.lookup("::1".parse().unwrap()).is_none());
+}
+
+#[test]
+fn test_snapshot_serializes_every_network() {
+    let map: IpAsnMap = serde_json::from_str(SNAPSHOT).unwrap();
+    let reloaded: IpAsnMap = serde_json::from_value(serde_json::to_value(&map).unwrap()).unwrap();
+    assert_eq!(
+        serde_json::to_value(&reloaded).unwrap(),
+        serde_json::from_str::<serde_json::Value>(SNAPSHOT).unwrap()
+    );
+}
+
+#[test]
+fn test_snapshot_rejects_invalid_networks() {
+    for (networks, message) in [
+        (
+            r#"[["1.0.0.0/24", 13335, "AU", 1]]"#,
+            "organization index out of range: 1",
+        ),
+        (
+            r#"[["1.0.0.0/24", 13335, "AUS", 0]]"#,
+            "invalid country code: AUS",
+        ),
+        (
+            r#"[["1.0.0.1/24", 13335, "AU", 0]]"#,
+            "invalid network: 1.0.0.1/24",
+        ),
+    ] {
+        let snapshot = format!(r#"{{"organizations": ["CLOUDFLARENET"], "networks": {networks}}}"#);
+        let err = serde_json::from_str::<IpAsnMap>(&snapshot).unwrap_err();
+        assert!(err.to_string().starts_with(message), "{err}");
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_snapshot_round_trip() {
+    let map = ip2asn::Builder::new()
+        .from_path("testdata/testdata-small-ip2asn.tsv")
+        .unwrap()
+        .strict()
+        .build()
+        .unwrap();
+    let snapshot = serde_json::to_string(&map).unwrap();
+    let reloaded: IpAsnMap = serde_json::from_str(&snapshot).unwrap();
+
+    let mut original = Vec::new();
+    map.write_tsv(&mut original).unwrap();
+    let mut output = Vec::new();
+    reloaded.write_tsv(&mut output).unwrap();
+    assert_eq!(output, original);
+
+    for ip in ["1.0.0.1", "38.103.145.1", "2804:2f8c::1"] {
+        let ip = ip.parse().unwrap();
+        assert_eq!(reloaded.lookup_owned(ip), map.lookup_owned(ip), "{ip}");
+    }
+}
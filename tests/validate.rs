@@ -0,0 +1,233 @@
+//! Integration tests for dataset validation.
+#![cfg(feature = "std")]
+
+use ip2asn::validate::{IssueKind, ValidationReport};
+use ip2asn::{Builder, CancellationToken, Error, InputFormat};
+use rstest::rstest;
+
+fn validate(data: &str) -> ValidationReport {
+    Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .validate()
+        .unwrap()
+}
+
+#[test]
+fn test_validate_fixture() {
+    let report = Builder::new()
+        .from_path("testdata/testdata-small-ip2asn.tsv")
+        .unwrap()
+        .validate()
+        .unwrap();
+    assert_eq!(report.lines_read, 22);
+    assert_eq!(report.records_checked, 22);
+    // The fixture is a sample of unrelated ranges in no particular order, so
+    // sorting is the only problem with it.
+    assert_eq!(
+        report.issues.keys().collect::<Vec<_>>(),
+        vec![&IssueKind::Unsorted]
+    );
+    assert_eq!(report.issue_count(), 8);
+}
+
+#[test]
+fn test_validate_clean() {
+    let data = "\
+1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET
+1.0.1.0\t1.0.3.255\t0\tNone\tNot routed
+8.8.8.0\t8.8.8.255\t15169\tUS\tGOOGLE
+2001:200::\t2001:200:ffff:ffff:ffff:ffff:ffff:ffff\t2500\tJP\tWIDE
+";
+    let report = validate(data);
+    assert_eq!(report.records_checked, 4);
+    assert!(report.is_clean(), "{:?}", report.issues);
+    assert_eq!(report.issue_count(), 0);
+}
+
+#[rstest]
+#[case::malformed("1.0.0.0\t1.0.0.255\tnot-a-number\tUS\tBAD", IssueKind::Malformed)]
+#[case::unsorted(
+    "1.0.1.0\t1.0.1.255\t13335\tUS\tA\n1.0.0.0\t1.0.0.255\t15169\tUS\tB",
+    IssueKind::Unsorted
+)]
+#[case::overlap(
+    "1.0.0.0\t1.0.0.255\t13335\tUS\tA\n1.0.0.128\t1.0.1.255\t15169\tUS\tB",
+    IssueKind::Overlap
+)]
+#[case::duplicate(
+    "1.0.0.0\t1.0.0.255\t13335\tUS\tA\n1.0.0.0\t1.0.0.255\t13335\tUS\tA",
+    IssueKind::Duplicate
+)]
+#[case::gap(
+    "1.0.0.0\t1.0.0.255\t13335\tUS\tA\n1.0.2.0\t1.0.2.255\t13335\tUS\tA",
+    IssueKind::Gap
+)]
+#[case::gap_around_other_asn(
+    "1.0.0.0\t1.0.0.255\t13335\tUS\tA\n1.0.1.0\t1.0.1.255\t15169\tUS\tB\n\
+     1.0.3.0\t1.0.3.255\t13335\tUS\tA",
+    IssueKind::Gap
+)]
+#[case::bogon_v4("192.168.0.0\t192.168.0.255\t13335\tUS\tA", IssueKind::BogonWithAsn)]
+#[case::bogon_v6("2001:db8::\t2001:db8::ffff\t13335\tUS\tA", IssueKind::BogonWithAsn)]
+#[case::bogon_ietf_v4("192.0.0.11\t192.0.0.11\t13335\tUS\tA", IssueKind::BogonWithAsn)]
+#[case::bogon_6to4_relay("192.88.99.0\t192.88.99.255\t13335\tUS\tA", IssueKind::BogonWithAsn)]
+#[case::bogon_v6_benchmarking("2001:2::\t2001:2::ffff\t13335\tUS\tA", IssueKind::BogonWithAsn)]
+#[case::private_asn("1.0.0.0\t1.0.0.255\t4200000001\tUS\tA", IssueKind::PrivateAsn)]
+#[case::unknown_country("1.0.0.0\t1.0.0.255\t13335\tXK\tA", IssueKind::UnknownCountry)]
+fn test_validate_finds_issue(#[case] data: &str, #[case] kind: IssueKind) {
+    let report = validate(data);
+    assert_eq!(report.issue_count(), 1, "{:?}", report.issues);
+    assert_eq!(report.count(kind), 1);
+}
+
+#[rstest]
+#[case::adjacent_same_asn("1.0.0.0\t1.0.0.255\t13335\tUS\tA\n1.0.1.0\t1.0.1.255\t13335\tUS\tA")]
+#[case::gap_other_asn("1.0.0.0\t1.0.0.255\t13335\tUS\tA\n1.0.2.0\t1.0.2.255\t15169\tUS\tB")]
+#[case::gap_unrouted(
+    "1.0.0.0\t1.0.0.255\t0\tNone\tNot routed\n1.0.2.0\t1.0.2.255\t0\tNone\tNot routed"
+)]
+#[case::gap_filled_by_other_asn(
+    "1.0.0.0\t1.0.0.255\t13335\tUS\tA\n1.0.1.0\t1.0.1.255\t15169\tUS\tB\n\
+     1.0.2.0\t1.0.2.255\t13335\tUS\tA"
+)]
+#[case::bogon_unrouted("10.0.0.0\t10.255.255.255\t0\tNone\tNot routed")]
+#[case::as112("192.175.48.0\t192.175.48.255\t112\tUS\tAS112")]
+#[case::pcp_anycast("192.0.0.9\t192.0.0.9\t13335\tUS\tA")]
+#[case::as112_v6("2001:4:112::\t2001:4:112:ffff:ffff:ffff:ffff:ffff\t112\tUS\tAS112")]
+#[case::unknown_country_code("1.0.0.0\t1.0.0.255\t13335\tZZ\tA")]
+#[case::families("1.0.0.0\t1.0.0.255\t13335\tUS\tA\n2001:200::\t2001:200::ffff\t13335\tUS\tA")]
+fn test_validate_accepts(#[case] data: &str) {
+    let report = validate(data);
+    assert!(report.is_clean(), "{:?}", report.issues);
+}
+
+#[test]
+fn test_validate_skips_gaps_in_unsorted_input() {
+    let data = "\
+1.0.0.0\t1.0.0.255\t13335\tUS\tA
+1.0.2.0\t1.0.2.255\t13335\tUS\tA
+1.0.1.0\t1.0.1.255\t0\tNone\tNot routed
+";
+    let report = validate(data);
+    assert_eq!(report.count(IssueKind::Unsorted), 1);
+    assert_eq!(report.count(IssueKind::Gap), 0);
+}
+
+#[test]
+fn test_validate_gap_details() {
+    let data = "\
+1.0.0.0\t1.0.0.255\t13335\tUS\tA
+1.0.1.0\t1.0.1.255\t0\tNone\tNot routed
+1.0.3.0\t1.0.3.255\t13335\tUS\tA
+";
+    let report = validate(data);
+    let gap = &report.issues[&IssueKind::Gap].samples[0];
+    assert_eq!(gap.line_number, 3);
+    assert_eq!(gap.related_line, Some(1));
+    assert_eq!(gap.line_content, "1.0.3.0\t1.0.3.255\t13335\tUS\tA");
+    assert_eq!(gap.message, "gap before 1.0.3.0 within AS13335");
+}
+
+#[test]
+fn test_validate_skips_gaps_in_sparse_formats() {
+    let report = Builder::new()
+        .with_source("1.0.0.0\t24\t13335\n1.0.2.0\t24\t13335\n".as_bytes())
+        .unwrap()
+        .format(InputFormat::Pfx2As)
+        .validate()
+        .unwrap();
+    assert_eq!(report.records_checked, 2);
+    assert!(report.is_clean(), "{:?}", report.issues);
+}
+
+#[test]
+fn test_validate_ipv6_before_ipv4_is_unsorted() {
+    let report =
+        validate("2001:200::\t2001:200::ffff\t2500\tJP\tWIDE\n1.0.0.0\t1.0.0.255\t13335\tUS\tA");
+    assert_eq!(report.count(IssueKind::Unsorted), 1);
+}
+
+#[test]
+fn test_validate_issue_details() {
+    let data = "\
+1.0.0.0\t1.0.0.255\t13335\tUS\tA
+1.0.0.128\t1.0.0.191\t64496\tAU\tNESTED
+";
+    let report = validate(data);
+    assert_eq!(report.records_checked, 2);
+
+    let overlap = &report.issues[&IssueKind::Overlap].samples[0];
+    assert_eq!(overlap.line_number, 2);
+    assert_eq!(overlap.related_line, Some(1));
+    assert_eq!(
+        overlap.line_content,
+        "1.0.0.128\t1.0.0.191\t64496\tAU\tNESTED"
+    );
+    assert_eq!(
+        overlap.message,
+        "1.0.0.128-1.0.0.191 overlaps 1.0.0.0-1.0.0.255 from line 1"
+    );
+    assert_eq!(
+        overlap.to_string(),
+        "overlap on line 2: 1.0.0.128-1.0.0.191 overlaps 1.0.0.0-1.0.0.255 from line 1 \
+         in line: \"1.0.0.128\t1.0.0.191\t64496\tAU\tNESTED\""
+    );
+
+    // Reserved for documentation, but not for private use.
+    assert_eq!(report.count(IssueKind::PrivateAsn), 0);
+}
+
+#[test]
+fn test_validate_samples_are_limited() {
+    let data: String = (0..10)
+        .map(|i| format!("1.0.{i}.0\t1.0.{i}.255\t64512\tUS\tPRIVATE\n"))
+        .collect();
+    let report = Builder::new()
+        .with_source(data.as_bytes())
+        .unwrap()
+        .warning_samples(3)
+        .validate()
+        .unwrap();
+    let summary = &report.issues[&IssueKind::PrivateAsn];
+    assert_eq!(summary.count, 10);
+    assert_eq!(summary.samples.len(), 3);
+    assert_eq!(summary.samples[2].line_number, 3);
+}
+
+#[test]
+fn test_validate_strict_mode_reports_malformed_lines() {
+    let report = Builder::new()
+        .with_source("garbage\n1.0.0.0\t1.0.0.255\t13335\tUS\tA".as_bytes())
+        .unwrap()
+        .strict()
+        .validate()
+        .unwrap();
+    assert_eq!(report.count(IssueKind::Malformed), 1);
+    assert_eq!(report.records_checked, 1);
+}
+
+#[test]
+fn test_validate_cancelled() {
+    let token = CancellationToken::new();
+    token.cancel();
+    let result = Builder::new()
+        .with_source("1.0.0.0\t1.0.0.255\t13335\tUS\tA".as_bytes())
+        .unwrap()
+        .cancellation_token(token)
+        .validate();
+    assert!(matches!(result, Err(Error::Cancelled)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_validation_report_serializes() {
+    let report = validate("1.0.0.0\t1.0.0.255\t64512\tUS\tPRIVATE");
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["records_checked"], 1);
+    assert_eq!(json["issues"]["private_asn"]["count"], 1);
+    assert_eq!(
+        json["issues"]["private_asn"]["samples"][0]["kind"],
+        "private_asn"
+    );
+}